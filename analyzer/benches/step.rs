@@ -0,0 +1,87 @@
+//! Simulation of a program on [CompiledNode] and on [RegisterNode]:
+//! `cargo bench -p lustre_analyzer`
+
+use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
+use lustre_analyzer::{
+    ast::literal::Value,
+    interpreter::{compiled_node::CompiledNode, register_node::RegisterNode},
+    parser::{lustre_parser::lustre_parse, span::Span},
+};
+
+const PROGRAM: &str = "node integrator(x: float, dt: float) returns (y: float);
+let
+    y = 0.0 fby (y + x * dt);
+tel
+
+node controller(target: float, measure: float) returns (command: float);
+var
+    error: float;
+let
+    error = target - measure;
+    command = clamp(2.0 * error + 0.5 * integrator(error, 0.01), -10.0, 10.0);
+tel
+
+node plant(command: float) returns (position: float);
+let
+    position = integrator(integrator(command, 0.01), 0.01);
+tel
+
+node main(target: float, enabled: bool) returns (position: float, ticks: int);
+var
+    command: float;
+let
+    command = if enabled then controller(target, 0.0 fby position) else 0.0;
+    position = plant(command);
+    ticks = (if enabled then 1 else 0) + (0 fby ticks);
+tel
+";
+
+const STEPS: usize = 10_000;
+
+fn inputs() -> Vec<Vec<Value>> {
+    (0..STEPS)
+        .map(|i| vec![Value::Float((i / 100) as f64), Value::Bool(i % 7 != 0)])
+        .collect()
+}
+
+fn compile() -> (CompiledNode, RegisterNode) {
+    let ast = lustre_parse(PROGRAM).unwrap();
+    // Spans are compared by their fragment
+    let main = Span::new(0, 0, "main".to_string());
+    (
+        ast.compile(main.clone()),
+        ast.compile_registers(main).unwrap(),
+    )
+}
+
+fn step(c: &mut Criterion) {
+    let (compiled, registers) = compile();
+    let inputs = inputs();
+    let mut group = c.benchmark_group(format!("{STEPS} steps"));
+    group.bench_function("CompiledNode", |b| {
+        b.iter_batched(
+            || (compiled.clone(), inputs.clone()),
+            |(mut node, inputs)| {
+                for input in inputs {
+                    node.step(input).unwrap();
+                }
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function("RegisterNode", |b| {
+        b.iter_batched(
+            || (registers.clone(), inputs.clone()),
+            |(mut node, inputs)| {
+                for input in inputs {
+                    node.step(input).unwrap();
+                }
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, step);
+criterion_main!(benches);
@@ -7,10 +7,16 @@ pub mod visitor;
 
 pub mod ast;
 pub mod ast_types;
+pub mod hover;
 pub mod node;
+pub mod prelude;
 
+pub mod automaton;
 pub mod binop;
+pub mod builtin;
+pub mod contract;
 pub mod expression;
 pub mod ftag;
+pub mod integer;
 pub mod literal;
 pub mod unary_op;
@@ -1,8 +1,9 @@
 use crate::{
     ast::{
-        ast_types::AstTypes, expression::Expr, highlight::DocumentHighlightVisitor, node::Node,
-        semantic_token::SemanticTokenVisitor, visitor::Visitor,
+        ast_types::AstTypes, expression::Expr, highlight::DocumentHighlightVisitor,
+        integer::Overflow, node::Node, semantic_token::SemanticTokenVisitor, visitor::Visitor,
     },
+    checker::function_type::FunctionType,
     parser::span::Span,
 };
 use lsp_types::{DocumentHighlight, Position, Range, SemanticToken, TextEdit};
@@ -10,14 +11,27 @@ use lsp_types::{DocumentHighlight, Position, Range, SemanticToken, TextEdit};
 #[derive(Clone, Debug)]
 pub struct Ast {
     pub(crate) nodes: Vec<Node>,
+    /// Behavior of integer overflows, set with `#![overflow(...)]`
+    pub(crate) overflow: Overflow,
+    /// Whether the prelude is loaded, disabled with `#![no_prelude]`
+    pub(crate) prelude: bool,
+    /// Number of prelude nodes at the start of `nodes`
+    pub(crate) prelude_len: usize,
     pub types: AstTypes,
 }
 
 impl std::fmt::Display for Ast {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for (i, node) in self.nodes.iter().enumerate() {
+        if self.overflow != Overflow::default() {
+            writeln!(f, "#![overflow({})]\n", self.overflow)?;
+        }
+        if !self.prelude {
+            writeln!(f, "#![no_prelude]\n")?;
+        }
+        let nodes = self.user_nodes();
+        for (i, node) in nodes.iter().enumerate() {
             write!(f, "{node}")?;
-            if i != self.nodes.len() - 1 {
+            if i != nodes.len() - 1 {
                 write!(f, "\n\n")?;
             } else {
                 writeln!(f)?;
@@ -37,6 +51,9 @@ impl Ast {
     pub fn new() -> Self {
         Self {
             nodes: vec![],
+            overflow: Overflow::default(),
+            prelude: true,
+            prelude_len: 0,
             types: AstTypes::new(),
         }
     }
@@ -49,6 +66,14 @@ impl Ast {
             None => false,
         }
     }
+    /// Inputs and outputs of the node `name` with their types
+    pub fn function_type(&self, name: &str) -> Option<FunctionType> {
+        let node = self
+            .nodes
+            .iter()
+            .find(|node| node.name.fragment() == name)?;
+        Some(FunctionType::get_function_type(node).0)
+    }
     pub fn push_expr(&mut self, name: Span, expr: Expr) {
         if let Some(node) = self.nodes.last_mut() {
             node.push_expr(name, expr)
@@ -0,0 +1,521 @@
+use crate::{
+    ast::{
+        ast::Ast,
+        binop::BinOp,
+        builtin::{CONDACT, RESTART},
+        expression::Expr,
+        literal::Value,
+        node::Node,
+        unary_op::UnaryOp,
+    },
+    parser::{
+        span::{Ident, Span},
+        var_type::{InnerVarType, VarType},
+    },
+};
+
+/// State machine in the body of a node, the first state is the initial one
+///
+/// ```text
+/// automaton
+///     initial state Off
+///         let
+///             y = 0;
+///         tel
+///         until on restart On;
+///     state On
+///         unless off resume Off;
+///         let
+///             y = 0 -> pre y + 1;
+///         tel
+/// end;
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Automaton {
+    pub(crate) span_automaton: Span,
+    pub(crate) states: Vec<State>,
+    pub(crate) span_end: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct State {
+    /// `initial state` or `state`
+    pub(crate) span_state: Span,
+    pub(crate) name: Ident,
+    /// Strong transitions, checked before the state is active
+    pub(crate) unless: Vec<Transition>,
+    pub(crate) span_let: Span,
+    /// Equations of the variables while the state is active
+    pub(crate) let_bindings: Vec<(Ident, Expr)>,
+    pub(crate) span_tel: Span,
+    /// Weak transitions, taken at the end of an instant where the state is active
+    pub(crate) until: Vec<Transition>,
+}
+
+/// `unless cond restart Target;` or `until cond resume Target;`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Transition {
+    pub(crate) span_keyword: Span,
+    pub(crate) cond: Expr,
+    /// `restart` or `resume`
+    pub(crate) span_kind: Span,
+    /// Memories of the target state are reset when entering it
+    pub(crate) restart: bool,
+    pub(crate) target: Ident,
+}
+
+impl Automaton {
+    /// Variables defined by the states, in order of first definition
+    pub(crate) fn defined(&self) -> Vec<&Ident> {
+        let mut defined: Vec<&Ident> = vec![];
+        for state in self.states.iter() {
+            for (name, _) in state.let_bindings.iter() {
+                if !defined.contains(&name) {
+                    defined.push(name);
+                }
+            }
+        }
+        defined
+    }
+    /// Transition conditions and equations of every state
+    pub(crate) fn exprs(&self) -> impl Iterator<Item = &Expr> {
+        self.states.iter().flat_map(|state| {
+            state
+                .unless
+                .iter()
+                .map(|transition| &transition.cond)
+                .chain(state.let_bindings.iter().map(|(_, expr)| expr))
+                .chain(state.until.iter().map(|transition| &transition.cond))
+        })
+    }
+    pub(crate) fn state(&self, name: &Ident) -> Option<usize> {
+        self.states.iter().position(|state| &state.name == name)
+    }
+}
+
+impl std::fmt::Display for Transition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = if self.restart { "restart" } else { "resume" };
+        write!(
+            f,
+            "{} {} {kind} {};",
+            self.span_keyword, self.cond, self.target
+        )
+    }
+}
+
+impl std::fmt::Display for Automaton {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "\tautomaton")?;
+        for (i, state) in self.states.iter().enumerate() {
+            let initial = if i == 0 { "initial " } else { "" };
+            writeln!(f, "\t\t{initial}state {}", state.name)?;
+            for transition in state.unless.iter() {
+                writeln!(f, "\t\t\t{transition}")?;
+            }
+            writeln!(f, "\t\t\tlet")?;
+            for (name, expr) in state.let_bindings.iter() {
+                writeln!(f, "\t\t\t\t{name} = {expr};")?;
+            }
+            writeln!(f, "\t\t\ttel")?;
+            for transition in state.until.iter() {
+                writeln!(f, "\t\t\t{transition}")?;
+            }
+        }
+        write!(f, "\tend;")
+    }
+}
+
+impl Node {
+    /// Same node where the automata are replaced by equations, the active
+    /// state is selected with `if` and remembered with `pre`.
+    ///
+    /// In the equations of a state, `->` is reset when the state is entered
+    /// by a `restart` transition and `pre x` is the value of `x` the last
+    /// time the state was active. The nodes called in a state are only
+    /// stepped while it is active, and restarted with it. Transition
+    /// conditions are evaluated at every instant.
+    pub(crate) fn lower_automata(&self, ast: &Ast) -> Node {
+        let mut node = self.clone();
+        node.automata = vec![];
+        for (k, automaton) in self.automata.iter().enumerate() {
+            let mut lowering = Lowering {
+                ast,
+                node: self,
+                prefix: format!("automaton{k}#"),
+                span: automaton.span_automaton.clone(),
+                equations: vec![],
+                vars: vec![],
+            };
+            lowering.lower(automaton);
+            node.let_bindings.extend(lowering.equations);
+            node.vars.extend(lowering.vars);
+        }
+        node
+    }
+}
+
+struct Lowering<'a> {
+    ast: &'a Ast,
+    node: &'a Node,
+    /// Fresh variables can not be written by the user
+    prefix: String,
+    span: Span,
+    equations: Vec<(Ident, Expr)>,
+    vars: Vec<(Ident, VarType)>,
+}
+
+impl Lowering<'_> {
+    fn fresh(&mut self, name: String, inner: InnerVarType) -> Ident {
+        let ident = Span::new(0, 0, format!("{}{name}", self.prefix));
+        let t = VarType {
+            inner,
+            initialized: true,
+        };
+        self.vars.push((ident.clone(), t));
+        ident
+    }
+    fn define(&mut self, name: &Ident, expr: Expr) {
+        self.equations.push((name.clone(), expr));
+    }
+    fn bin_op(&self, lhs: Expr, op: BinOp, rhs: Expr) -> Expr {
+        Expr::BinOp {
+            lhs: Box::new(lhs),
+            op,
+            span_op: self.span.clone(),
+            rhs: Box::new(rhs),
+        }
+    }
+    fn unary_op(&self, op: UnaryOp, rhs: Expr) -> Expr {
+        Expr::UnaryOp {
+            op,
+            span_op: self.span.clone(),
+            rhs: Box::new(rhs),
+        }
+    }
+    fn ite(cond: Expr, yes: Expr, no: Expr) -> Expr {
+        Expr::If {
+            cond: Box::new(cond),
+            yes: Box::new(yes),
+            no: Box::new(no),
+        }
+    }
+    /// `if conds[0] then branches[0] else ... else branches[n - 1]`
+    fn select(conds: Vec<Expr>, branches: Vec<Expr>) -> Expr {
+        let mut branches = branches.into_iter().rev();
+        let last = branches.next().expect("an automaton has an initial state");
+        conds
+            .into_iter()
+            .rev()
+            .skip(1)
+            .zip(branches)
+            .fold(last, |no, (cond, yes)| Self::ite(cond, yes, no))
+    }
+    /// First transition whose condition holds, `stay` if none
+    fn transitions(
+        transitions: &[Transition],
+        stay: Expr,
+        value: impl Fn(&Transition) -> Expr,
+    ) -> Expr {
+        transitions.iter().rev().fold(stay, |no, transition| {
+            Self::ite(transition.cond.clone(), value(transition), no)
+        })
+    }
+
+    fn lower(&mut self, automaton: &Automaton) {
+        use InnerVarType::{Bool, Int};
+        let int = |i: usize| Expr::Lit(Value::Int(i as i64));
+        let bool = |b: bool| Expr::Lit(Value::Bool(b));
+        let var = |name: &Ident| Expr::Variable(name.clone());
+        // Unknown states are reported by the checker
+        let target = |transition: &Transition| int(automaton.state(&transition.target).unwrap());
+
+        let state_in = self.fresh("state_in".to_string(), Int);
+        let reset_in = self.fresh("reset_in".to_string(), Bool);
+        let state = self.fresh("state".to_string(), Int);
+        let reset = self.fresh("reset".to_string(), Bool);
+        let next = self.fresh("next".to_string(), Int);
+        let reset_next = self.fresh("reset_next".to_string(), Bool);
+
+        // State and reset chosen by the weak transitions of the previous instant
+        let pre_next = self.unary_op(UnaryOp::Pre, var(&next));
+        let e = self.bin_op(int(0), BinOp::Arrow, pre_next);
+        self.define(&state_in, e);
+        let pre_reset_next = self.unary_op(UnaryOp::Pre, var(&reset_next));
+        let e = self.bin_op(bool(false), BinOp::Arrow, pre_reset_next);
+        self.define(&reset_in, e);
+
+        // Strong transitions of the state we come from
+        let coming_from: Vec<Expr> = (0..automaton.states.len())
+            .map(|i| self.bin_op(var(&state_in), BinOp::Eq, int(i)))
+            .collect();
+        let e = Self::select(
+            coming_from.clone(),
+            automaton
+                .states
+                .iter()
+                .enumerate()
+                .map(|(i, s)| Self::transitions(&s.unless, int(i), target))
+                .collect(),
+        );
+        self.define(&state, e);
+        let e = Self::select(
+            coming_from,
+            automaton
+                .states
+                .iter()
+                .map(|s| Self::transitions(&s.unless, var(&reset_in), |t| bool(t.restart)))
+                .collect(),
+        );
+        self.define(&reset, e);
+
+        // `first` is true until the state is active for the first time since its reset
+        let mut active = vec![];
+        let mut first = vec![];
+        for (i, s) in automaton.states.iter().enumerate() {
+            let active_i = self.fresh(format!("{}.active", s.name), Bool);
+            let e = self.bin_op(var(&state), BinOp::Eq, int(i));
+            self.define(&active_i, e);
+            let first_i = self.fresh(format!("{}.first", s.name), Bool);
+            let reset_now = self.bin_op(var(&active_i), BinOp::And, var(&reset));
+            let not_active = self.unary_op(UnaryOp::Not, var(&active_i));
+            let waiting = self.bin_op(var(&first_i), BinOp::And, not_active);
+            let pre_waiting = self.unary_op(UnaryOp::Pre, waiting);
+            let e = Self::ite(
+                reset_now,
+                bool(true),
+                self.bin_op(bool(true), BinOp::Arrow, pre_waiting),
+            );
+            self.define(&first_i, e);
+            active.push(active_i);
+            first.push(first_i);
+        }
+
+        // Weak transitions of the active state
+        let is_active: Vec<Expr> = active.iter().map(var).collect();
+        let e = Self::select(
+            is_active.clone(),
+            automaton
+                .states
+                .iter()
+                .enumerate()
+                .map(|(i, s)| Self::transitions(&s.until, int(i), target))
+                .collect(),
+        );
+        self.define(&next, e);
+        let e = Self::select(
+            is_active.clone(),
+            automaton
+                .states
+                .iter()
+                .map(|s| Self::transitions(&s.until, bool(false), |t| bool(t.restart)))
+                .collect(),
+        );
+        self.define(&reset_next, e);
+
+        // Variables take the equation of the active state
+        let mut memories = vec![];
+        for name in automaton.defined() {
+            let branches = automaton
+                .states
+                .iter()
+                .enumerate()
+                .map(|(i, s)| {
+                    // A state without the equation is reported by the checker
+                    let (_, expr) = s.let_bindings.iter().find(|(n, _)| n == name).unwrap();
+                    let mut state = StateMemory {
+                        name: &s.name,
+                        active: &active[i],
+                        first: &first[i],
+                        memories: &mut memories,
+                    };
+                    self.local(&mut state, expr)
+                })
+                .collect();
+            let e = Self::select(is_active.clone(), branches);
+            self.define(name, e);
+        }
+    }
+
+    /// Expression of a state where `->`, `fby` and `pre` follow the state
+    fn local(&mut self, state: &mut StateMemory, expr: &Expr) -> Expr {
+        match expr {
+            Expr::BinOp {
+                lhs,
+                op: BinOp::Arrow,
+                rhs,
+                ..
+            } => Self::ite(
+                Expr::Variable(state.first.clone()),
+                self.local(state, lhs),
+                self.local(state, rhs),
+            ),
+            Expr::BinOp {
+                lhs,
+                op: BinOp::Fby,
+                rhs,
+                ..
+            } => Self::ite(
+                Expr::Variable(state.first.clone()),
+                self.local(state, lhs),
+                self.pre(state, rhs),
+            ),
+            Expr::UnaryOp {
+                op: UnaryOp::Pre,
+                rhs,
+                ..
+            } => self.pre(state, rhs),
+            Expr::FCall { name, .. } if self.is_node(name) => {
+                let call = map_children(expr, |e| self.local(state, e));
+                Expr::FCall {
+                    name: Span::new(0, 0, RESTART.to_string()),
+                    args: vec![
+                        Expr::Variable(state.active.clone()),
+                        Expr::Variable(state.first.clone()),
+                        call,
+                    ],
+                }
+            }
+            // The node of a `condact` is also stepped only while the state is active
+            Expr::FCall { name, args } if name.fragment() == CONDACT && !self.is_node(name) => {
+                let [
+                    cond,
+                    Expr::FCall {
+                        name: callee,
+                        args: callee_args,
+                    },
+                    defaults @ ..,
+                ] = &args[..]
+                else {
+                    return map_children(expr, |e| self.local(state, e));
+                };
+                let cond = self.local(state, cond);
+                let cond = self.bin_op(Expr::Variable(state.active.clone()), BinOp::And, cond);
+                let call = Expr::FCall {
+                    name: callee.clone(),
+                    args: callee_args.iter().map(|e| self.local(state, e)).collect(),
+                };
+                let defaults = defaults.iter().map(|e| self.local(state, e));
+                Expr::FCall {
+                    name: name.clone(),
+                    args: [cond, call].into_iter().chain(defaults).collect(),
+                }
+            }
+            expr => map_children(expr, |e| self.local(state, e)),
+        }
+    }
+    fn is_node(&self, name: &Ident) -> bool {
+        self.ast.nodes.iter().any(|node| &node.name == name)
+    }
+    /// `pre expr` in a state, the value of `expr` the last time the state was active
+    fn pre(&mut self, state: &mut StateMemory, expr: &Expr) -> Expr {
+        match expr {
+            Expr::Variable(name) => {
+                let Some(t) = self.type_of(name) else {
+                    return self.unary_op(UnaryOp::Pre, expr.clone());
+                };
+                let memory = match state
+                    .memories
+                    .iter()
+                    .find(|(s, v, _)| s == state.name && v == name)
+                {
+                    Some((_, _, memory)) => memory.clone(),
+                    None => {
+                        // `memory = if active then name else pre memory`
+                        let memory = self.fresh(format!("{}.pre.{name}", state.name), t);
+                        let pre_memory =
+                            self.unary_op(UnaryOp::Pre, Expr::Variable(memory.clone()));
+                        let e = Self::ite(
+                            Expr::Variable(state.active.clone()),
+                            expr.clone(),
+                            pre_memory,
+                        );
+                        self.define(&memory, e);
+                        state
+                            .memories
+                            .push((state.name.clone(), name.clone(), memory.clone()));
+                        memory
+                    }
+                };
+                self.unary_op(UnaryOp::Pre, Expr::Variable(memory))
+            }
+            Expr::Lit(_) => expr.clone(),
+            Expr::BinOp { op, .. } if !op.is_temporal() => {
+                map_children(expr, |e| self.pre(state, e))
+            }
+            Expr::UnaryOp { op, .. } if !op.is_temporal() => {
+                map_children(expr, |e| self.pre(state, e))
+            }
+            Expr::If { .. } | Expr::Array(_) | Expr::Tuple(_) => {
+                map_children(expr, |e| self.pre(state, e))
+            }
+            Expr::Index { expr, index } => Expr::Index {
+                expr: Box::new(self.pre(state, expr)),
+                index: index.clone(),
+            },
+            // Memories of node calls and temporal operators follow the node
+            expr => {
+                let expr = self.local(state, expr);
+                self.unary_op(UnaryOp::Pre, expr)
+            }
+        }
+    }
+    fn type_of(&self, name: &Ident) -> Option<InnerVarType> {
+        self.node
+            .inputs
+            .iter()
+            .chain(self.node.outputs.iter())
+            .chain(self.node.vars.iter())
+            .chain(self.vars.iter())
+            .find(|(v, _)| v == name)
+            .map(|(_, t)| t.inner.clone())
+    }
+}
+
+/// Memories shared by the equations of a state
+struct StateMemory<'a> {
+    name: &'a Ident,
+    active: &'a Ident,
+    first: &'a Ident,
+    /// State, variable and memory of its value when the state was last active
+    memories: &'a mut Vec<(Ident, Ident, Ident)>,
+}
+
+/// Same expression with `f` applied on its direct sub-expressions
+fn map_children(expr: &Expr, mut f: impl FnMut(&Expr) -> Expr) -> Expr {
+    let mut boxed = |e: &Expr| Box::new(f(e));
+    match expr {
+        Expr::BinOp {
+            lhs,
+            op,
+            span_op,
+            rhs,
+        } => Expr::BinOp {
+            lhs: boxed(lhs),
+            op: *op,
+            span_op: span_op.clone(),
+            rhs: boxed(rhs),
+        },
+        Expr::UnaryOp { op, span_op, rhs } => Expr::UnaryOp {
+            op: *op,
+            span_op: span_op.clone(),
+            rhs: boxed(rhs),
+        },
+        Expr::If { cond, yes, no } => Expr::If {
+            cond: boxed(cond),
+            yes: boxed(yes),
+            no: boxed(no),
+        },
+        Expr::Index { expr, index } => Expr::Index {
+            expr: boxed(expr),
+            index: boxed(index),
+        },
+        Expr::Array(exprs) => Expr::Array(exprs.iter().map(f).collect()),
+        Expr::Tuple(exprs) => Expr::Tuple(exprs.iter().map(f).collect()),
+        Expr::FCall { name, args } => Expr::FCall {
+            name: name.clone(),
+            args: args.iter().map(f).collect(),
+        },
+        Expr::Variable(_) | Expr::Lit(_) => expr.clone(),
+    }
+}
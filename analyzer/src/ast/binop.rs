@@ -1,9 +1,14 @@
 use crate::{
-    ast::{expression::Precedence, literal::Value},
+    ast::{
+        expression::Precedence,
+        integer::{IntKind, Overflow},
+        literal::Value,
+    },
     interpreter::instant::Instant,
 };
+use serde_derive::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, PartialEq, Eq, Copy)]
+#[derive(Clone, Debug, PartialEq, Eq, Copy, Serialize, Deserialize)]
 pub enum BinOp {
     Add,
     Sub,
@@ -16,6 +21,22 @@ pub enum BinOp {
     Or,
     And,
     Caret,
+    /// Bitwise and
+    Land,
+    /// Bitwise or
+    Lor,
+    /// Bitwise exclusive or
+    Lxor,
+    /// Shift to the left
+    Shl,
+    /// Arithmetic shift to the right
+    Shr,
+    /// `bit(x, i)`, bit number `i` of `x`
+    Bit,
+    /// `a since b`, `b` has been true and `a` has been true ever since
+    Since,
+    /// `a triggered b`, `b` has been true ever since `a` was last true
+    Triggered,
 }
 
 impl BinOp {
@@ -27,29 +48,56 @@ impl BinOp {
     // of temporal operator
     //
     // todo const generics on instant
-    pub fn apply(self, lhs: &Value, rhs: &Value, instant: Option<Instant>) -> Option<Value> {
+    //
+    // Integer arithmetic follows `overflow`, an integer literal (`int`)
+    // used with a sized integer takes the type of the sized integer.
+    pub fn apply(
+        self,
+        lhs: &Value,
+        rhs: &Value,
+        instant: Option<Instant>,
+        overflow: Overflow,
+    ) -> Option<Value> {
         use BinOp::*;
         use Value::*;
         match (lhs, self, rhs) {
             // Singular Values
-            (Int(l), Add, Int(r)) => Some(Int(l + r)),
-            (Float(l), Add, Float(r)) => Some(Float(l + r)),
+            (Int(l), Add | Sub | Mult | Div, Int(r)) => {
+                Some(Int(overflow.apply(self, IntKind::Int64, *l, *r)?))
+            }
+            (SizedInt(kind, l), Add | Sub | Mult | Div, SizedInt(_, r) | Int(r))
+            | (Int(l), Add | Sub | Mult | Div, SizedInt(kind, r)) => {
+                Some(SizedInt(*kind, overflow.apply(self, *kind, *l, *r)?))
+            }
+            (Int(l), Land | Lor | Lxor | Shl | Shr, Int(r)) => {
+                Some(Int(IntKind::Int64.bitwise(self, *l, *r)?))
+            }
+            (SizedInt(kind, l), Land | Lor | Lxor | Shl | Shr, SizedInt(_, r) | Int(r))
+            | (Int(l), Land | Lor | Lxor, SizedInt(kind, r)) => {
+                Some(SizedInt(*kind, kind.bitwise(self, *l, *r)?))
+            }
+            // The shift amount does not give its type to the result
+            (Int(l), Shl | Shr, SizedInt(_, r)) => Some(Int(IntKind::Int64.bitwise(self, *l, *r)?)),
+            (Int(l), Bit, Int(r) | SizedInt(_, r)) => Some(Bool(IntKind::Int64.bit(*l, *r)?)),
+            (SizedInt(kind, l), Bit, Int(r) | SizedInt(_, r)) => Some(Bool(kind.bit(*l, *r)?)),
 
-            (Int(l), Sub, Int(r)) => Some(Int(l - r)),
+            (Float(l), Add, Float(r)) => Some(Float(l + r)),
+            // Concatenation
+            (String(l), Add, String(r)) => Some(String(format!("{l}{r}"))),
             (Float(l), Sub, Float(r)) => Some(Float(l - r)),
-
-            (Int(l), Mult, Int(r)) => Some(Int(l * r)),
             (Float(l), Mult, Float(r)) => Some(Float(l * r)),
-
-            (Int(l), Div, Int(r)) => Some(Int(l / r)),
             (Float(l), Div, Float(r)) => Some(Float(l / r)),
 
-            (Int(l), Eq, Int(r)) => Some(Bool(l == r)),
+            (Int(l) | SizedInt(_, l), Eq, Int(r) | SizedInt(_, r)) => Some(Bool(l == r)),
             (Bool(l), Eq, Bool(r)) => Some(Bool(l == r)),
+            (Char(l), Eq, Char(r)) => Some(Bool(l == r)),
+            (String(l), Eq, String(r)) => Some(Bool(l == r)),
             (Float(l), Eq, Float(r)) => Some(Bool(l == r)),
 
-            (Int(l), Neq, Int(r)) => Some(Bool(l != r)),
+            (Int(l) | SizedInt(_, l), Neq, Int(r) | SizedInt(_, r)) => Some(Bool(l != r)),
             (Bool(l), Neq, Bool(r)) => Some(Bool(l != r)),
+            (Char(l), Neq, Char(r)) => Some(Bool(l != r)),
+            (String(l), Neq, String(r)) => Some(Bool(l != r)),
             (Float(l), Neq, Float(r)) => Some(Bool(l != r)),
 
             (Bool(l), Or, Bool(r)) => Some(Bool(*l || *r)),
@@ -61,9 +109,11 @@ impl BinOp {
             },
 
             (_, Fby, _) => None, // todo maybe put unreachable!(),
+            // Lowered by the compiler, they depend on the past values
+            (_, Since | Triggered, _) => None,
             (_, Arrow, _) => match instant {
-                Some(Instant::Initial) => Some(lhs.clone()),
-                Some(Instant::NonInitial) => Some(rhs.clone()),
+                Some(instant) if instant.is_init() => Some(lhs.clone()),
+                Some(_) => Some(rhs.clone()),
                 None => None,
             },
 
@@ -71,17 +121,17 @@ impl BinOp {
             (Tuple(l), Eq | Neq, Tuple(r)) | (Array(l), Eq | Neq, Array(r)) => {
                 let mut res = true; // op.apply(Value::Bool(true), Value::Bool(true));
                 for (lv, rv) in l.iter().zip(r.iter()) {
-                    if let Some(Value::Bool(b)) = self.apply(lv, rv, instant) {
+                    if let Some(Value::Bool(b)) = self.apply(lv, rv, instant, overflow) {
                         res = res && b
                     }
                 }
                 Some(Bool(res))
             }
-            (Tuple(l), Add | Sub | Mult | Div | Or | And, Tuple(r))
-            | (Array(l), Add | Sub | Mult | Div | Or | And, Array(r)) => {
+            (Tuple(l), Add | Sub | Mult | Div | Or | And | Land | Lor | Lxor, Tuple(r))
+            | (Array(l), Add | Sub | Mult | Div | Or | And | Land | Lor | Lxor, Array(r)) => {
                 let mut res = vec![];
                 for (lv, rv) in l.iter().zip(r.iter()) {
-                    res.push(self.apply(lv, rv, instant)?);
+                    res.push(self.apply(lv, rv, instant, overflow)?);
                 }
                 if let Tuple(_) = lhs {
                     Some(Tuple(res))
@@ -94,6 +144,18 @@ impl BinOp {
     }
 }
 
+impl BinOp {
+    /// Operators written like a function call : `bit(x, i)`
+    pub fn is_function_like(&self) -> bool {
+        self == &BinOp::Bit
+    }
+    /// Operators depending on the previous instants
+    pub fn is_temporal(&self) -> bool {
+        use BinOp::*;
+        matches!(self, Fby | Arrow | Since | Triggered)
+    }
+}
+
 impl Precedence for BinOp {
     fn precedence(&self) -> usize {
         match self {
@@ -102,12 +164,20 @@ impl Precedence for BinOp {
             BinOp::Neq => 4,
             BinOp::Or => 4,
             BinOp::And => 4,
+            BinOp::Since => 4,
+            BinOp::Triggered => 4,
             BinOp::Add => 3,
             BinOp::Sub => 3,
             BinOp::Mult => 2,
             BinOp::Fby => 2,
             BinOp::Div => 2,
             BinOp::Caret => 2,
+            BinOp::Land => 2,
+            BinOp::Lor => 2,
+            BinOp::Lxor => 2,
+            BinOp::Shl => 2,
+            BinOp::Shr => 2,
+            BinOp::Bit => 0,
         }
     }
 }
@@ -126,6 +196,14 @@ impl std::fmt::Display for BinOp {
             BinOp::Or => write!(f, "or"),
             BinOp::And => write!(f, "and"),
             BinOp::Caret => write!(f, "^"),
+            BinOp::Land => write!(f, "land"),
+            BinOp::Lor => write!(f, "lor"),
+            BinOp::Lxor => write!(f, "lxor"),
+            BinOp::Shl => write!(f, "shl"),
+            BinOp::Shr => write!(f, "shr"),
+            BinOp::Bit => write!(f, "bit"),
+            BinOp::Since => write!(f, "since"),
+            BinOp::Triggered => write!(f, "triggered"),
         }
     }
 }
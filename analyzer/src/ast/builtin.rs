@@ -0,0 +1,143 @@
+use crate::{ast::literal::Value, parser::var_type::InnerVarType};
+use serde_derive::{Deserialize, Serialize};
+
+/// Activation condition of a node call, `condact(c, f(args), defaults...)`
+/// steps `f` only when `c` holds and otherwise holds its outputs.
+/// A node with the same name hides it.
+pub const CONDACT: &str = "condact";
+
+/// Node call in a state of an automaton, `condact#restart(c, r, f(args))`
+/// steps `f` only when `c` holds and forgets its memories first when `r`
+/// holds. Only written by [Node::lower_automata](crate::ast::node::Node::lower_automata).
+pub(crate) const RESTART: &str = "condact#restart";
+
+/// Functions known by every program, a node with the same name hides them.
+///
+/// They apply elementwise on arrays, like a lifted node call.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Builtin {
+    Sqrt,
+    Sin,
+    Cos,
+    Atan2,
+    Exp,
+    Min,
+    Max,
+    Clamp,
+}
+
+impl Builtin {
+    pub const ALL: [Builtin; 8] = [
+        Builtin::Sqrt,
+        Builtin::Sin,
+        Builtin::Cos,
+        Builtin::Atan2,
+        Builtin::Exp,
+        Builtin::Min,
+        Builtin::Max,
+        Builtin::Clamp,
+    ];
+    pub fn from_name(name: &str) -> Option<Builtin> {
+        Builtin::ALL.into_iter().find(|b| b.name() == name)
+    }
+    pub fn name(&self) -> &'static str {
+        use Builtin::*;
+        match self {
+            Sqrt => "sqrt",
+            Sin => "sin",
+            Cos => "cos",
+            Atan2 => "atan2",
+            Exp => "exp",
+            Min => "min",
+            Max => "max",
+            Clamp => "clamp",
+        }
+    }
+    /// Number of arguments, all of the same type
+    pub fn arity(&self) -> usize {
+        use Builtin::*;
+        match self {
+            Sqrt | Sin | Cos | Exp => 1,
+            Atan2 | Min | Max => 2,
+            Clamp => 3,
+        }
+    }
+    /// Scalar types accepted for the arguments, the result has the same type
+    pub fn accepts(&self, t: &InnerVarType) -> bool {
+        use Builtin::*;
+        match self {
+            Sqrt | Sin | Cos | Atan2 | Exp => t == &InnerVarType::Float,
+            Min | Max | Clamp => t.is_numeric(),
+        }
+    }
+    /// Signature shown to the user
+    pub fn signature(&self) -> String {
+        use Builtin::*;
+        let t = match self {
+            Sqrt | Sin | Cos | Atan2 | Exp => "float",
+            Min | Max | Clamp => "T",
+        };
+        let args = vec![t; self.arity()].join(", ");
+        format!("{}({args}) -> {t}", self.name())
+    }
+
+    pub fn apply(&self, args: &[Value]) -> Option<Value> {
+        use Builtin::*;
+        use Value::*;
+        if let Some(Array(first)) = args.first() {
+            let mut res = vec![];
+            for i in 0..first.len() {
+                let column = args
+                    .iter()
+                    .map(|arg| match arg {
+                        Array(values) => values.get(i).cloned(),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                res.push(self.apply(&column)?);
+            }
+            return Some(Array(res));
+        }
+        match (self, args) {
+            (Sqrt, [Float(x)]) => Some(Float(x.sqrt())),
+            (Sin, [Float(x)]) => Some(Float(x.sin())),
+            (Cos, [Float(x)]) => Some(Float(x.cos())),
+            (Exp, [Float(x)]) => Some(Float(x.exp())),
+            (Atan2, [Float(y), Float(x)]) => Some(Float(y.atan2(*x))),
+            (Min, [l, r]) => Some(with_kind(if less(r, l)? { r } else { l }, args)),
+            (Max, [l, r]) => Some(with_kind(if less(l, r)? { r } else { l }, args)),
+            (Clamp, [x, low, high]) => {
+                let x = Min.apply(&[x.clone(), high.clone()])?;
+                Max.apply(&[x, low.clone()])
+            }
+            _ => None,
+        }
+    }
+}
+
+/// An integer literal takes the type of the sized integers it is used with
+fn with_kind(value: &Value, args: &[Value]) -> Value {
+    match value {
+        Value::Int(i) => match args.iter().find(|v| matches!(v, Value::SizedInt(..))) {
+            Some(Value::SizedInt(kind, _)) => Value::SizedInt(*kind, *i),
+            _ => value.clone(),
+        },
+        _ => value.clone(),
+    }
+}
+
+/// `lhs < rhs` on numeric values
+fn less(lhs: &Value, rhs: &Value) -> Option<bool> {
+    use Value::*;
+    match (lhs, rhs) {
+        (Int(l) | SizedInt(_, l), Int(r) | SizedInt(_, r)) => Some(l < r),
+        (Float(l), Float(r)) => Some(l < r),
+        _ => None,
+    }
+}
+
+impl std::fmt::Display for Builtin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
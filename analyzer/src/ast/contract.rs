@@ -0,0 +1,73 @@
+use crate::{ast::expression::Expr, parser::span::Span};
+
+/// Statement of a contract with the span of its keyword
+pub(crate) type Clause = (Span, Expr);
+
+/// Assume/guarantee contract of a node, written between its signature and its body
+///
+/// ```text
+/// (*@contract
+///     assume x != 0;
+///     guarantee y == x * x;
+///     mode negative (
+///         require x == -x;
+///         ensure y == 0;
+///     );
+/// *)
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Contract {
+    pub(crate) span_contract: Span,
+    /// Expected from the callers
+    pub(crate) assumes: Vec<Clause>,
+    /// Promised by the node when its assumptions hold
+    pub(crate) guarantees: Vec<Clause>,
+    pub(crate) modes: Vec<Mode>,
+}
+
+/// Guarantees `ensures` that only hold when all the `requires` are true
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mode {
+    pub(crate) span_mode: Span,
+    pub(crate) name: Span,
+    pub(crate) requires: Vec<Clause>,
+    pub(crate) ensures: Vec<Clause>,
+}
+
+impl Contract {
+    /// Every expression of the contract
+    pub(crate) fn exprs(&self) -> impl Iterator<Item = &Expr> {
+        self.assumes
+            .iter()
+            .chain(self.guarantees.iter())
+            .chain(
+                self.modes
+                    .iter()
+                    .flat_map(|mode| mode.requires.iter().chain(mode.ensures.iter())),
+            )
+            .map(|(_, expr)| expr)
+    }
+}
+
+impl std::fmt::Display for Contract {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "(*@contract")?;
+        for (_, e) in self.assumes.iter() {
+            writeln!(f, "\tassume {e};")?;
+        }
+        for (_, e) in self.guarantees.iter() {
+            writeln!(f, "\tguarantee {e};")?;
+        }
+        for mode in self.modes.iter() {
+            writeln!(f, "\tmode {} (", mode.name)?;
+            for (_, e) in mode.requires.iter() {
+                writeln!(f, "\t\trequire {e};")?;
+            }
+            for (_, e) in mode.ensures.iter() {
+                writeln!(f, "\t\tensure {e};")?;
+            }
+            writeln!(f, "\t);")?;
+        }
+        write!(f, "*)")
+    }
+}
@@ -1,5 +1,8 @@
 use crate::{
-    ast::{ast::Ast, binop::BinOp, expression::Expr, ftag::Tag, literal::Value, node::Node},
+    ast::{
+        ast::Ast, automaton::Automaton, binop::BinOp, expression::Expr, ftag::Tag, literal::Value,
+        node::Node,
+    },
     parser::{span::Span, var_type::VarType},
 };
 use colored::Colorize;
@@ -11,6 +14,7 @@ pub(crate) trait DoubleTogetherVisitor {
     fn visit_tag(&mut self, a: &Tag, b: &Tag);
     fn visit_var_type(&mut self, a: &VarType, b: &VarType);
     fn visit_expr(&mut self, a: &Expr, b: &Expr);
+    fn visit_automaton(&mut self, a: &Automaton, b: &Automaton);
     fn visit_node(&mut self, a: &Node, b: &Node);
     fn visit_ast(&mut self, x1: &Ast, x2: &Ast);
     fn walk(&mut self, ast1: &Ast, ast2: &Ast) {
@@ -73,12 +77,21 @@ impl DoubleTogetherVisitor for ShallowEq {
             (Value::Int(i1), Value::Int(i2)) => {
                 self.and(i1 == i2);
             }
+            (Value::SizedInt(k1, i1), Value::SizedInt(k2, i2)) => {
+                self.and(k1 == k2 && i1 == i2);
+            }
             (Value::Float(f1), Value::Float(f2)) => {
                 self.and(f1 == f2);
             }
             (Value::Bool(b1), Value::Bool(b2)) => {
                 self.and(b1 == b2);
             }
+            (Value::Char(c1), Value::Char(c2)) => {
+                self.and(c1 == c2);
+            }
+            (Value::String(s1), Value::String(s2)) => {
+                self.and(s1 == s2);
+            }
             (_, _) => {
                 self.set(false);
             }
@@ -111,6 +124,37 @@ impl DoubleTogetherVisitor for ShallowEq {
         }
         im_here!(self, a, b);
     }
+    fn visit_automaton(&mut self, x1: &Automaton, x2: &Automaton) {
+        if x1.states.len() != x2.states.len() {
+            self.set(false);
+            return;
+        }
+        for (s1, s2) in x1.states.iter().zip(x2.states.iter()) {
+            self.visit_span(&s1.name, &s2.name);
+            if s1.unless.len() != s2.unless.len()
+                || s1.until.len() != s2.until.len()
+                || s1.let_bindings.len() != s2.let_bindings.len()
+            {
+                self.set(false);
+                return;
+            }
+            for (t1, t2) in s1
+                .unless
+                .iter()
+                .chain(s1.until.iter())
+                .zip(s2.unless.iter().chain(s2.until.iter()))
+            {
+                self.visit_expr(&t1.cond, &t2.cond);
+                self.and(t1.restart == t2.restart);
+                self.visit_span(&t1.target, &t2.target);
+            }
+            for ((n1, e1), (n2, e2)) in s1.let_bindings.iter().zip(s2.let_bindings.iter()) {
+                self.visit_span(n1, n2);
+                self.visit_expr(e1, e2);
+            }
+        }
+        im_here!(self, x1, x2);
+    }
     fn visit_node(&mut self, x1: &Node, x2: &Node) {
         match (&x1.tag, &x2.tag) {
             (None, None) => {}
@@ -121,6 +165,14 @@ impl DoubleTogetherVisitor for ShallowEq {
             (Some((_, t1)), Some((_, t2))) => self.visit_tag(t1, t2),
         }
 
+        if x1.allow.len() != x2.allow.len() {
+            self.set(false);
+            return;
+        }
+        for i in 0..x1.allow.len() {
+            self.visit_span(&x1.allow[i], &x2.allow[i]);
+        }
+
         self.visit_span(&x1.name, &x2.name);
 
         if x1.inputs.len() != x2.inputs.len() {
@@ -158,11 +210,43 @@ impl DoubleTogetherVisitor for ShallowEq {
             self.visit_span(&x1.let_bindings[i].0, &x2.let_bindings[i].0);
             self.visit_expr(&x1.let_bindings[i].1, &x2.let_bindings[i].1);
         }
+
+        if x1.asserts.len() != x2.asserts.len() {
+            self.set(false);
+            return;
+        }
+        for i in 0..x1.asserts.len() {
+            self.visit_expr(&x1.asserts[i].1, &x2.asserts[i].1);
+        }
+
+        // Contracts are compared through their expressions in order
+        let (exprs1, exprs2): (Vec<_>, Vec<_>) = (
+            x1.contract.iter().flat_map(|c| c.exprs()).collect(),
+            x2.contract.iter().flat_map(|c| c.exprs()).collect(),
+        );
+        if x1.contract.is_some() != x2.contract.is_some() || exprs1.len() != exprs2.len() {
+            self.set(false);
+            return;
+        }
+        for (e1, e2) in exprs1.into_iter().zip(exprs2) {
+            self.visit_expr(e1, e2);
+        }
+
+        if x1.automata.len() != x2.automata.len() {
+            self.set(false);
+            return;
+        }
+        for (a1, a2) in x1.automata.iter().zip(x2.automata.iter()) {
+            self.visit_automaton(a1, a2);
+        }
         im_here!(self, x1, x2);
     }
 
     fn visit_ast(&mut self, x1: &Ast, x2: &Ast) {
-        if x1.nodes.len() != x2.nodes.len() {
+        if x1.nodes.len() != x2.nodes.len()
+            || x1.overflow != x2.overflow
+            || x1.prelude != x2.prelude
+        {
             self.set(false);
             return;
         }
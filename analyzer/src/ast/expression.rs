@@ -123,6 +123,9 @@ impl Expr {
         parent_op: Option<BinOp>,
     ) -> std::fmt::Result {
         match self {
+            Expr::BinOp { lhs, op, rhs, .. } if op.is_function_like() => {
+                write!(f, "{op}({lhs}, {rhs})")
+            }
             Expr::BinOp {
                 lhs,
                 op,
@@ -157,7 +160,11 @@ impl Expr {
                 span_op: _,
                 rhs,
             } => {
-                write!(f, "{op} {rhs}")
+                if op.is_function_like() {
+                    write!(f, "{op}({rhs})")
+                } else {
+                    write!(f, "{op} {rhs}")
+                }
             }
             Expr::Lit(lt) => {
                 write!(f, "{lt}")
@@ -19,14 +19,14 @@ pub(crate) struct DocumentHighlightVisitor {
 impl DocumentHighlightVisitor {
     pub fn new(ast: &Ast, pos: Position) -> Self {
         let mut node_index: i32 = 0;
-        for (i, node) in ast.nodes.iter().enumerate() {
+        for (i, node) in ast.user_nodes().iter().enumerate() {
             if node.name >= pos {
                 node_index = i as i32 - 1;
                 break;
             }
         }
         let searched_node = if node_index >= 0 {
-            Some(ast.nodes[node_index as usize].name.clone())
+            Some(ast.user_nodes()[node_index as usize].name.clone())
         } else {
             None
         };
@@ -67,6 +67,12 @@ impl Visitor for DocumentHighlightVisitor {
             self.visit_span(name);
             self.visit_expr(t)
         }
+        for automaton in node.automata.iter() {
+            self.visit_automaton(automaton)
+        }
+        for expr in node.checked_exprs() {
+            self.visit_expr(expr)
+        }
     }
     fn visit_span(&mut self, x: &Span) {
         self.hightlights.push(DocumentHighlight {
@@ -0,0 +1,92 @@
+use crate::{
+    ast::{ast::Ast, prelude::documentation, to_range::ToRange, visitor::Visitor},
+    parser::span::Span,
+};
+use lsp_types::{Hover, HoverContents, MarkupContent, MarkupKind, Position};
+
+impl Ast {
+    /// Signature, contract and documentation of the node called or declared at `pos`
+    pub fn hover(&self, pos: Position) -> Option<Hover> {
+        let mut visitor = HoverVisitor { pos, name: None };
+        visitor.walk(self);
+        let name = visitor.name.or_else(|| {
+            self.user_nodes()
+                .iter()
+                .map(|node| &node.name)
+                .find(|name| contains(name, pos))
+                .cloned()
+        })?;
+        let index = self.nodes.iter().position(|node| node.name == name)?;
+        let node = &self.nodes[index];
+
+        let mut value = format!("```lustre\n{}\n", node.signature());
+        if let Some(contract) = &node.contract {
+            value.push_str(&format!("{contract}\n"));
+        }
+        value.push_str("```\n");
+        if index < self.prelude_len
+            && let Some(doc) = documentation(&node.name.fragment())
+        {
+            value.push_str(doc);
+        }
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value,
+            }),
+            range: Some(name.to_range()),
+        })
+    }
+}
+
+fn contains(name: &Span, pos: Position) -> bool {
+    let range = name.to_range();
+    range.start <= pos && pos <= range.end
+}
+
+struct HoverVisitor {
+    pos: Position,
+    name: Option<Span>,
+}
+
+impl Visitor for HoverVisitor {
+    fn visit_call(&mut self, name: &Span) {
+        if contains(name, self.pos) {
+            self.name = Some(name.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parser::lustre_parser::lustre_parse;
+    use lsp_types::{HoverContents, Position};
+
+    fn hover_text(input: &str, pos: Position) -> Option<String> {
+        let ast = lustre_parse(input).unwrap();
+        match ast.hover(pos)?.contents {
+            HoverContents::Markup(content) => Some(content.value),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn hover_prelude_call() {
+        let input = "node main(x: bool) returns (y: bool);\nlet\n    y = rising_edge(x);\ntel\n";
+        let content = hover_text(input, Position::new(2, 10)).unwrap();
+        assert!(content.contains("node rising_edge(x : bool) returns (edge : bool)"));
+        assert!(content.contains("becomes true"));
+        assert!(hover_text(input, Position::new(2, 4)).is_none());
+    }
+
+    #[test]
+    fn hover_contract() {
+        let input = "node half(x: int) returns (y: int);\n(*@contract\n    assume x != 0;\n    guarantee y + y == x;\n*)\nlet\n    y = x / 2;\ntel\n\nnode main(x: int) returns (y: int);\nlet\n    y = half(x + x);\ntel\n";
+        let call = hover_text(input, Position::new(11, 10)).unwrap();
+        assert!(call.contains("node half(x : int) returns (y : int)"));
+        assert!(call.contains("assume x != 0;"));
+        assert!(call.contains("guarantee y + y == x;"));
+        let declaration = hover_text(input, Position::new(0, 6)).unwrap();
+        assert_eq!(call, declaration);
+    }
+}
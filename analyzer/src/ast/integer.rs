@@ -0,0 +1,166 @@
+use crate::ast::binop::BinOp;
+use serde_derive::{Deserialize, Serialize};
+
+/// Sized integer types, `int` is a 64 bits signed integer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum IntKind {
+    Int8,
+    Int16,
+    Int32,
+    Int64,
+    UInt8,
+    UInt16,
+    UInt32,
+}
+
+impl IntKind {
+    pub const ALL: [IntKind; 7] = [
+        IntKind::Int8,
+        IntKind::Int16,
+        IntKind::Int32,
+        IntKind::Int64,
+        IntKind::UInt8,
+        IntKind::UInt16,
+        IntKind::UInt32,
+    ];
+    pub fn min(&self) -> i64 {
+        use IntKind::*;
+        match self {
+            Int8 => i8::MIN as i64,
+            Int16 => i16::MIN as i64,
+            Int32 => i32::MIN as i64,
+            Int64 => i64::MIN,
+            UInt8 | UInt16 | UInt32 => 0,
+        }
+    }
+    pub fn max(&self) -> i64 {
+        use IntKind::*;
+        match self {
+            Int8 => i8::MAX as i64,
+            Int16 => i16::MAX as i64,
+            Int32 => i32::MAX as i64,
+            Int64 => i64::MAX,
+            UInt8 => u8::MAX as i64,
+            UInt16 => u16::MAX as i64,
+            UInt32 => u32::MAX as i64,
+        }
+    }
+    pub fn contains(&self, value: i64) -> bool {
+        self.min() <= value && value <= self.max()
+    }
+    pub fn bits(&self) -> i64 {
+        use IntKind::*;
+        match self {
+            Int8 | UInt8 => 8,
+            Int16 | UInt16 => 16,
+            Int32 | UInt32 => 32,
+            Int64 => 64,
+        }
+    }
+    /// Bitwise operator `op` on two values of this type.
+    ///
+    /// Bits shifted out are lost, shifting by a negative amount or by
+    /// the width of the type or more has no result.
+    pub fn bitwise(&self, op: BinOp, lhs: i64, rhs: i64) -> Option<i64> {
+        match op {
+            BinOp::Land => Some(lhs & rhs),
+            BinOp::Lor => Some(lhs | rhs),
+            BinOp::Lxor => Some(lhs ^ rhs),
+            BinOp::Shl if (0..self.bits()).contains(&rhs) => Some(self.wrap((lhs as i128) << rhs)),
+            BinOp::Shr if (0..self.bits()).contains(&rhs) => Some(lhs >> rhs),
+            _ => None,
+        }
+    }
+    /// `lnot value`, every bit of the type is flipped
+    pub fn not(&self, value: i64) -> i64 {
+        self.wrap(!value as i128)
+    }
+    /// Bit number `index` of `value`, starting from the least significant one
+    pub fn bit(&self, value: i64, index: i64) -> Option<bool> {
+        (0..self.bits())
+            .contains(&index)
+            .then_some((value >> index) & 1 == 1)
+    }
+    /// Value of the type equal to `value` modulo 2^bits
+    fn wrap(&self, value: i128) -> i64 {
+        use IntKind::*;
+        match self {
+            Int8 => value as i8 as i64,
+            Int16 => value as i16 as i64,
+            Int32 => value as i32 as i64,
+            Int64 => value as i64,
+            UInt8 => value as u8 as i64,
+            UInt16 => value as u16 as i64,
+            UInt32 => value as u32 as i64,
+        }
+    }
+}
+
+impl std::fmt::Display for IntKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use IntKind::*;
+        match self {
+            Int8 => write!(f, "int8"),
+            Int16 => write!(f, "int16"),
+            Int32 => write!(f, "int32"),
+            Int64 => write!(f, "int64"),
+            UInt8 => write!(f, "uint8"),
+            UInt16 => write!(f, "uint16"),
+            UInt32 => write!(f, "uint32"),
+        }
+    }
+}
+
+/// Behavior of integer arithmetic when the result does not fit in its type,
+/// chosen for a whole program with `#![overflow(...)]`.
+///
+/// Division by zero has no result whatever the behavior.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Overflow {
+    /// The result is taken modulo 2^bits
+    Wrapping,
+    /// The result is clamped to the bounds of the type
+    Saturating,
+    /// There is no result
+    #[default]
+    Trapping,
+}
+
+impl Overflow {
+    /// Bring the exact result `value` into the range of `kind`
+    pub fn fit(&self, value: i128, kind: IntKind) -> Option<i64> {
+        if kind.min() as i128 <= value && value <= kind.max() as i128 {
+            return Some(value as i64);
+        }
+        match self {
+            Overflow::Wrapping => Some(kind.wrap(value)),
+            Overflow::Saturating => {
+                Some(value.clamp(kind.min() as i128, kind.max() as i128) as i64)
+            }
+            Overflow::Trapping => None,
+        }
+    }
+    /// Integer arithmetic of `op` on values of type `kind`
+    pub fn apply(&self, op: BinOp, kind: IntKind, lhs: i64, rhs: i64) -> Option<i64> {
+        let (lhs, rhs) = (lhs as i128, rhs as i128);
+        let value = match op {
+            BinOp::Add => lhs + rhs,
+            BinOp::Sub => lhs - rhs,
+            BinOp::Mult => lhs * rhs,
+            BinOp::Div if rhs == 0 => return None,
+            BinOp::Div => lhs / rhs,
+            _ => return None,
+        };
+        self.fit(value, kind)
+    }
+}
+
+impl std::fmt::Display for Overflow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Overflow::Wrapping => write!(f, "wrapping"),
+            Overflow::Saturating => write!(f, "saturating"),
+            Overflow::Trapping => write!(f, "trapping"),
+        }
+    }
+}
@@ -1,16 +1,22 @@
+use crate::ast::integer::IntKind;
 use crate::ast::to_range::ToRange;
 use crate::checker::infer_types::InferLen;
 use crate::parser::span::ZERORANGE;
 use crate::parser::var_type::InnerVarType;
 use crate::parser::var_type::VarType;
 use lsp_types::Range;
+use serde_derive::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Value {
     Unit,
     Int(i64),
+    /// Value of a sized integer type, always inside the bounds of its type
+    SizedInt(IntKind, i64),
     Float(f64),
     Bool(bool),
+    Char(char),
+    String(String),
     Tuple(Vec<Value>),
     Array(Vec<Value>),
 }
@@ -38,8 +44,11 @@ impl Value {
         match self {
             Value::Unit => InnerVarType::Unit,
             Value::Int(_) => InnerVarType::Int,
+            Value::SizedInt(kind, _) => InnerVarType::SizedInt(*kind),
             Value::Float(_) => InnerVarType::Float,
             Value::Bool(_) => InnerVarType::Bool,
+            Value::Char(_) => InnerVarType::Char,
+            Value::String(_) => InnerVarType::String,
             Value::Tuple(v) => {
                 if v.is_empty() {
                     InnerVarType::Unit
@@ -78,13 +87,34 @@ impl ToRange for Value {
     }
 }
 
+/// Escape the backslashes, line breaks, tabulations and `quote` of `s`.
+fn escape(s: &str, quote: char) -> String {
+    let mut res = String::new();
+    for c in s.chars() {
+        match c {
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            '\t' => res.push_str("\\t"),
+            c if c == quote => {
+                res.push('\\');
+                res.push(c);
+            }
+            c => res.push(c),
+        }
+    }
+    res
+}
+
 impl std::fmt::Display for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
             Value::Unit => write!(f, "()"),
-            Value::Int(i) => write!(f, "{i}"),
+            Value::Int(i) | Value::SizedInt(_, i) => write!(f, "{i}"),
             Value::Float(fl) => write!(f, "{fl}"),
             Value::Bool(b) => write!(f, "{b}"),
+            // Same escapes as the literal parser
+            Value::Char(c) => write!(f, "'{}'", escape(&c.to_string(), '\'')),
+            Value::String(s) => write!(f, "\"{}\"", escape(s, '"')),
             Value::Tuple(vec) => {
                 if vec.is_empty() {
                     write!(f, "{}", InnerVarType::Unit)
@@ -1,5 +1,5 @@
 use crate::{
-    ast::{expression::Expr, ftag::Tag, literal::Value},
+    ast::{automaton::Automaton, contract::Contract, expression::Expr, ftag::Tag, literal::Value},
     parser::{
         parsed_node::ParsedNode,
         span::{Ident, PositionEnd, Span},
@@ -15,14 +15,21 @@ pub struct Node {
     pub(crate) span_let: Span,
     pub(crate) span_tel: Span,
     pub(crate) span_semicolon: Span,
+    pub(crate) span_var: Option<Span>,
 
     pub(crate) tag: Option<(Span, Tag)>,
+    /// Lints allowed with `#[allow(...)]`
+    pub(crate) allow: Vec<Span>,
     pub(crate) name: Span,
     pub(crate) inputs: Vec<(Ident, VarType)>,
     pub(crate) vars: Vec<(Ident, VarType)>,
     pub(crate) outputs: Vec<(Ident, VarType)>,
     pub(crate) let_bindings: Vec<(Ident, Expr)>,
     pub(crate) span_semicolon_equations: Vec<Span>,
+    /// `assert expr;` with the span of the keyword
+    pub(crate) asserts: Vec<(Span, Expr)>,
+    pub(crate) contract: Option<Contract>,
+    pub(crate) automata: Vec<Automaton>,
 }
 
 impl From<Node> for ParsedNode {
@@ -33,13 +40,18 @@ impl From<Node> for ParsedNode {
             span_let,
             span_tel,
             span_semicolon,
+            span_var,
             tag,
+            allow,
             name,
             inputs,
             vars,
             outputs,
             let_bindings,
             span_semicolon_equations,
+            asserts,
+            contract,
+            automata,
         } = node;
 
         Self {
@@ -48,7 +60,9 @@ impl From<Node> for ParsedNode {
             span_let,
             span_tel,
             span_semicolon,
+            span_var,
             tag,
+            allow,
             name,
             inputs,
             vars,
@@ -58,6 +72,9 @@ impl From<Node> for ParsedNode {
                 .map(|(var, expr)| (vec![var], expr))
                 .collect(),
             span_semicolon_equations,
+            asserts,
+            contract,
+            automata,
         }
     }
 }
@@ -66,34 +83,68 @@ impl std::fmt::Display for Node {
         if let Some((_, t)) = &self.tag {
             writeln!(f, "#[{t}]")?;
         }
-        write!(f, "node {}(", self.name)?;
-        if self.inputs.len() != 1 || self.inputs[0].1.inner != InnerVarType::Unit {
-            for (i, (s, t)) in self.inputs.iter().enumerate() {
-                write!(f, "{s} : {t}")?;
-                if i != self.inputs.len() - 1 {
-                    write!(f, ", ")?;
-                }
-            }
+        if !self.allow.is_empty() {
+            let lints: Vec<String> = self.allow.iter().map(|lint| lint.fragment()).collect();
+            writeln!(f, "#[allow({})]", lints.join(", "))?;
         }
-        write!(f, ") returns (")?;
-        for (i, (s, t)) in self.outputs.iter().enumerate() {
-            write!(f, "{s} : {t}")?;
-            if i != self.outputs.len() - 1 {
-                write!(f, ", ")?;
-            }
+        writeln!(f, "{};", self.signature())?;
+        if let Some(contract) = &self.contract {
+            writeln!(f, "{contract}")?;
         }
 
-        writeln!(f, ");")?;
-
+        if !self.vars.is_empty() {
+            writeln!(f, "var")?;
+            for (s, t) in self.vars.iter() {
+                writeln!(f, "\t{s} : {t};")?;
+            }
+        }
         writeln!(f, "let")?;
         for (s, e) in self.let_bindings.iter() {
             writeln!(f, "\t{s} = {e};")?;
         }
+        for automaton in self.automata.iter() {
+            writeln!(f, "{automaton}")?;
+        }
+        for (_, e) in self.asserts.iter() {
+            writeln!(f, "\tassert {e};")?;
+        }
         write!(f, "tel")
     }
 }
 
 impl Node {
+    /// Expressions checked at every step: assertions and contract
+    pub(crate) fn checked_exprs(&self) -> impl Iterator<Item = &Expr> {
+        self.asserts
+            .iter()
+            .map(|(_, expr)| expr)
+            .chain(self.contract.iter().flat_map(|contract| contract.exprs()))
+    }
+    /// Every expression of the node
+    pub(crate) fn exprs(&self) -> impl Iterator<Item = &Expr> {
+        self.let_bindings
+            .iter()
+            .map(|(_, expr)| expr)
+            .chain(self.automata.iter().flat_map(|automaton| automaton.exprs()))
+            .chain(self.checked_exprs())
+    }
+    /// `node name(inputs) returns (outputs)`
+    pub(crate) fn signature(&self) -> String {
+        let typed = |vars: &[(Ident, VarType)]| {
+            let vars: Vec<String> = vars.iter().map(|(s, t)| format!("{s} : {t}")).collect();
+            vars.join(", ")
+        };
+        let inputs = if self.inputs.len() != 1 || self.inputs[0].1.inner != InnerVarType::Unit {
+            typed(&self.inputs)
+        } else {
+            String::new()
+        };
+        format!(
+            "node {}({inputs}) returns ({})",
+            self.name,
+            typed(&self.outputs)
+        )
+    }
     pub fn hint_reduced(&self) -> (Position, String) {
         (
             self.tag.as_ref().unwrap().0.position_end(),
@@ -115,6 +166,61 @@ impl Node {
     pub fn push_expr(&mut self, name: Span, expr: Expr) {
         self.let_bindings.push((name, expr));
     }
+    /// Node computing `condact(c, self(inputs), defaults...)`, its inputs are
+    /// `c` then the inputs of `self`
+    pub(crate) fn condact_wrapper(&self, condact: &Span, defaults: Vec<Expr>) -> Self {
+        let cond = Span::new(0, 0, format!("{condact}#cond"));
+        let call = Expr::FCall {
+            name: condact.clone(),
+            args: std::iter::once(Expr::Variable(cond.clone()))
+                .chain(std::iter::once(Expr::FCall {
+                    name: self.name.clone(),
+                    args: self
+                        .inputs
+                        .iter()
+                        .map(|(input, _)| Expr::Variable(input.clone()))
+                        .collect(),
+                }))
+                .chain(defaults)
+                .collect(),
+        };
+        let let_bindings = match &self.outputs[..] {
+            [(output, _)] => vec![(output.clone(), call)],
+            outputs => outputs
+                .iter()
+                .enumerate()
+                .map(|(i, (output, _))| {
+                    let index = Expr::Lit(Value::Int(i as i64));
+                    (
+                        output.clone(),
+                        Expr::Index {
+                            expr: Box::new(call.clone()),
+                            index: Box::new(index),
+                        },
+                    )
+                })
+                .collect(),
+        };
+        Self {
+            name: Span::new(0, 0, format!("{condact}#{}", self.name)),
+            inputs: std::iter::once((
+                cond,
+                VarType {
+                    initialized: true,
+                    inner: InnerVarType::Bool,
+                },
+            ))
+            .chain(self.inputs.iter().cloned())
+            .collect(),
+            vars: vec![],
+            let_bindings,
+            span_semicolon_equations: vec![],
+            asserts: vec![],
+            contract: None,
+            automata: vec![],
+            ..self.clone()
+        }
+    }
     pub fn shell_from_node(&self) -> Self {
         let Self {
             span_node,
@@ -122,13 +228,18 @@ impl Node {
             span_let,
             span_tel,
             span_semicolon,
+            span_var,
             tag,
+            allow,
             name,
             inputs,
             vars,
             outputs,
             let_bindings: _,
             span_semicolon_equations,
+            asserts,
+            contract,
+            automata,
         } = self;
 
         Self {
@@ -138,13 +249,18 @@ impl Node {
             span_let: span_let.clone(),
             span_tel: span_tel.clone(),
             span_semicolon: span_semicolon.clone(),
+            span_var: span_var.clone(),
             tag: tag.clone(),
+            allow: allow.clone(),
             name: name.clone(),
             inputs: inputs.clone(),
             vars: vars.clone(),
             outputs: outputs.clone(),
             let_bindings: vec![],
             span_semicolon_equations: span_semicolon_equations.clone(),
+            asserts: asserts.clone(),
+            contract: contract.clone(),
+            automata: automata.clone(),
         }
     }
 }
@@ -0,0 +1,49 @@
+use crate::{
+    ast::{ast::Ast, node::Node},
+    parser::{ast::ast, span::LSpan},
+};
+
+/// Nodes loaded before the user code, unless `#![no_prelude]` is given
+const PRELUDE: &str = include_str!("prelude.lus");
+
+/// Documentation shown on hover of a call to a prelude node
+pub(crate) fn documentation(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "rising_edge" => "True at the instants where `x` becomes true.",
+        "falling_edge" => "True at the instants where `x` becomes false.",
+        "count" => "Number of instants where `x` has been true, including the current one.",
+        "count_since" => {
+            "Number of instants since `x` was last true, or since the first instant if it never was."
+        }
+        "has_been_true" => "True from the first instant where `x` is true.",
+        "integrator" => "Sum of `x * dt` over all instants, starting from `0.0`.",
+        "debounce" => "True once `x` has been true for `n` consecutive instants.",
+        "timer" => "True during the `duration` instants following the last `start`.",
+        _ => return None,
+    })
+}
+
+impl Ast {
+    /// Insert the prelude nodes before the user nodes.
+    ///
+    /// A user node with the same name hides the prelude node.
+    pub(crate) fn load_prelude(mut self) -> Self {
+        if !self.prelude {
+            return self;
+        }
+        let (_, prelude) = ast(LSpan::new(PRELUDE)).expect("the prelude parses");
+        let nodes: Vec<_> = prelude
+            .flatten()
+            .nodes
+            .into_iter()
+            .filter(|node| self.nodes.iter().all(|user| user.name != node.name))
+            .collect();
+        self.prelude_len = nodes.len();
+        self.nodes.splice(0..0, nodes);
+        self
+    }
+    /// Nodes written by the user, without the prelude
+    pub(crate) fn user_nodes(&self) -> &[Node] {
+        &self.nodes[self.prelude_len..]
+    }
+}
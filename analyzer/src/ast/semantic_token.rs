@@ -1,5 +1,12 @@
 use crate::{
-    ast::{ast::Ast, node::Node, token_type::TokenType, visitor::Visitor},
+    ast::{
+        ast::Ast,
+        automaton::{Automaton, Transition},
+        contract::{Clause, Contract},
+        node::Node,
+        token_type::TokenType,
+        visitor::Visitor,
+    },
     parser::{span::Span, var_type::VarType},
 };
 use lsp_types::SemanticToken;
@@ -14,6 +21,8 @@ impl SemanticTokenVisitor {
         self.tokens.push(token);
     }
     fn compile_tokens(&mut self) {
+        // Assertions are visited after the equations they may precede
+        self.tokens.sort_by_key(|t| (t.delta_line, t.delta_start));
         for i in (1..self.tokens.len()).rev() {
             let current = self.tokens[i];
             if let Some(last) = self.tokens.get(i - 1) {
@@ -45,6 +54,37 @@ impl SemanticTokenVisitor {
     }
 }
 
+impl SemanticTokenVisitor {
+    fn visit_clauses(&mut self, clauses: &[Clause]) {
+        for (keyword, expr) in clauses.iter() {
+            self.push(keyword.to_semantic_token(TokenType::Keyword));
+            self.visit_expr(expr);
+        }
+    }
+    fn visit_transitions(&mut self, transitions: &[Transition]) {
+        for transition in transitions.iter() {
+            self.push(
+                transition
+                    .span_keyword
+                    .to_semantic_token(TokenType::Keyword),
+            );
+            self.visit_expr(&transition.cond);
+            self.push(transition.span_kind.to_semantic_token(TokenType::Keyword));
+            self.push(transition.target.to_semantic_token(TokenType::Type));
+        }
+    }
+    fn visit_contract(&mut self, contract: &Contract) {
+        self.push(contract.span_contract.to_semantic_token(TokenType::Comment));
+        self.visit_clauses(&contract.assumes);
+        self.visit_clauses(&contract.guarantees);
+        for mode in contract.modes.iter() {
+            self.push(mode.span_mode.to_semantic_token(TokenType::Keyword));
+            self.visit_clauses(&mode.requires);
+            self.visit_clauses(&mode.ensures);
+        }
+    }
+}
+
 impl Visitor for SemanticTokenVisitor {
     fn visit_var_type(&mut self, _: &VarType) {}
     fn visit_node(&mut self, x: &Node) {
@@ -53,6 +93,9 @@ impl Visitor for SemanticTokenVisitor {
             self.visit_tag(t)
         }
         self.visit_span(&x.name);
+        if let Some(contract) = &x.contract {
+            self.visit_contract(contract);
+        }
         for (name, t) in x.inputs.iter() {
             self.visit_span(name);
             self.visit_var_type(t)
@@ -63,6 +106,9 @@ impl Visitor for SemanticTokenVisitor {
             self.visit_span(name);
             self.visit_var_type(t)
         }
+        if let Some(span_var) = &x.span_var {
+            self.push(span_var.to_semantic_token(TokenType::Keyword));
+        }
         for (name, t) in x.vars.iter() {
             self.visit_span(name);
             self.visit_var_type(t)
@@ -73,8 +119,31 @@ impl Visitor for SemanticTokenVisitor {
             self.visit_span(name);
             self.visit_expr(t)
         }
+        for automaton in x.automata.iter() {
+            self.visit_automaton(automaton)
+        }
+        for (span_assert, expr) in x.asserts.iter() {
+            self.push(span_assert.to_semantic_token(TokenType::Keyword));
+            self.visit_expr(expr)
+        }
         self.push(x.span_tel.to_semantic_token(TokenType::Keyword));
     }
+    fn visit_automaton(&mut self, x: &Automaton) {
+        self.push(x.span_automaton.to_semantic_token(TokenType::Keyword));
+        for state in x.states.iter() {
+            self.push(state.span_state.to_semantic_token(TokenType::Keyword));
+            self.push(state.name.to_semantic_token(TokenType::Type));
+            self.visit_transitions(&state.unless);
+            self.push(state.span_let.to_semantic_token(TokenType::Keyword));
+            for (name, t) in state.let_bindings.iter() {
+                self.visit_span(name);
+                self.visit_expr(t)
+            }
+            self.push(state.span_tel.to_semantic_token(TokenType::Keyword));
+            self.visit_transitions(&state.until);
+        }
+        self.push(x.span_end.to_semantic_token(TokenType::Keyword));
+    }
     fn visit_span(&mut self, x: &Span) {
         self.push(x.to_semantic_token(TokenType::Variable));
     }
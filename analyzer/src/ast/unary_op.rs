@@ -1,37 +1,100 @@
 use crate::{
-    ast::{expression::Precedence, literal::Value},
+    ast::{
+        expression::Precedence,
+        integer::{IntKind, Overflow},
+        literal::Value,
+    },
     interpreter::instant::Instant,
+    parser::var_type::InnerVarType,
 };
+use serde_derive::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, PartialEq, Eq, Copy)]
+#[derive(Clone, Debug, PartialEq, Eq, Copy, Serialize, Deserialize)]
 pub enum UnaryOp {
     Inv,
     Pre,
     Not,
+    /// `float(x)`
+    ToFloat,
+    /// `int(x)`, rounds toward zero
+    ToInt,
+    /// `floor(x)`
+    Floor,
+    /// `round(x)`, rounds half away from zero
+    Round,
+    /// `abs(x)`
+    Abs,
+    /// `int8(x)`, conversion to a sized integer
+    ToSized(IntKind),
+    /// Bitwise not
+    Lnot,
+    /// `len(s)`, number of characters of a string
+    Len,
+    /// `once a`, `a` has been true at least once
+    Once,
+    /// `historically a`, `a` has always been true
+    Historically,
+    /// `yesterday a`, `a` was true at the previous instant
+    Yesterday,
 }
 
 impl UnaryOp {
-    pub fn apply(&self, rhs: &Value, instant: Option<Instant>) -> Option<Value> {
+    // Integer results follow `overflow`
+    pub fn apply(
+        &self,
+        rhs: &Value,
+        instant: Option<Instant>,
+        overflow: Overflow,
+    ) -> Option<Value> {
         use UnaryOp::*;
         use Value::*;
         match (self, rhs) {
-            (Inv, Int(i)) => Some(Int(-i)),
+            (Inv, Int(i)) => Some(Int(overflow.fit(-(*i as i128), IntKind::Int64)?)),
+            (Inv, SizedInt(kind, i)) => Some(SizedInt(*kind, overflow.fit(-(*i as i128), *kind)?)),
             (Inv, Float(i)) => Some(Float(-i)),
 
             (Pre, Int(i)) => match instant {
-                Some(Instant::NonInitial) => Some(Int(*i)),
+                Some(instant) if !instant.is_init() => Some(Int(*i)),
+                _ => None,
+            },
+            (Pre, SizedInt(kind, i)) => match instant {
+                Some(instant) if !instant.is_init() => Some(SizedInt(*kind, *i)),
                 _ => None,
             },
             (Pre, Float(f)) => match instant {
-                Some(Instant::NonInitial) => Some(Float(*f)),
+                Some(instant) if !instant.is_init() => Some(Float(*f)),
                 _ => None,
             },
 
             (Not, Bool(b)) => Some(Bool(!b)),
+            (Lnot, Int(i)) => Some(Int(!i)),
+            (Lnot, SizedInt(kind, i)) => Some(SizedInt(*kind, kind.not(*i))),
+
+            (ToFloat, Int(i) | SizedInt(_, i)) => Some(Float(*i as f64)),
+            (ToFloat, Float(f)) => Some(Float(*f)),
+            (ToInt | Floor | Round, Int(i) | SizedInt(_, i)) => Some(Int(*i)),
+            (ToInt, Float(f)) => Some(Int(overflow.fit(f.trunc() as i128, IntKind::Int64)?)),
+            (Floor, Float(f)) => Some(Int(overflow.fit(f.floor() as i128, IntKind::Int64)?)),
+            (Round, Float(f)) => Some(Int(overflow.fit(f.round() as i128, IntKind::Int64)?)),
+            (Abs, Int(i)) => Some(Int(overflow.fit((*i as i128).abs(), IntKind::Int64)?)),
+            (Abs, SizedInt(kind, i)) => {
+                Some(SizedInt(*kind, overflow.fit((*i as i128).abs(), *kind)?))
+            }
+            (Abs, Float(f)) => Some(Float(f.abs())),
+            (ToSized(kind), Int(i) | SizedInt(_, i)) => {
+                Some(SizedInt(*kind, overflow.fit(*i as i128, *kind)?))
+            }
+            (Len, String(s)) => Some(Int(s.chars().count() as i64)),
+            (ToSized(kind), Float(f)) => {
+                Some(SizedInt(*kind, overflow.fit(f.trunc() as i128, *kind)?))
+            }
+            // Lowered by the compiler, they depend on the past values
+            (Once | Historically | Yesterday, _) => None,
+
             (_, Tuple(l)) | (_, Array(l)) => {
                 let mut res = vec![];
                 for v in l.iter() {
-                    res.push(self.apply(v, instant)?);
+                    res.push(self.apply(v, instant, overflow)?);
                 }
                 Some(if let Tuple(_) = rhs {
                     Tuple(res)
@@ -43,12 +106,47 @@ impl UnaryOp {
         }
     }
 }
+impl UnaryOp {
+    /// Operators written like a function call : `float(x)`
+    pub fn is_function_like(&self) -> bool {
+        use UnaryOp::*;
+        match self {
+            Inv | Pre | Not | Lnot | Once | Historically | Yesterday => false,
+            ToFloat | ToInt | Floor | Round | Abs | ToSized(_) | Len => true,
+        }
+    }
+    /// Operators depending on the previous instants
+    pub fn is_temporal(&self) -> bool {
+        use UnaryOp::*;
+        matches!(self, Pre | Once | Historically | Yesterday)
+    }
+    /// Scalar type of the result of a function like operator applied on `t`
+    pub fn numeric_result(&self, t: &InnerVarType) -> InnerVarType {
+        use UnaryOp::*;
+        match self {
+            ToFloat => InnerVarType::Float,
+            ToInt | Floor | Round | Len => InnerVarType::Int,
+            ToSized(kind) => InnerVarType::SizedInt(*kind),
+            Inv | Pre | Not | Lnot | Abs | Once | Historically | Yesterday => t.clone(),
+        }
+    }
+}
+
 impl Precedence for UnaryOp {
     fn precedence(&self) -> usize {
         match self {
             UnaryOp::Inv => 1,
             UnaryOp::Pre => 1,
             UnaryOp::Not => 1,
+            UnaryOp::Lnot => 1,
+            UnaryOp::Once | UnaryOp::Historically | UnaryOp::Yesterday => 1,
+            UnaryOp::ToFloat
+            | UnaryOp::ToInt
+            | UnaryOp::Floor
+            | UnaryOp::Round
+            | UnaryOp::Abs
+            | UnaryOp::ToSized(_)
+            | UnaryOp::Len => 1,
         }
     }
 }
@@ -59,6 +157,17 @@ impl std::fmt::Display for UnaryOp {
             UnaryOp::Inv => write!(f, "-"),
             UnaryOp::Pre => write!(f, "pre"),
             UnaryOp::Not => write!(f, "not"),
+            UnaryOp::Lnot => write!(f, "lnot"),
+            UnaryOp::Len => write!(f, "len"),
+            UnaryOp::Once => write!(f, "once"),
+            UnaryOp::Historically => write!(f, "historically"),
+            UnaryOp::Yesterday => write!(f, "yesterday"),
+            UnaryOp::ToFloat => write!(f, "float"),
+            UnaryOp::ToInt => write!(f, "int"),
+            UnaryOp::Floor => write!(f, "floor"),
+            UnaryOp::Round => write!(f, "round"),
+            UnaryOp::Abs => write!(f, "abs"),
+            UnaryOp::ToSized(kind) => write!(f, "{kind}"),
         }
     }
 }
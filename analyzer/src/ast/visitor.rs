@@ -1,7 +1,7 @@
 use crate::{
     ast::{
-        ast::Ast, binop::BinOp, expression::Expr, ftag::Tag, literal::Value, node::Node,
-        unary_op::UnaryOp,
+        ast::Ast, automaton::Automaton, binop::BinOp, expression::Expr, ftag::Tag, literal::Value,
+        node::Node, unary_op::UnaryOp,
     },
     parser::{span::Span, var_type::VarType},
 };
@@ -11,6 +11,10 @@ pub(crate) trait Visitor {
     fn visit_unary_op(&mut self, _: &UnaryOp) {}
 
     fn visit_span(&mut self, _: &Span) {}
+    /// Name of a called node
+    fn visit_call(&mut self, name: &Span) {
+        self.visit_span(name)
+    }
 
     fn visit_literal(&mut self, _: &Value) {}
     fn visit_tag(&mut self, _: &Tag) {}
@@ -43,7 +47,7 @@ pub(crate) trait Visitor {
             }
             Expr::Array(arr) | Expr::Tuple(arr) => arr.iter().for_each(|x| self.visit_expr(x)),
             Expr::FCall { name, args } => {
-                self.visit_span(name);
+                self.visit_call(name);
                 args.iter().for_each(|e| self.visit_expr(e));
             }
             Expr::Variable(s) => {
@@ -80,9 +84,29 @@ pub(crate) trait Visitor {
             self.visit_span(name);
             self.visit_expr(t)
         }
+        for automaton in x.automata.iter() {
+            self.visit_automaton(automaton)
+        }
+        for expr in x.checked_exprs() {
+            self.visit_expr(expr)
+        }
+    }
+    fn visit_automaton(&mut self, x: &Automaton) {
+        for state in x.states.iter() {
+            for transition in state.unless.iter() {
+                self.visit_expr(&transition.cond)
+            }
+            for (name, t) in state.let_bindings.iter() {
+                self.visit_span(name);
+                self.visit_expr(t)
+            }
+            for transition in state.until.iter() {
+                self.visit_expr(&transition.cond)
+            }
+        }
     }
     fn visit_ast(&mut self, x: &Ast) {
-        for node in x.nodes.iter() {
+        for node in x.user_nodes() {
             self.visit_node(node);
         }
     }
@@ -1,3 +1,8 @@
+pub mod call_graph;
+pub mod definitions;
+pub mod error_code;
 pub mod function_type;
 pub mod infer_types;
+pub mod lint;
+pub mod quick_fix;
 pub mod types;
@@ -0,0 +1,186 @@
+use crate::{
+    ast::{expression::Expr, node::Node, to_range::ToRange},
+    checker::error_code::{ErrorCode, related},
+    parser::span::Span,
+};
+use lsp_types::{Diagnostic, DiagnosticSeverity};
+
+/// Push in `calls` the names of all the nodes called by `expr`,
+/// in the order of the calls.
+pub(crate) fn called_nodes<'a>(expr: &'a Expr, calls: &mut Vec<&'a Span>) {
+    match expr {
+        Expr::Variable(_) | Expr::Lit(_) => {}
+        Expr::BinOp { lhs, rhs, .. } => {
+            called_nodes(lhs, calls);
+            called_nodes(rhs, calls);
+        }
+        Expr::UnaryOp { rhs, .. } => called_nodes(rhs, calls),
+        Expr::If { cond, yes, no } => {
+            called_nodes(cond, calls);
+            called_nodes(yes, calls);
+            called_nodes(no, calls);
+        }
+        Expr::Index { expr, index } => {
+            called_nodes(expr, calls);
+            called_nodes(index, calls);
+        }
+        Expr::Array(exprs) | Expr::Tuple(exprs) => {
+            for expr in exprs {
+                called_nodes(expr, calls);
+            }
+        }
+        Expr::FCall { name, args } => {
+            calls.push(name);
+            for expr in args {
+                called_nodes(expr, calls);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Visit {
+    NotVisited,
+    InProgress,
+    Done,
+}
+
+/// Calls between the nodes of a file.
+pub(crate) struct CallGraph<'a> {
+    nodes: &'a [Node],
+    /// For each node, the index of the called nodes with the name used in the call
+    calls: Vec<Vec<(usize, &'a Span)>>,
+}
+
+impl<'a> CallGraph<'a> {
+    pub(crate) fn new(nodes: &'a [Node]) -> Self {
+        let calls = nodes
+            .iter()
+            .map(|node| {
+                let mut names = vec![];
+                for expr in node.exprs() {
+                    called_nodes(expr, &mut names);
+                }
+                // Unknown nodes are reported while checking types
+                names
+                    .into_iter()
+                    .filter_map(|name| {
+                        let index = nodes.iter().position(|node| &node.name == name)?;
+                        Some((index, name))
+                    })
+                    .collect()
+            })
+            .collect();
+        Self { nodes, calls }
+    }
+
+    /// Whether node `i` is called by another node
+    pub(crate) fn is_called(&self, i: usize) -> bool {
+        self.calls
+            .iter()
+            .enumerate()
+            .any(|(caller, calls)| caller != i && calls.iter().any(|(j, _)| *j == i))
+    }
+    /// Number of nodes called by node `i`, directly or not
+    pub(crate) fn reachable(&self, i: usize) -> usize {
+        let mut seen = vec![false; self.nodes.len()];
+        let mut pile = vec![i];
+        while let Some(k) = pile.pop() {
+            for (j, _) in self.calls[k].iter() {
+                if !seen[*j] {
+                    seen[*j] = true;
+                    pile.push(*j);
+                }
+            }
+        }
+        seen.iter()
+            .enumerate()
+            .filter(|(j, seen)| **seen && *j != i)
+            .count()
+    }
+
+    /// Index of the nodes sorted so that a node comes after all the nodes
+    /// it calls, and the diagnostics of the recursive calls.
+    ///
+    /// Nodes that do not depend on each other keep the order of the file.
+    pub(crate) fn topological_order(&self) -> (Vec<usize>, Vec<Diagnostic>) {
+        let mut visits = vec![Visit::NotVisited; self.nodes.len()];
+        let mut order = vec![];
+        let mut diags = vec![];
+        let mut reported = vec![];
+        for i in 0..self.nodes.len() {
+            if visits[i] == Visit::NotVisited {
+                self.visit(
+                    i,
+                    &mut visits,
+                    &mut vec![],
+                    &mut order,
+                    &mut diags,
+                    &mut reported,
+                );
+            }
+        }
+        (order, diags)
+    }
+
+    /// Depth first search from node `i`, `path` holds the nodes being visited
+    /// with the call leading to the next one.
+    fn visit(
+        &self,
+        i: usize,
+        visits: &mut [Visit],
+        path: &mut Vec<(usize, &'a Span)>,
+        order: &mut Vec<usize>,
+        diags: &mut Vec<Diagnostic>,
+        reported: &mut Vec<Vec<usize>>,
+    ) {
+        visits[i] = Visit::InProgress;
+        for &(j, call) in self.calls[i].iter() {
+            match visits[j] {
+                Visit::Done => {}
+                Visit::NotVisited => {
+                    path.push((i, call));
+                    self.visit(j, visits, path, order, diags, reported);
+                    path.pop();
+                }
+                Visit::InProgress => {
+                    let start = path.iter().position(|(k, _)| *k == j).unwrap_or(path.len());
+                    let mut cycle = path[start..].to_vec();
+                    cycle.push((i, call));
+
+                    // The same cycle can be reached from several nodes
+                    let mut cycle_nodes: Vec<usize> = cycle.iter().map(|(k, _)| *k).collect();
+                    cycle_nodes.sort();
+                    if reported.contains(&cycle_nodes) {
+                        continue;
+                    }
+                    reported.push(cycle_nodes);
+
+                    let mut s = String::new();
+                    for (k, _) in cycle.iter() {
+                        s.push_str(&format!("{} -> ", self.nodes[*k].name));
+                    }
+                    diags.push(Diagnostic {
+                        message: format!(
+                            "Recursive call of '{call}' is not allowed. All cycle : {s}{call}"
+                        ),
+                        severity: Some(DiagnosticSeverity::ERROR),
+                        range: call.to_range(),
+                        code: Some(ErrorCode::RecursiveCall.into()),
+                        related_information: (cycle.len() > 1).then(|| {
+                            cycle[..cycle.len() - 1]
+                                .iter()
+                                .map(|(_, call)| {
+                                    related(call.to_range(), format!("'{call}' is called here."))
+                                })
+                                .collect()
+                        }),
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+        visits[i] = Visit::Done;
+        order.push(i);
+    }
+}
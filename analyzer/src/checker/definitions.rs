@@ -0,0 +1,268 @@
+use crate::{
+    ast::{automaton::Automaton, node::Node, to_range::ToRange},
+    checker::{
+        error_code::{ErrorCode, related},
+        quick_fix::{QuickFix, default_literal},
+    },
+    parser::{span::Ident, var_type::VarType},
+};
+use lsp_types::{Diagnostic, DiagnosticSeverity, Position, Range, TextEdit};
+
+/// Edit removing the equation of `name`, up to its semicolon.
+///
+/// Returns `None` when the semicolon is shared with other variables
+/// (tuple equation), removing it would remove the other definitions too.
+fn remove_equation(node: &Node, index: usize, name: &Ident) -> Option<QuickFix> {
+    let semicolon = node.span_semicolon_equations.get(index)?;
+    let shared = node
+        .span_semicolon_equations
+        .iter()
+        .filter(|s| s.eq_exact(semicolon))
+        .count();
+    if shared > 1 {
+        return None;
+    }
+    Some(QuickFix::new(
+        format!("Remove the equation of '{name}'"),
+        TextEdit {
+            range: Range {
+                start: name.to_range().start,
+                end: semicolon.to_range().end,
+            },
+            new_text: String::new(),
+        },
+    ))
+}
+
+/// Edit adding a default equation for `name` just before `tel`.
+fn add_equation(node: &Node, name: &Ident, literal: String) -> QuickFix {
+    let line = node.span_tel.to_range().start.line;
+    QuickFix::new(
+        format!("Add an equation for '{name}'"),
+        TextEdit {
+            range: Range {
+                start: Position { line, character: 0 },
+                end: Position { line, character: 0 },
+            },
+            new_text: format!("\t{name} = {literal};\n"),
+        },
+    )
+}
+
+/// Declaration of `name` among `decls`.
+fn declaration<'a>(decls: &'a [(Ident, VarType)], name: &Ident) -> Option<&'a Ident> {
+    decls.iter().map(|(n, _)| n).find(|n| *n == name)
+}
+
+/// Check that the equations of a node define its outputs and local
+/// variables exactly once and nothing else.
+pub(crate) fn check_definitions(node: &Node) -> Vec<Diagnostic> {
+    let mut diags = vec![];
+    let mut defined: Vec<&Ident> = vec![];
+
+    for (index, (name, _)) in node.let_bindings.iter().enumerate() {
+        let fix = remove_equation(node, index, name);
+        if let Some(input) = declaration(&node.inputs, name) {
+            diags.push(Diagnostic {
+                message: format!(
+                    "'{name}' is an input of '{}', it cannot be defined by an equation.",
+                    node.name
+                ),
+                severity: Some(DiagnosticSeverity::ERROR),
+                range: name.to_range(),
+                code: Some(ErrorCode::InputDefinition.into()),
+                related_information: Some(vec![related(
+                    input.to_range(),
+                    format!("'{input}' is declared here."),
+                )]),
+                data: QuickFix::to_data(fix.into_iter().collect()),
+                ..Default::default()
+            });
+        } else if let Some(first) = defined.iter().find(|n| **n == name) {
+            diags.push(Diagnostic {
+                message: format!("Equation for '{name}' already defined."),
+                severity: Some(DiagnosticSeverity::ERROR),
+                range: name.to_range(),
+                code: Some(ErrorCode::DuplicateEquation.into()),
+                related_information: Some(vec![related(
+                    first.to_range(),
+                    "First definition is here.".to_string(),
+                )]),
+                data: QuickFix::to_data(fix.into_iter().collect()),
+                ..Default::default()
+            });
+        } else {
+            // Without a `var` section, local variables are declared implicitly
+            if !node.vars.is_empty()
+                && declaration(&node.outputs, name).is_none()
+                && declaration(&node.vars, name).is_none()
+            {
+                diags.push(Diagnostic {
+                    message: format!(
+                        "'{name}' is neither an output nor declared in the var section of '{}'.",
+                        node.name
+                    ),
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    range: name.to_range(),
+                    code: Some(ErrorCode::UndeclaredVariable.into()),
+                    related_information: node.span_var.as_ref().map(|var| {
+                        vec![related(
+                            var.to_range(),
+                            "Local variables are declared here.".to_string(),
+                        )]
+                    }),
+                    ..Default::default()
+                });
+            }
+            defined.push(name);
+        }
+    }
+
+    for automaton in node.automata.iter() {
+        diags.extend(check_automaton(node, automaton, &mut defined));
+    }
+
+    for (name, t) in node.outputs.iter().chain(node.vars.iter()) {
+        if defined.contains(&name) {
+            continue;
+        }
+        let kind = if declaration(&node.outputs, name).is_some() {
+            "Output"
+        } else {
+            "Variable"
+        };
+        let fix = default_literal(&t.inner).map(|literal| add_equation(node, name, literal));
+        diags.push(Diagnostic {
+            message: format!("{kind} '{name}' is never defined by an equation."),
+            severity: Some(DiagnosticSeverity::ERROR),
+            range: name.to_range(),
+            code: Some(ErrorCode::MissingEquation.into()),
+            data: QuickFix::to_data(fix.into_iter().collect()),
+            ..Default::default()
+        });
+    }
+    diags
+}
+
+/// Check the states of an automaton and the variables it defines.
+///
+/// Variables of an automaton must be declared and defined in every state,
+/// they can not be defined elsewhere in the node.
+fn check_automaton<'a>(
+    node: &Node,
+    automaton: &'a Automaton,
+    defined: &mut Vec<&'a Ident>,
+) -> Vec<Diagnostic> {
+    let mut diags = vec![];
+    for (index, state) in automaton.states.iter().enumerate() {
+        if let Some(first) = automaton.states[..index]
+            .iter()
+            .find(|other| other.name == state.name)
+        {
+            diags.push(Diagnostic {
+                message: format!("State '{}' already declared.", state.name),
+                severity: Some(DiagnosticSeverity::ERROR),
+                range: state.name.to_range(),
+                code: Some(ErrorCode::DuplicateDeclaration.into()),
+                related_information: Some(vec![related(
+                    first.name.to_range(),
+                    "First declaration is here.".to_string(),
+                )]),
+                ..Default::default()
+            });
+        }
+        for transition in state.unless.iter().chain(state.until.iter()) {
+            if automaton.state(&transition.target).is_none() {
+                diags.push(Diagnostic {
+                    message: format!("No state '{}' in this automaton.", transition.target),
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    range: transition.target.to_range(),
+                    code: Some(ErrorCode::UnknownState.into()),
+                    ..Default::default()
+                });
+            }
+        }
+        for (index, (name, _)) in state.let_bindings.iter().enumerate() {
+            if let Some((first, _)) = state.let_bindings[..index].iter().find(|(n, _)| n == name) {
+                diags.push(Diagnostic {
+                    message: format!(
+                        "Equation for '{name}' already defined in state '{}'.",
+                        state.name
+                    ),
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    range: name.to_range(),
+                    code: Some(ErrorCode::DuplicateEquation.into()),
+                    related_information: Some(vec![related(
+                        first.to_range(),
+                        "First definition is here.".to_string(),
+                    )]),
+                    ..Default::default()
+                });
+            }
+        }
+    }
+
+    for name in automaton.defined() {
+        if let Some(input) = declaration(&node.inputs, name) {
+            diags.push(Diagnostic {
+                message: format!(
+                    "'{name}' is an input of '{}', it cannot be defined by an automaton.",
+                    node.name
+                ),
+                severity: Some(DiagnosticSeverity::ERROR),
+                range: name.to_range(),
+                code: Some(ErrorCode::InputDefinition.into()),
+                related_information: Some(vec![related(
+                    input.to_range(),
+                    format!("'{input}' is declared here."),
+                )]),
+                ..Default::default()
+            });
+            continue;
+        }
+        if let Some(first) = defined.iter().find(|n| **n == name) {
+            diags.push(Diagnostic {
+                message: format!("Equation for '{name}' already defined."),
+                severity: Some(DiagnosticSeverity::ERROR),
+                range: name.to_range(),
+                code: Some(ErrorCode::DuplicateEquation.into()),
+                related_information: Some(vec![related(
+                    first.to_range(),
+                    "First definition is here.".to_string(),
+                )]),
+                ..Default::default()
+            });
+            continue;
+        }
+        // The type of the variable is only known from its declaration
+        if declaration(&node.outputs, name).is_none() && declaration(&node.vars, name).is_none() {
+            diags.push(Diagnostic {
+                message: format!(
+                    "'{name}' is defined by an automaton, it must be an output or declared in the var section of '{}'.",
+                    node.name
+                ),
+                severity: Some(DiagnosticSeverity::ERROR),
+                range: name.to_range(),
+                code: Some(ErrorCode::UndeclaredVariable.into()),
+                ..Default::default()
+            });
+        }
+        for state in automaton.states.iter() {
+            if state.let_bindings.iter().all(|(n, _)| n != name) {
+                diags.push(Diagnostic {
+                    message: format!("'{name}' is never defined in state '{}'.", state.name),
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    range: state.name.to_range(),
+                    code: Some(ErrorCode::MissingEquation.into()),
+                    related_information: Some(vec![related(
+                        name.to_range(),
+                        format!("'{name}' is defined by the automaton here."),
+                    )]),
+                    ..Default::default()
+                });
+            }
+        }
+        defined.push(name);
+    }
+    diags
+}
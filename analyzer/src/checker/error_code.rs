@@ -0,0 +1,152 @@
+use lsp_types::{DiagnosticRelatedInformation, Location, NumberOrString, Range, Uri};
+use std::str::FromStr;
+
+/// Placeholder document used in the related information of diagnostics.
+///
+/// The analyzer only knows about one file at a time, the LSP replaces this
+/// uri by the one of the document being checked.
+pub const DOCUMENT_URI: &str = "lustre:document";
+
+/// Stable codes of the diagnostics emitted by the type checker.
+///
+/// The numbers must never be reused for another kind of error, editors and
+/// users rely on them to filter diagnostics.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorCode {
+    /// Both sides of an operator have incompatible types.
+    MismatchedOperands,
+    /// Arithmetic operator used on something else than an integer or `float`.
+    ExpectedNumeric,
+    /// Boolean operator used on something else than `bool`.
+    ExpectedBool,
+    /// An `int` was expected (index, right side of `^`), or an integer
+    /// type for bitwise operators.
+    ExpectedInt,
+    /// A value that is not initialized at the first instant is used.
+    NotInitialized,
+    /// `pre` applied on a value already not initialized.
+    DoublePre,
+    /// Unary operator not defined for the type of its operand.
+    InvalidUnaryOperand,
+    /// Index outside the bounds of a tuple or an array.
+    IndexOutOfBounds,
+    /// Index not known at compile time.
+    UnknownIndex,
+    /// Elements of an array literal have different types.
+    HeterogeneousArray,
+    /// Condition of an `if` is not a `bool`.
+    IfConditionNotBool,
+    /// Branches of an `if` have different types.
+    IfBranchesMismatch,
+    /// Length of an array argument not known at compile time.
+    UnknownArrayLength,
+    /// A node calls itself, directly or through other nodes.
+    RecursiveCall,
+    /// A node is called before being defined.
+    ///
+    /// No longer emitted, nodes can be declared in any order.
+    CallBeforeDefinition,
+    /// Wrong number of arguments in a call.
+    ArgumentCount,
+    /// Wrong type of argument in a call.
+    ArgumentType,
+    /// Array arguments of a lifted call have different lengths.
+    ArgumentArrayLength,
+    /// Called node does not exist.
+    UnknownNode,
+    /// Variable without any equation.
+    UnknownVariable,
+    /// Type of a variable depends on itself.
+    CyclicInference,
+    /// Instantaneous cycle between equations.
+    CyclicDefinition,
+    /// Output or local variable type differs from its declaration.
+    OutputTypeMismatch,
+    /// Two equations for the same variable.
+    DuplicateEquation,
+    /// Two inputs, outputs or vars with the same name.
+    DuplicateDeclaration,
+    /// Two nodes with the same name.
+    DuplicateNode,
+    /// Output or declared local variable without any equation.
+    MissingEquation,
+    /// Equation defining an input.
+    InputDefinition,
+    /// Equation defining a name absent from the `var` section.
+    UndeclaredVariable,
+    /// Integer literal outside the range of the expected sized integer.
+    LiteralOutOfRange,
+    /// Integer division by a constant zero.
+    DivisionByZero,
+    /// Constant integer arithmetic overflowing with `#![overflow(trapping)]`.
+    ArithmeticOverflow,
+    /// Shift amount or bit index outside the width of the integer type.
+    ShiftOutOfRange,
+    /// Transition to a state absent from the automaton.
+    UnknownState,
+}
+
+impl ErrorCode {
+    pub fn code(&self) -> &'static str {
+        use ErrorCode::*;
+        match self {
+            MismatchedOperands => "E0001",
+            ExpectedNumeric => "E0002",
+            ExpectedBool => "E0003",
+            ExpectedInt => "E0004",
+            NotInitialized => "E0005",
+            DoublePre => "E0006",
+            InvalidUnaryOperand => "E0007",
+            IndexOutOfBounds => "E0008",
+            UnknownIndex => "E0009",
+            HeterogeneousArray => "E0010",
+            IfConditionNotBool => "E0011",
+            IfBranchesMismatch => "E0012",
+            UnknownArrayLength => "E0013",
+            RecursiveCall => "E0014",
+            CallBeforeDefinition => "E0015",
+            ArgumentCount => "E0016",
+            ArgumentType => "E0017",
+            ArgumentArrayLength => "E0018",
+            UnknownNode => "E0019",
+            UnknownVariable => "E0020",
+            CyclicInference => "E0021",
+            CyclicDefinition => "E0022",
+            OutputTypeMismatch => "E0023",
+            DuplicateEquation => "E0024",
+            DuplicateDeclaration => "E0025",
+            DuplicateNode => "E0026",
+            MissingEquation => "E0027",
+            InputDefinition => "E0028",
+            UndeclaredVariable => "E0029",
+            LiteralOutOfRange => "E0030",
+            DivisionByZero => "E0031",
+            ArithmeticOverflow => "E0032",
+            ShiftOutOfRange => "E0033",
+            UnknownState => "E0034",
+        }
+    }
+}
+
+impl From<ErrorCode> for NumberOrString {
+    fn from(code: ErrorCode) -> Self {
+        NumberOrString::String(code.code().to_string())
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+/// Build the related information of a diagnostic pointing at `range`.
+pub fn related(range: Range, message: String) -> DiagnosticRelatedInformation {
+    DiagnosticRelatedInformation {
+        location: Location {
+            uri: Uri::from_str(DOCUMENT_URI).unwrap(),
+            range,
+        },
+        message,
+    }
+}
@@ -1,6 +1,10 @@
 use crate::{
     ast::{literal::Value, node::Node, to_range::ToRange},
-    parser::{span::Ident, var_type::VarType},
+    checker::error_code::ErrorCode,
+    parser::{
+        span::Ident,
+        var_type::{InnerVarType, VarType},
+    },
 };
 use indexmap::IndexMap;
 use lsp_types::{Diagnostic, DiagnosticSeverity};
@@ -22,7 +26,11 @@ impl FunctionType {
     pub fn function_call_type(self, args: &Vec<Value>) -> Option<FunctionCallType> {
         let mut res = None;
         for (arg, (_, input_type)) in args.iter().zip(self.inputs.iter()) {
-            let arg_type = arg.get_type();
+            // Integer literals given to sized integer inputs have been checked
+            let arg_type = match (arg, &input_type.inner) {
+                (Value::Int(_), InnerVarType::SizedInt(_)) => input_type.clone(),
+                _ => arg.get_type(),
+            };
             match res {
                 None => {
                     if &arg_type == input_type {
@@ -47,6 +55,13 @@ impl FunctionType {
         }
         res
     }
+    pub(crate) fn new(inputs: IndexMap<Ident, VarType>, outputs: IndexMap<Ident, VarType>) -> Self {
+        Self {
+            inputs,
+            outputs,
+            vars: HashMap::new(),
+        }
+    }
     pub(crate) fn get_function_type(node: &Node) -> (Self, Vec<Diagnostic>) {
         let mut diags = vec![];
         let mut func = FunctionType {
@@ -60,6 +75,7 @@ impl FunctionType {
                     message: "Input name already used.".to_string(),
                     severity: Some(DiagnosticSeverity::ERROR),
                     range: name.to_range(),
+                    code: Some(ErrorCode::DuplicateDeclaration.into()),
                     ..Default::default()
                 })
             } else {
@@ -72,6 +88,7 @@ impl FunctionType {
                     message: "Output name already used.".to_string(),
                     severity: Some(DiagnosticSeverity::ERROR),
                     range: name.to_range(),
+                    code: Some(ErrorCode::DuplicateDeclaration.into()),
                     ..Default::default()
                 })
             } else {
@@ -84,6 +101,7 @@ impl FunctionType {
                     message: "Var name already used.".to_string(),
                     severity: Some(DiagnosticSeverity::ERROR),
                     range: name.to_range(),
+                    code: Some(ErrorCode::DuplicateDeclaration.into()),
                     ..Default::default()
                 })
             } else {
@@ -0,0 +1,411 @@
+use crate::{
+    ast::{
+        ast::Ast, binop::BinOp, expression::Expr, literal::Value, node::Node, to_range::ToRange,
+    },
+    checker::call_graph::CallGraph,
+    parser::{span::Span, var_type::InnerVarType},
+};
+use lsp_types::{Diagnostic, DiagnosticSeverity, DiagnosticTag, NumberOrString, Range};
+use std::collections::HashMap;
+
+/// Lints checked by [Ast::lint] on a program without typing errors.
+///
+/// A lint can be allowed for a node with the attribute `#[allow(name)]`,
+/// where `name` is the name of the lint or of its group.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Lint {
+    /// Local variable that is never read.
+    UnusedVariables,
+    /// Input that is never read.
+    UnusedInputs,
+    /// Node that is never called, except the entry point of the file and tests.
+    UnusedNodes,
+    /// Equation that no output depends on, it is never computed.
+    UnreachableEquations,
+    /// `x -> x` is always `x`.
+    RedundantArrow,
+    /// Condition of an `if` that is always true or always false.
+    ConstantCondition,
+    /// Comparison that is always true or always false.
+    ConstantComparison,
+    /// Name in `#[allow(...)]` that is not a lint.
+    UnknownLints,
+}
+
+impl Lint {
+    pub const ALL: [Lint; 8] = [
+        Lint::UnusedVariables,
+        Lint::UnusedInputs,
+        Lint::UnusedNodes,
+        Lint::UnreachableEquations,
+        Lint::RedundantArrow,
+        Lint::ConstantCondition,
+        Lint::ConstantComparison,
+        Lint::UnknownLints,
+    ];
+    pub fn name(&self) -> &'static str {
+        use Lint::*;
+        match self {
+            UnusedVariables => "unused_variables",
+            UnusedInputs => "unused_inputs",
+            UnusedNodes => "unused_nodes",
+            UnreachableEquations => "unreachable_equations",
+            RedundantArrow => "redundant_arrow",
+            ConstantCondition => "constant_condition",
+            ConstantComparison => "constant_comparison",
+            UnknownLints => "unknown_lints",
+        }
+    }
+    /// Group of the lint, allowing a group allows all its lints.
+    pub fn group(&self) -> Option<&'static str> {
+        use Lint::*;
+        match self {
+            UnusedVariables | UnusedInputs | UnusedNodes | UnreachableEquations => Some("unused"),
+            RedundantArrow | ConstantCondition | ConstantComparison | UnknownLints => None,
+        }
+    }
+    pub fn default_level(&self) -> LintLevel {
+        match self {
+            Lint::RedundantArrow => LintLevel::Hint,
+            _ => LintLevel::Warn,
+        }
+    }
+    fn is_named(&self, name: &str) -> bool {
+        self.name() == name || self.group() == Some(name)
+    }
+}
+
+impl std::fmt::Display for Lint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LintLevel {
+    Allow,
+    Hint,
+    Warn,
+}
+
+impl LintLevel {
+    fn severity(&self) -> Option<DiagnosticSeverity> {
+        match self {
+            LintLevel::Allow => None,
+            LintLevel::Hint => Some(DiagnosticSeverity::HINT),
+            LintLevel::Warn => Some(DiagnosticSeverity::WARNING),
+        }
+    }
+}
+
+/// Level of every lint for a whole file, `#[allow(...)]` attributes
+/// can still allow a lint for a given node.
+#[derive(Clone, Debug)]
+pub struct LintConfig {
+    levels: HashMap<Lint, LintLevel>,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self {
+            levels: Lint::ALL
+                .iter()
+                .map(|lint| (*lint, lint.default_level()))
+                .collect(),
+        }
+    }
+}
+
+impl LintConfig {
+    pub fn set_level(&mut self, lint: Lint, level: LintLevel) {
+        self.levels.insert(lint, level);
+    }
+    pub fn level(&self, lint: Lint) -> LintLevel {
+        self.levels
+            .get(&lint)
+            .copied()
+            .unwrap_or(lint.default_level())
+    }
+}
+
+/// Push in `vars` all the variables read by `expr`.
+fn read_variables<'a>(expr: &'a Expr, vars: &mut Vec<&'a Span>) {
+    match expr {
+        Expr::Variable(var) => vars.push(var),
+        Expr::Lit(_) => {}
+        Expr::BinOp { lhs, rhs, .. } => {
+            read_variables(lhs, vars);
+            read_variables(rhs, vars);
+        }
+        Expr::UnaryOp { rhs, .. } => read_variables(rhs, vars),
+        Expr::If { cond, yes, no } => {
+            read_variables(cond, vars);
+            read_variables(yes, vars);
+            read_variables(no, vars);
+        }
+        Expr::Index { expr, index } => {
+            read_variables(expr, vars);
+            read_variables(index, vars);
+        }
+        Expr::Array(exprs)
+        | Expr::Tuple(exprs)
+        | Expr::FCall {
+            name: _,
+            args: exprs,
+        } => {
+            for expr in exprs {
+                read_variables(expr, vars);
+            }
+        }
+    }
+}
+
+struct Linter<'a> {
+    ast: &'a Ast,
+    config: &'a LintConfig,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Linter<'a> {
+    fn push_lint(&mut self, node: &Node, lint: Lint, range: Range, message: String) {
+        if node
+            .allow
+            .iter()
+            .any(|name| lint.is_named(&name.fragment()))
+        {
+            return;
+        }
+        let Some(severity) = self.config.level(lint).severity() else {
+            return;
+        };
+        self.diagnostics.push(Diagnostic {
+            message,
+            severity: Some(severity),
+            range,
+            code: Some(NumberOrString::String(lint.name().to_string())),
+            tags: (lint.group() == Some("unused")).then(|| vec![DiagnosticTag::UNNECESSARY]),
+            ..Default::default()
+        });
+    }
+
+    fn lint_unknown_lints(&mut self, node: &Node) {
+        for name in node.allow.iter() {
+            if !Lint::ALL.iter().any(|lint| lint.is_named(&name.fragment())) {
+                self.push_lint(
+                    node,
+                    Lint::UnknownLints,
+                    name.to_range(),
+                    format!("Unknown lint '{name}'."),
+                );
+            }
+        }
+    }
+
+    fn lint_unused(&mut self, node: &Node) {
+        let mut read = vec![];
+        for expr in node.exprs() {
+            read_variables(expr, &mut read);
+        }
+        // Names starting with '_' are unused on purpose
+        let unused = |name: &Span| !name.fragment().starts_with('_') && !read.contains(&name);
+
+        let mut unused_vars = vec![];
+        for (input, t) in node.inputs.iter() {
+            if t.inner != InnerVarType::Unit && unused(input) {
+                self.push_lint(
+                    node,
+                    Lint::UnusedInputs,
+                    input.to_range(),
+                    format!("Input '{input}' is never used."),
+                );
+            }
+        }
+        let locals = node
+            .vars
+            .iter()
+            .map(|(var, _)| var)
+            .chain(node.let_bindings.iter().map(|(var, _)| var))
+            .filter(|var| node.outputs.iter().all(|(out, _)| out != *var));
+        for var in locals {
+            if unused(var) && !unused_vars.contains(&var) {
+                unused_vars.push(var);
+            }
+        }
+        for var in unused_vars.iter() {
+            self.push_lint(
+                node,
+                Lint::UnusedVariables,
+                var.to_range(),
+                format!("Variable '{var}' is never used."),
+            );
+        }
+
+        // Equations reachable from the outputs and the checked expressions,
+        // the others are never computed
+        let mut reachable: Vec<&Span> = node.outputs.iter().map(|(out, _)| out).collect();
+        for expr in node.checked_exprs() {
+            read_variables(expr, &mut reachable);
+        }
+        let mut i = 0;
+        while i < reachable.len() {
+            let mut read = vec![];
+            for (name, expr) in node.let_bindings.iter() {
+                if name == reachable[i] {
+                    read_variables(expr, &mut read);
+                }
+            }
+            // Variables of an automaton depend on the whole automaton
+            for automaton in node.automata.iter() {
+                if automaton.defined().contains(&reachable[i]) {
+                    for expr in automaton.exprs() {
+                        read_variables(expr, &mut read);
+                    }
+                }
+            }
+            for var in read {
+                if !reachable.contains(&var) {
+                    reachable.push(var);
+                }
+            }
+            i += 1;
+        }
+        for (name, _) in node.let_bindings.iter() {
+            if !reachable.contains(&name) && !unused_vars.contains(&name) {
+                self.push_lint(
+                    node,
+                    Lint::UnreachableEquations,
+                    name.to_range(),
+                    format!(
+                        "Equation of '{name}' is never computed, no output of '{}' depends on it.",
+                        node.name
+                    ),
+                );
+            }
+        }
+    }
+
+    fn lint_expr(&mut self, node: &Node, expr: &Expr) {
+        match expr {
+            Expr::BinOp {
+                lhs,
+                op: BinOp::Arrow,
+                span_op,
+                rhs,
+            } if lhs == rhs => {
+                self.push_lint(
+                    node,
+                    Lint::RedundantArrow,
+                    span_op.to_range(),
+                    format!("'{lhs} -> {rhs}' is always equal to '{lhs}'."),
+                );
+                self.lint_expr(node, lhs);
+            }
+            Expr::BinOp {
+                lhs,
+                op: BinOp::Eq | BinOp::Neq,
+                span_op,
+                rhs,
+            } if !node.is_test() => match self.ast.const_value(node, expr) {
+                Some(Value::Bool(b)) => self.push_lint(
+                    node,
+                    Lint::ConstantComparison,
+                    span_op.to_range(),
+                    format!("This comparison is always {b}."),
+                ),
+                _ => {
+                    self.lint_expr(node, lhs);
+                    self.lint_expr(node, rhs);
+                }
+            },
+            Expr::If { cond, yes, no } => {
+                match self.ast.const_value(node, cond) {
+                    Some(Value::Bool(b)) if !node.is_test() => self.push_lint(
+                        node,
+                        Lint::ConstantCondition,
+                        cond.to_range(),
+                        format!("This condition is always {b}."),
+                    ),
+                    _ => self.lint_expr(node, cond),
+                }
+                self.lint_expr(node, yes);
+                self.lint_expr(node, no);
+            }
+            Expr::BinOp { lhs, rhs, .. } => {
+                self.lint_expr(node, lhs);
+                self.lint_expr(node, rhs);
+            }
+            Expr::UnaryOp { rhs, .. } => self.lint_expr(node, rhs),
+            Expr::Index { expr, index } => {
+                self.lint_expr(node, expr);
+                self.lint_expr(node, index);
+            }
+            Expr::Array(exprs)
+            | Expr::Tuple(exprs)
+            | Expr::FCall {
+                name: _,
+                args: exprs,
+            } => {
+                for expr in exprs {
+                    self.lint_expr(node, expr);
+                }
+            }
+            Expr::Variable(_) | Expr::Lit(_) => {}
+        }
+    }
+
+    /// The nodes that are never called are the roots of the call graph, the
+    /// entry point is `main` or else the root calling the most nodes
+    fn lint_unused_nodes(&mut self) {
+        let graph = CallGraph::new(&self.ast.nodes);
+        let roots: Vec<usize> = (self.ast.prelude_len..self.ast.nodes.len())
+            .filter(|i| !self.ast.nodes[*i].is_test() && !graph.is_called(*i))
+            .collect();
+        let entry = roots
+            .iter()
+            .find(|i| self.ast.nodes[**i].name.fragment() == "main")
+            .or_else(|| roots.iter().max_by_key(|i| graph.reachable(**i)))
+            .copied();
+        for i in roots {
+            if Some(i) == entry {
+                continue;
+            }
+            let node = &self.ast.nodes[i];
+            self.push_lint(
+                node,
+                Lint::UnusedNodes,
+                node.name.to_range(),
+                format!("Node '{}' is never called.", node.name),
+            );
+        }
+    }
+
+    fn lint_ast(&mut self) {
+        for node in self.ast.user_nodes() {
+            self.lint_unknown_lints(node);
+            self.lint_unused(node);
+            for expr in node.exprs() {
+                self.lint_expr(node, expr);
+            }
+        }
+        self.lint_unused_nodes();
+    }
+}
+
+impl Ast {
+    /// Warnings and hints of the default [LintConfig].
+    ///
+    /// The program is expected to type check, lints are not errors
+    /// and are never returned by [Ast::check].
+    pub fn lint(&self) -> Vec<Diagnostic> {
+        self.lint_with(&LintConfig::default())
+    }
+    pub fn lint_with(&self, config: &LintConfig) -> Vec<Diagnostic> {
+        let mut linter = Linter {
+            ast: self,
+            config,
+            diagnostics: vec![],
+        };
+        linter.lint_ast();
+        linter.diagnostics
+    }
+}
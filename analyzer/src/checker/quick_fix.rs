@@ -0,0 +1,106 @@
+use crate::{
+    ast::{expression::Expr, to_range::ToRange, unary_op::UnaryOp},
+    checker::infer_types::InferLen,
+    parser::var_type::{InnerVarType, VarType},
+};
+use lsp_types::{Diagnostic, TextEdit};
+use serde_derive::{Deserialize, Serialize};
+
+/// Edit fixing the problem reported by a diagnostic.
+///
+/// Quick fixes are stored in the `data` field of the diagnostic so that
+/// the LSP can turn them into code actions without checking the file again.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct QuickFix {
+    pub title: String,
+    pub edit: TextEdit,
+}
+
+impl QuickFix {
+    pub fn new(title: String, edit: TextEdit) -> Self {
+        Self { title, edit }
+    }
+    pub fn to_data(fixes: Vec<QuickFix>) -> Option<serde_json::Value> {
+        if fixes.is_empty() {
+            None
+        } else {
+            serde_json::to_value(fixes).ok()
+        }
+    }
+    pub fn from_diagnostic(diag: &Diagnostic) -> Vec<QuickFix> {
+        match &diag.data {
+            Some(data) => serde_json::from_value(data.clone()).unwrap_or_default(),
+            None => vec![],
+        }
+    }
+}
+
+/// Literal of the given type that can be written in place of a missing value.
+pub(crate) fn default_literal(t: &InnerVarType) -> Option<String> {
+    match t {
+        InnerVarType::Unit => Some("()".to_string()),
+        InnerVarType::Int | InnerVarType::SizedInt(_) => Some("0".to_string()),
+        InnerVarType::Float => Some("0.0".to_string()),
+        InnerVarType::Bool => Some("false".to_string()),
+        InnerVarType::Char => Some("' '".to_string()),
+        InnerVarType::String => Some("\"\"".to_string()),
+        InnerVarType::Tuple(v) => {
+            let literals = v.iter().map(default_literal).collect::<Option<Vec<_>>>()?;
+            Some(format!("({})", literals.join(", ")))
+        }
+        InnerVarType::Array {
+            t,
+            len: InferLen::Known(len),
+        } => Some(format!("{} ^ {len}", default_literal(t)?)),
+        InnerVarType::Array {
+            t: _,
+            len: InferLen::Unknown,
+        } => None,
+    }
+}
+
+/// Edit applying the function like operator `op` on `expr`.
+fn wrap_expr(expr: &Expr, op: UnaryOp) -> QuickFix {
+    QuickFix::new(
+        format!("Convert `{expr}` with `{op}(...)`"),
+        TextEdit {
+            range: expr.to_range(),
+            new_text: format!("{op}({expr})"),
+        },
+    )
+}
+
+/// Conversions making numeric operands compatible, the other operand
+/// of a sized integer is converted to its type.
+pub(crate) fn numeric_conversions(
+    (lhs, lt): (&Expr, &VarType),
+    (rhs, rt): (&Expr, &VarType),
+) -> Vec<QuickFix> {
+    match (&lt.inner, &rt.inner) {
+        (InnerVarType::SizedInt(kind), t) if t.is_numeric() => {
+            return vec![wrap_expr(rhs, UnaryOp::ToSized(*kind))];
+        }
+        (t, InnerVarType::SizedInt(kind)) if t.is_numeric() => {
+            return vec![wrap_expr(lhs, UnaryOp::ToSized(*kind))];
+        }
+        _ => {}
+    }
+    let to_float = |t: &VarType| t.inner.map_scalars(|_| InnerVarType::Float);
+    let is_int = |t: &VarType| t.inner.all_scalars(|t| t == &InnerVarType::Int);
+    let is_float = |t: &VarType| t.inner.all_scalars(|t| t == &InnerVarType::Float);
+    if to_float(lt) != to_float(rt) {
+        vec![]
+    } else if is_int(lt) && is_float(rt) {
+        vec![
+            wrap_expr(lhs, UnaryOp::ToFloat),
+            wrap_expr(rhs, UnaryOp::ToInt),
+        ]
+    } else if is_float(lt) && is_int(rt) {
+        vec![
+            wrap_expr(rhs, UnaryOp::ToFloat),
+            wrap_expr(lhs, UnaryOp::ToInt),
+        ]
+    } else {
+        vec![]
+    }
+}
@@ -2,14 +2,24 @@ use crate::{
     ast::{
         ast::Ast,
         ast_types::AstTypes,
+        automaton::Automaton,
         binop::BinOp,
+        builtin::{Builtin, CONDACT, RESTART},
         expression::Expr,
+        integer::Overflow,
         literal::Value,
         node::Node,
         to_range::{Merge, ToRange},
         unary_op::UnaryOp,
     },
-    checker::{function_type::FunctionType, infer_types::InferLen},
+    checker::{
+        call_graph::CallGraph,
+        definitions::check_definitions,
+        error_code::{ErrorCode, related},
+        function_type::FunctionType,
+        infer_types::InferLen,
+        quick_fix::{QuickFix, numeric_conversions},
+    },
     parser::{
         span::{Ident, PositionEnd, Span},
         var_type::{InnerVarType, VarType},
@@ -18,15 +28,56 @@ use crate::{
 use lsp_types::{
     Diagnostic, DiagnosticSeverity, InlayHint, InlayHintKind, InlayHintLabel, Position,
 };
+use std::collections::HashSet;
 
 struct CheckerInfo<'a> {
     types: &'a mut AstTypes,
     search_stack: Vec<Span>,
+    /// Variables (node name, variable name) whose type could not be computed.
+    /// Their diagnostic has already been emitted, it must not be emitted again
+    /// on every use of the variable.
+    failed_vars: HashSet<(Ident, Ident)>,
     // current_node: Ident,
+    overflow: Overflow,
     diagnostics: Vec<Diagnostic>,
     hints: Vec<InlayHint>,
 }
 
+/// Value of an integer literal, possibly negated.
+fn int_literal(expr: &Expr) -> Option<i64> {
+    match expr {
+        Expr::Lit(Value::Int(i)) => Some(*i),
+        Expr::UnaryOp {
+            op: UnaryOp::Inv,
+            rhs,
+            ..
+        } => int_literal(rhs)?.checked_neg(),
+        _ => None,
+    }
+}
+
+/// Value of an expression made only of literals, as computed by the interpreter.
+fn literal_value(expr: &Expr, overflow: Overflow) -> Option<Value> {
+    match expr {
+        Expr::Lit(value) => Some(value.clone()),
+        Expr::UnaryOp { op, rhs, .. } if op != &UnaryOp::Pre => {
+            op.apply(&literal_value(rhs, overflow)?, None, overflow)
+        }
+        Expr::BinOp {
+            lhs,
+            op: op @ (BinOp::Add | BinOp::Sub | BinOp::Mult | BinOp::Div),
+            rhs,
+            ..
+        } => op.apply(
+            &literal_value(lhs, overflow)?,
+            &literal_value(rhs, overflow)?,
+            None,
+            overflow,
+        ),
+        _ => None,
+    }
+}
+
 pub(crate) fn numeral_string(i: usize) -> String {
     if i == 0 {
         "1st".to_string()
@@ -35,11 +86,13 @@ pub(crate) fn numeral_string(i: usize) -> String {
     }
 }
 impl<'a> CheckerInfo<'a> {
-    fn new(types: &'a mut AstTypes) -> CheckerInfo<'a> {
+    fn new(types: &'a mut AstTypes, overflow: Overflow) -> CheckerInfo<'a> {
         Self {
             types: types,
             search_stack: vec![],
+            failed_vars: HashSet::new(),
             // current_node: Span::default(),
+            overflow,
             diagnostics: vec![],
             hints: vec![],
         }
@@ -76,55 +129,183 @@ impl<'a> CheckerInfo<'a> {
         })
     }
 
+    /// Diagnostic for a binary operator whose operands cannot be merged.
+    fn push_diagnostic_mismatched_operands(
+        &mut self,
+        span_op: &Span,
+        (lhs, lt): (&Expr, &VarType),
+        (rhs, rt): (&Expr, &VarType),
+    ) {
+        self.push_diagnostic(Diagnostic {
+            message: format!(
+                "Got type '{}' on the left and '{}' on the right but expected to have the same type.",
+                lt, rt
+            ),
+            severity: Some(DiagnosticSeverity::ERROR),
+            range: span_op.to_range(),
+            code: Some(ErrorCode::MismatchedOperands.into()),
+            related_information: Some(vec![
+                related(lhs.to_range(), format!("This is of type '{lt}'.")),
+                related(rhs.to_range(), format!("This is of type '{rt}'.")),
+            ]),
+            data: QuickFix::to_data(numeric_conversions((lhs, lt), (rhs, rt))),
+            ..Default::default()
+        });
+    }
+
+    /// Diagnostic for an operator applied on operands of the wrong type.
+    fn push_diagnostic_wrong_operands(
+        &mut self,
+        code: ErrorCode,
+        span_op: &Span,
+        message: String,
+        operands: &[(&Expr, &VarType)],
+    ) {
+        self.push_diagnostic(Diagnostic {
+            message,
+            severity: Some(DiagnosticSeverity::ERROR),
+            range: span_op.to_range(),
+            code: Some(code.into()),
+            related_information: Some(
+                operands
+                    .iter()
+                    .map(|(e, t)| related(e.to_range(), format!("This is of type '{t}'.")))
+                    .collect(),
+            ),
+            ..Default::default()
+        });
+    }
+
+    /// Type of an integer literal `expr` of type `t` used where a sized integer
+    /// of type `expected` is expected, other expressions keep their type.
+    fn coerce_int_literal(
+        &mut self,
+        expr: &Expr,
+        t: VarType,
+        expected: &VarType,
+    ) -> Option<VarType> {
+        let (InnerVarType::Int, InnerVarType::SizedInt(kind)) = (&t.inner, &expected.inner) else {
+            return Some(t);
+        };
+        let Some(value) = int_literal(expr) else {
+            return Some(t);
+        };
+        if kind.contains(value) {
+            return Some(VarType {
+                initialized: t.initialized,
+                inner: InnerVarType::SizedInt(*kind),
+            });
+        }
+        self.push_diagnostic(Diagnostic {
+            message: format!(
+                "Literal '{value}' does not fit in type '{kind}', whose values are between {} and {}.",
+                kind.min(),
+                kind.max()
+            ),
+            severity: Some(DiagnosticSeverity::ERROR),
+            range: expr.to_range(),
+            code: Some(ErrorCode::LiteralOutOfRange.into()),
+            ..Default::default()
+        });
+        None
+    }
+
+    /// Integer arithmetic on constant operands that has no value at runtime :
+    /// division by zero and overflow with `#![overflow(trapping)]`.
+    fn check_constant_arithmetic(&mut self, expr: &Expr, span_op: &Span) -> Option<()> {
+        let (operands, zero_divisor) = match expr {
+            Expr::BinOp { lhs, op, rhs, .. } => (
+                vec![&**lhs, &**rhs],
+                op == &BinOp::Div
+                    && matches!(
+                        literal_value(rhs, self.overflow),
+                        Some(Value::Int(0) | Value::SizedInt(_, 0))
+                    ),
+            ),
+            Expr::UnaryOp { rhs, .. } => (vec![&**rhs], false),
+            _ => return Some(()),
+        };
+        if zero_divisor {
+            self.push_diagnostic(Diagnostic {
+                message: "Integer division by zero has no value.".to_string(),
+                severity: Some(DiagnosticSeverity::ERROR),
+                range: span_op.to_range(),
+                code: Some(ErrorCode::DivisionByZero.into()),
+                ..Default::default()
+            });
+            return None;
+        }
+        let operands = operands
+            .into_iter()
+            .map(|e| literal_value(e, self.overflow))
+            .collect::<Option<Vec<_>>>();
+        if operands.is_some() && literal_value(expr, self.overflow).is_none() {
+            self.push_diagnostic(Diagnostic {
+                message: format!(
+                    "'{expr}' overflows, overflows are errors with `#![overflow({})]`.",
+                    self.overflow
+                ),
+                severity: Some(DiagnosticSeverity::ERROR),
+                range: span_op.to_range(),
+                code: Some(ErrorCode::ArithmeticOverflow.into()),
+                ..Default::default()
+            });
+            return None;
+        }
+        Some(())
+    }
+
     fn get_type_expression(&mut self, node: &Node, expr: &Expr) -> Option<VarType> {
         match expr {
             Expr::BinOp {
                 lhs,
-                op: BinOp::Add | BinOp::Sub | BinOp::Div | BinOp::Mult,
+                op: op @ (BinOp::Add | BinOp::Sub | BinOp::Div | BinOp::Mult),
                 span_op,
                 rhs,
             } => {
                 let lt = self.get_type_expression(node, lhs)?;
                 let rt = self.get_type_expression(node, rhs)?;
-                let message = format!(
-                    "Got type '{}' on the left and '{}' on the right but expected to have the same type.",
-                    lt, rt
-                );
-                let message_type_expected_is_int_or_float = format!(
-                    "Got type '{}' but either type Int or type Float is expected.",
-                    lt
-                );
-                match lt.merge(rt) {
-                    Some(VarType {
-                        initialized: true,
-                        inner: InnerVarType::Float,
-                    }) => Some(VarType {
-                        initialized: true,
-                        inner: InnerVarType::Float,
-                    }),
-                    Some(VarType {
-                        initialized: true,
-                        inner: InnerVarType::Int,
-                    }) => Some(VarType {
-                        initialized: true,
-                        inner: InnerVarType::Int,
-                    }),
-                    Some(_) => {
-                        self.push_diagnostic(Diagnostic {
-                            message: message_type_expected_is_int_or_float,
-                            severity: Some(DiagnosticSeverity::ERROR),
-                            range: span_op.to_range(),
-                            ..Default::default()
-                        });
+                let lt = self.coerce_int_literal(lhs, lt, &rt)?;
+                let rt = self.coerce_int_literal(rhs, rt, &lt)?;
+                // `+` also concatenates strings
+                let valid = |t: &InnerVarType| {
+                    t.is_numeric() || (op == &BinOp::Add && t == &InnerVarType::String)
+                };
+                match lt.clone().merge(rt.clone()) {
+                    Some(
+                        t @ VarType {
+                            initialized: true, ..
+                        },
+                    ) if valid(&t.inner) => {
+                        self.check_constant_arithmetic(expr, span_op)?;
+                        Some(t)
+                    }
+                    Some(t) if !t.initialized && valid(&t.inner) => {
+                        self.push_diagnostic_wrong_operands(
+                            ErrorCode::NotInitialized,
+                            span_op,
+                            format!(
+                                "Operands of '{}' are not initialized at first instant, use '->' to give them an initial value.",
+                                op
+                            ),
+                            &[(lhs, &lt), (rhs, &rt)],
+                        );
+                        None
+                    }
+                    Some(t) => {
+                        self.push_diagnostic_wrong_operands(
+                            ErrorCode::ExpectedNumeric,
+                            span_op,
+                            format!(
+                                "Got type '{}' but an integer type or type Float is expected.",
+                                t
+                            ),
+                            &[(lhs, &lt), (rhs, &rt)],
+                        );
                         None
                     }
                     None => {
-                        self.push_diagnostic(Diagnostic {
-                            message,
-                            severity: Some(DiagnosticSeverity::ERROR),
-                            range: span_op.to_range(),
-                            ..Default::default()
-                        });
+                        self.push_diagnostic_mismatched_operands(span_op, (lhs, &lt), (rhs, &rt));
                         None
                     }
                 }
@@ -137,11 +318,9 @@ impl<'a> CheckerInfo<'a> {
             } => {
                 let lt = self.get_type_expression(node, lhs)?;
                 let rt = self.get_type_expression(node, rhs)?;
-                let message = format!(
-                    "Got type '{}' on the left and '{}' on the right but expected to have the same type.",
-                    lt, rt
-                );
-                match lt.merge(rt) {
+                let lt = self.coerce_int_literal(lhs, lt, &rt)?;
+                let rt = self.coerce_int_literal(rhs, rt, &lt)?;
+                match lt.clone().merge(rt.clone()) {
                     Some(t) => Some(VarType {
                         initialized: true,
                         inner: if op == &BinOp::Eq || op == &BinOp::Neq {
@@ -151,31 +330,20 @@ impl<'a> CheckerInfo<'a> {
                         },
                     }),
                     None => {
-                        self.push_diagnostic(Diagnostic {
-                            message,
-                            severity: Some(DiagnosticSeverity::ERROR),
-                            range: span_op.to_range(),
-                            ..Default::default()
-                        });
+                        self.push_diagnostic_mismatched_operands(span_op, (lhs, &lt), (rhs, &rt));
                         None
                     }
                 }
             }
             Expr::BinOp {
                 lhs,
-                op: BinOp::Or | BinOp::And,
+                op: BinOp::Or | BinOp::And | BinOp::Since | BinOp::Triggered,
                 span_op,
                 rhs,
             } => {
                 let lt = self.get_type_expression(node, lhs)?;
                 let rt = self.get_type_expression(node, rhs)?;
-                let message = format!(
-                    "Got type '{}' on the left and '{}' on the right but expected to have the same type.",
-                    lt, rt
-                );
-                let message_type_expected_is_bool =
-                    format!("Got type '{}' but type Bool is expected.", lt);
-                match lt.merge(rt) {
+                match lt.clone().merge(rt.clone()) {
                     Some(VarType {
                         initialized: true,
                         inner: InnerVarType::Bool,
@@ -183,55 +351,150 @@ impl<'a> CheckerInfo<'a> {
                         initialized: true,
                         inner: InnerVarType::Bool,
                     }),
-                    Some(_) => {
-                        self.push_diagnostic(Diagnostic {
-                            message: message_type_expected_is_bool,
-                            severity: Some(DiagnosticSeverity::ERROR),
-                            range: span_op.to_range(),
-                            ..Default::default()
-                        });
+                    Some(t) => {
+                        self.push_diagnostic_wrong_operands(
+                            ErrorCode::ExpectedBool,
+                            span_op,
+                            format!("Got type '{}' but type Bool is expected.", t),
+                            &[(lhs, &lt), (rhs, &rt)],
+                        );
                         None
                     }
                     None => {
-                        self.push_diagnostic(Diagnostic {
-                            message,
-                            severity: Some(DiagnosticSeverity::ERROR),
-                            range: span_op.to_range(),
-                            ..Default::default()
-                        });
+                        self.push_diagnostic_mismatched_operands(span_op, (lhs, &lt), (rhs, &rt));
                         None
                     }
                 }
             }
             Expr::BinOp {
                 lhs,
-                op: BinOp::Arrow,
+                op: op @ (BinOp::Land | BinOp::Lor | BinOp::Lxor),
                 span_op,
                 rhs,
             } => {
                 let lt = self.get_type_expression(node, lhs)?;
                 let rt = self.get_type_expression(node, rhs)?;
-                if lt.is_not_initialized() {
+                let lt = self.coerce_int_literal(lhs, lt, &rt)?;
+                let rt = self.coerce_int_literal(rhs, rt, &lt)?;
+                match lt.clone().merge(rt.clone()) {
+                    Some(t) if t.inner.all_scalars(InnerVarType::is_integer) => {
+                        if t.initialized {
+                            return Some(t);
+                        }
+                        self.push_diagnostic_wrong_operands(
+                            ErrorCode::NotInitialized,
+                            span_op,
+                            format!(
+                                "Operands of '{op}' are not initialized at first instant, use '->' to give them an initial value."
+                            ),
+                            &[(lhs, &lt), (rhs, &rt)],
+                        );
+                        None
+                    }
+                    Some(t) => {
+                        self.push_diagnostic_wrong_operands(
+                            ErrorCode::ExpectedInt,
+                            span_op,
+                            format!("Got type '{t}' but an integer type is expected."),
+                            &[(lhs, &lt), (rhs, &rt)],
+                        );
+                        None
+                    }
+                    None => {
+                        self.push_diagnostic_mismatched_operands(span_op, (lhs, &lt), (rhs, &rt));
+                        None
+                    }
+                }
+            }
+            Expr::BinOp {
+                lhs,
+                op: op @ (BinOp::Shl | BinOp::Shr | BinOp::Bit),
+                span_op,
+                rhs,
+            } => {
+                let lt = self.get_type_expression(node, lhs)?;
+                let rt = self.get_type_expression(node, rhs)?;
+                let Some(kind) = lt.inner.int_kind() else {
+                    self.push_diagnostic_wrong_operands(
+                        ErrorCode::ExpectedInt,
+                        span_op,
+                        format!("Expected an integer type on the left of '{op}' but got '{lt}'."),
+                        &[(lhs, &lt)],
+                    );
+                    return None;
+                };
+                if !rt.inner.is_integer() {
+                    self.push_diagnostic_wrong_operands(
+                        ErrorCode::ExpectedInt,
+                        span_op,
+                        format!("Expected an integer type on the right of '{op}' but got '{rt}'."),
+                        &[(rhs, &rt)],
+                    );
+                    return None;
+                }
+                if !lt.initialized || !rt.initialized {
+                    self.push_diagnostic_wrong_operands(
+                        ErrorCode::NotInitialized,
+                        span_op,
+                        format!(
+                            "Operands of '{op}' are not initialized at first instant, use '->' to give them an initial value."
+                        ),
+                        &[(lhs, &lt), (rhs, &rt)],
+                    );
+                    return None;
+                }
+                if let Some(Value::Int(i) | Value::SizedInt(_, i)) =
+                    literal_value(rhs, self.overflow)
+                    && !(0..kind.bits()).contains(&i)
+                {
                     self.push_diagnostic(Diagnostic {
                         message: format!(
-                            "Got type '{}' which is not initialized at first instant.",
-                            lt
+                            "'{i}' is outside of the {} bits of type '{lt}', expected a value between 0 and {}.",
+                            kind.bits(),
+                            kind.bits() - 1
                         ),
                         severity: Some(DiagnosticSeverity::ERROR),
-                        range: span_op.to_range(),
+                        range: rhs.to_range(),
+                        code: Some(ErrorCode::ShiftOutOfRange.into()),
                         ..Default::default()
                     });
                     return None;
                 }
+                if op == &BinOp::Bit {
+                    Some(VarType {
+                        initialized: true,
+                        inner: InnerVarType::Bool,
+                    })
+                } else {
+                    Some(lt)
+                }
+            }
+            Expr::BinOp {
+                lhs,
+                op: BinOp::Arrow,
+                span_op,
+                rhs,
+            } => {
+                let lt = self.get_type_expression(node, lhs)?;
+                let rt = self.get_type_expression(node, rhs)?;
+                let lt = self.coerce_int_literal(lhs, lt, &rt)?;
+                let rt = self.coerce_int_literal(rhs, rt, &lt)?;
+                if lt.is_not_initialized() {
+                    self.push_diagnostic_wrong_operands(
+                        ErrorCode::NotInitialized,
+                        span_op,
+                        format!(
+                            "Got type '{}' which is not initialized at first instant.",
+                            lt
+                        ),
+                        &[(lhs, &lt)],
+                    );
+                    return None;
+                }
                 if lt.equal_without_pre(&rt) {
                     Some(rt.remove_one_pre())
                 } else {
-                    self.push_diagnostic(Diagnostic {
-                                message: format!("Got type '{}' on the left and '{}' on the right but expected to have the same type.", lt, rt),
-                                severity: Some(DiagnosticSeverity::ERROR),
-                                range: span_op.to_range(),
-                                ..Default::default()
-                            });
+                    self.push_diagnostic_mismatched_operands(span_op, (lhs, &lt), (rhs, &rt));
                     None
                 }
             }
@@ -245,15 +508,15 @@ impl<'a> CheckerInfo<'a> {
                 let rt = self.get_type_expression(node, rhs)?;
 
                 if rt.inner != InnerVarType::Int {
-                    self.push_diagnostic(Diagnostic {
-                        message: format!(
-                            "Expected type `int` on the left of '{}' but got '{}'.",
+                    self.push_diagnostic_wrong_operands(
+                        ErrorCode::ExpectedInt,
+                        span_op,
+                        format!(
+                            "Expected type `int` on the right of '{}' but got '{}'.",
                             op, rt
                         ),
-                        severity: Some(DiagnosticSeverity::ERROR),
-                        range: span_op.to_range(),
-                        ..Default::default()
-                    });
+                        &[(rhs, &rt)],
+                    );
                     return None;
                 }
                 let len = match rhs.get_value() {
@@ -271,24 +534,87 @@ impl<'a> CheckerInfo<'a> {
                 rhs,
             } => {
                 let rt = self.get_type_expression(node, rhs)?;
-                match rt.inner {
-                    InnerVarType::Unit | InnerVarType::Char | InnerVarType::String => {
-                        self.push_diagnostic(Diagnostic {
-                            message: format!(
-                                "`{}` Operation not defined for `{}` type.",
-                                op, rt.inner
-                            ),
-                            severity: Some(DiagnosticSeverity::ERROR),
-                            range: span_op.to_range(),
-                            ..Default::default()
-                        });
-                        None
-                    }
-                    InnerVarType::Int
-                    | InnerVarType::Float
-                    | InnerVarType::Bool
-                    | InnerVarType::Tuple(_)
-                    | InnerVarType::Array { t: _, len: _ } => Some(rt),
+                if rt.inner.all_scalars(InnerVarType::is_numeric) {
+                    self.check_constant_arithmetic(expr, span_op)?;
+                    Some(rt)
+                } else {
+                    self.push_diagnostic_wrong_operands(
+                        ErrorCode::InvalidUnaryOperand,
+                        span_op,
+                        format!("`{}` Operation not defined for `{}` type.", op, rt.inner),
+                        &[(rhs, &rt)],
+                    );
+                    None
+                }
+            }
+            Expr::UnaryOp {
+                op:
+                    op @ (UnaryOp::ToFloat
+                    | UnaryOp::ToInt
+                    | UnaryOp::Floor
+                    | UnaryOp::Round
+                    | UnaryOp::Abs
+                    | UnaryOp::ToSized(_)),
+                span_op,
+                rhs,
+            } => {
+                let rt = self.get_type_expression(node, rhs)?;
+                if rt.inner.all_scalars(InnerVarType::is_numeric) {
+                    self.check_constant_arithmetic(expr, span_op)?;
+                    Some(VarType {
+                        inner: rt.inner.map_scalars(|t| op.numeric_result(t)),
+                        initialized: rt.initialized,
+                    })
+                } else {
+                    self.push_diagnostic_wrong_operands(
+                        ErrorCode::InvalidUnaryOperand,
+                        span_op,
+                        format!(
+                            "`{op}` expects an integer type or `float` but got `{}`.",
+                            rt.inner
+                        ),
+                        &[(rhs, &rt)],
+                    );
+                    None
+                }
+            }
+            Expr::UnaryOp {
+                op: op @ UnaryOp::Len,
+                span_op,
+                rhs,
+            } => {
+                let rt = self.get_type_expression(node, rhs)?;
+                if rt.inner.all_scalars(|t| t == &InnerVarType::String) {
+                    Some(VarType {
+                        inner: rt.inner.map_scalars(|t| op.numeric_result(t)),
+                        initialized: rt.initialized,
+                    })
+                } else {
+                    self.push_diagnostic_wrong_operands(
+                        ErrorCode::InvalidUnaryOperand,
+                        span_op,
+                        format!("`{op}` expects type `string` but got `{}`.", rt.inner),
+                        &[(rhs, &rt)],
+                    );
+                    None
+                }
+            }
+            Expr::UnaryOp {
+                op: op @ UnaryOp::Lnot,
+                span_op,
+                rhs,
+            } => {
+                let rt = self.get_type_expression(node, rhs)?;
+                if rt.inner.all_scalars(InnerVarType::is_integer) {
+                    Some(rt)
+                } else {
+                    self.push_diagnostic_wrong_operands(
+                        ErrorCode::InvalidUnaryOperand,
+                        span_op,
+                        format!("`{op}` expects an integer type but got `{}`.", rt.inner),
+                        &[(rhs, &rt)],
+                    );
+                    None
                 }
             }
             Expr::UnaryOp {
@@ -301,22 +627,32 @@ impl<'a> CheckerInfo<'a> {
                     t.uninitialized();
                     Some(t)
                 } else {
-                    self.push_diagnostic(Diagnostic {
-                        message: "Using pre operator on a not initialized value. This cannot be recovered with any other operator.".to_string(),
-                        severity: Some(DiagnosticSeverity::ERROR),
-                        range: span_op.to_range(),
-                        ..Default::default()
-                    });
+                    self.push_diagnostic_wrong_operands(
+                        ErrorCode::DoublePre,
+                        span_op,
+                        "Using pre operator on a not initialized value. This cannot be recovered with any other operator.".to_string(),
+                        &[(rhs, &t)],
+                    );
                     None
                 }
             }
             Expr::UnaryOp {
-                op: UnaryOp::Not,
-                span_op: _,
+                op: op @ (UnaryOp::Not | UnaryOp::Once | UnaryOp::Historically | UnaryOp::Yesterday),
+                span_op,
                 rhs,
             } => {
                 let t = self.get_type_expression(node, rhs)?;
-                Some(t)
+                if t.inner.all_scalars(|t| t == &InnerVarType::Bool) {
+                    Some(t)
+                } else {
+                    self.push_diagnostic_wrong_operands(
+                        ErrorCode::ExpectedBool,
+                        span_op,
+                        format!("`{}` Operation expects type `bool` but got `{}`.", op, t),
+                        &[(rhs, &t)],
+                    );
+                    None
+                }
             }
             Expr::Variable(s) => self.get_type_var(node, s, false),
             Expr::Lit(val) => Some(val.get_type()),
@@ -331,6 +667,7 @@ impl<'a> CheckerInfo<'a> {
                         ),
                         severity: Some(DiagnosticSeverity::ERROR),
                         range: index.to_range(),
+                        code: Some(ErrorCode::ExpectedInt.into()),
                         ..Default::default()
                     });
                     return None;
@@ -349,6 +686,11 @@ impl<'a> CheckerInfo<'a> {
                             ),
                             severity: Some(DiagnosticSeverity::ERROR),
                             range: expr.to_range().merge(index.to_range()),
+                            code: Some(ErrorCode::IndexOutOfBounds.into()),
+                            related_information: Some(vec![related(
+                                expr.to_range(),
+                                format!("This is of type '{texpr}'."),
+                            )]),
                             ..Default::default()
                         });
                         None
@@ -361,25 +703,43 @@ impl<'a> CheckerInfo<'a> {
                         ),
                         severity: Some(DiagnosticSeverity::ERROR),
                         range: expr.to_range().merge(index.to_range()),
+                        code: Some(ErrorCode::UnknownIndex.into()),
+                        related_information: Some(vec![related(
+                            index.to_range(),
+                            "This index is not known at compile time.".to_string(),
+                        )]),
                         ..Default::default()
                     });
                     None
                 }
             }
             Expr::Array(arr) => {
-                let mut t0 = None;
+                let mut t0: Option<(&Expr, VarType)> = None;
                 let mut initialized = true;
                 for e in arr.iter() {
+                    let t1 = self.get_type_expression(node, e)?;
+                    initialized = initialized && t1.initialized;
                     match &t0 {
-                        None => {
-                            let t1 = self.get_type_expression(node, e)?;
-                            initialized = initialized && t1.initialized;
-                            t0 = Some(t1);
-                        }
-                        Some(t0) => {
-                            let t1 = self.get_type_expression(node, e)?;
-                            initialized = initialized && t1.initialized;
+                        None => t0 = Some((e, t1)),
+                        Some((e0, t0)) => {
                             if !t1.equal_without_pre(t0) {
+                                self.push_diagnostic(Diagnostic {
+                                    message: format!(
+                                        "Elements of an array must have the same type, found '{}' and '{}'.",
+                                        t0, t1
+                                    ),
+                                    severity: Some(DiagnosticSeverity::ERROR),
+                                    range: e.to_range(),
+                                    code: Some(ErrorCode::HeterogeneousArray.into()),
+                                    related_information: Some(vec![
+                                        related(
+                                            e0.to_range(),
+                                            format!("First element is of type '{t0}'."),
+                                        ),
+                                        related(e.to_range(), format!("This is of type '{t1}'.")),
+                                    ]),
+                                    ..Default::default()
+                                });
                                 return None;
                             }
                         }
@@ -388,7 +748,11 @@ impl<'a> CheckerInfo<'a> {
                 Some(VarType {
                     initialized,
                     inner: InnerVarType::Array {
-                        t: Box::new(t0?.inner),
+                        t: Box::new(match t0 {
+                            Some((_, t0)) => t0.inner,
+                            // Same type as the empty array value
+                            None => InnerVarType::Unit,
+                        }),
                         len: InferLen::Known(arr.len()),
                     },
                 })
@@ -421,14 +785,45 @@ impl<'a> CheckerInfo<'a> {
                 let tcond = self.get_type_expression(node, cond)?;
                 let tyes = self.get_type_expression(node, yes)?;
                 let tno = self.get_type_expression(node, no)?;
+                let tyes = self.coerce_int_literal(yes, tyes, &tno)?;
+                let tno = self.coerce_int_literal(no, tno, &tyes)?;
 
-                if tcond == InnerVarType::Bool && tyes.equal_without_pre(&tno) {
+                if tcond != InnerVarType::Bool {
+                    self.push_diagnostic(Diagnostic {
+                        message: format!(
+                            "Condition of 'if' must be of type 'bool' but got '{}'.",
+                            tcond
+                        ),
+                        severity: Some(DiagnosticSeverity::ERROR),
+                        range: cond.to_range(),
+                        code: Some(ErrorCode::IfConditionNotBool.into()),
+                        ..Default::default()
+                    });
+                    None
+                } else if !tyes.equal_without_pre(&tno) {
+                    self.push_diagnostic(Diagnostic {
+                        message: format!(
+                            "Branches of 'if' must have the same type, got '{}' and '{}'.",
+                            tyes, tno
+                        ),
+                        severity: Some(DiagnosticSeverity::ERROR),
+                        range: expr.to_range(),
+                        code: Some(ErrorCode::IfBranchesMismatch.into()),
+                        related_information: Some(vec![
+                            related(
+                                yes.to_range(),
+                                format!("'then' branch is of type '{tyes}'."),
+                            ),
+                            related(no.to_range(), format!("'else' branch is of type '{tno}'.")),
+                        ]),
+                        ..Default::default()
+                    });
+                    None
+                } else {
                     Some(VarType {
                         initialized: tcond.initialized && tyes.initialized && tno.initialized,
                         inner: tyes.inner,
                     })
-                } else {
-                    None
                 }
             }
         }
@@ -453,15 +848,6 @@ impl<'a> CheckerInfo<'a> {
     /// - f([2, 3, 4, x])
     ///   Here we consider the type of `f : [int] -> [int]`
     fn get_type_function(&mut self, node: &Node, name: &Span, args: &Vec<Expr>) -> Option<VarType> {
-        if name == &node.name {
-            self.push_diagnostic(Diagnostic {
-                message: "Recursive function call are not allowed".to_string(),
-                severity: Some(DiagnosticSeverity::ERROR),
-                range: name.to_range(),
-                ..Default::default()
-            });
-            return None;
-        }
         // A function type call can be lifted
         // A function of type 'int -> int'
         enum FunctionCallType {
@@ -473,34 +859,11 @@ impl<'a> CheckerInfo<'a> {
             Array,
         }
         let mut call_type = FunctionCallType::Unknown;
+        // Recursive calls are reported by [CallGraph::topological_order]
         match self.types.get_node_type(name) {
             Some(ft) => {
                 // Arguments types for a Array call
                 let mut args_array_length = None;
-                // Cannot call function defined after the current node
-                let index_current_node = &self.types.get_nodes_index(&node.name).unwrap();
-                let index_called_node = &self.types.get_nodes_index(name).unwrap();
-                if index_called_node > index_current_node {
-                    self.push_diagnostic(Diagnostic {
-                        message: format!(
-                            "The function `{}` is defined after the place it is being called.",
-                            name
-                        ),
-                        severity: Some(DiagnosticSeverity::ERROR),
-                        range: name.to_range(),
-                        ..Default::default()
-                    });
-                    return None;
-                } else if index_called_node == index_current_node {
-                    self.push_diagnostic(Diagnostic {
-                        message: format!("Cannot call recursively the same node.",),
-                        severity: Some(DiagnosticSeverity::ERROR),
-                        range: name.to_range(),
-                        ..Default::default()
-                    });
-                    return None;
-                }
-
                 // fix for self being immutably borrowed then mutable borrowed
                 let ft = ft.clone();
                 if ft.inputs.len() != args.len() {
@@ -513,6 +876,7 @@ impl<'a> CheckerInfo<'a> {
                         ),
                         severity: Some(DiagnosticSeverity::ERROR),
                         range: name.to_range(),
+                        code: Some(ErrorCode::ArgumentCount.into()),
                         ..Default::default()
                     });
                     return None;
@@ -527,88 +891,107 @@ impl<'a> CheckerInfo<'a> {
                 // guess call type.
                 for (i, (arg, (_, expected_type))) in args.iter().zip(ft.inputs.iter()).enumerate()
                 {
-                    match self.get_type_expression(node, arg) {
-                        Some(t) => match call_type {
-                            FunctionCallType::Unknown => {
-                                // Always reachable because we begin with Unknown type
-                                if &t == expected_type {
-                                    call_type = FunctionCallType::Simple;
-                                } else if t.equal_array_of(expected_type) {
-                                    call_type = FunctionCallType::Array;
-                                    // We now know the length of arrays for this `array` call type.
-                                    // This is not true, we might have to do one more call to propagate const
-                                    args_array_length = Some(t.get_length_array()?);
-                                } else {
-                                    self.push_diagnostic(Diagnostic {
-                                        message: format!(
-                                            "{} arguments of function {} of type '{}' but expected '{}' or ['{}']",
-                                            numeral_string(i),
-                                            name,
-                                            t,
-                                            expected_type,
-                                            expected_type
-                                        ),
-                                        severity: Some(DiagnosticSeverity::ERROR),
-                                        range: name.to_range(),
-                                        ..Default::default()
-                                    });
-                                    return None;
+                    // The diagnostic of an argument that does not type check
+                    // has already been emitted.
+                    let t = self.get_type_expression(node, arg)?;
+                    let t = self.coerce_int_literal(arg, t, expected_type)?;
+                    match call_type {
+                        FunctionCallType::Unknown => {
+                            // Always reachable because we begin with Unknown type
+                            if &t == expected_type {
+                                call_type = FunctionCallType::Simple;
+                            } else if t.equal_array_of(expected_type) {
+                                call_type = FunctionCallType::Array;
+                                // We now know the length of arrays for this `array` call type.
+                                // This is not true, we might have to do one more call to propagate const
+                                match t.get_length_array() {
+                                    Some(len) => args_array_length = Some(len),
+                                    None => {
+                                        self.push_diagnostic_unknown_length(name, i, arg, &t);
+                                        return None;
+                                    }
                                 }
+                            } else {
+                                self.push_diagnostic(Diagnostic {
+                                    message: format!(
+                                        "{} arguments of function {} of type '{}' but expected '{}' or ['{}']",
+                                        numeral_string(i),
+                                        name,
+                                        t,
+                                        expected_type,
+                                        expected_type
+                                    ),
+                                    severity: Some(DiagnosticSeverity::ERROR),
+                                    range: name.to_range(),
+                                    code: Some(ErrorCode::ArgumentType.into()),
+                                    related_information: Some(vec![related(
+                                        arg.to_range(),
+                                        format!("This is of type '{t}'."),
+                                    )]),
+                                    ..Default::default()
+                                });
+                                return None;
                             }
-                            FunctionCallType::Simple => {
-                                if &t != expected_type {
-                                    self.push_diagnostic_call(name, i, expected_type, t);
-                                    return None;
-                                }
+                        }
+                        FunctionCallType::Simple => {
+                            if &t != expected_type {
+                                self.push_diagnostic_call(name, i, arg, expected_type, t);
+                                return None;
                             }
-                            FunctionCallType::Array => {
-                                if !t.equal_array_of(expected_type) {
-                                    self.push_diagnostic(Diagnostic {
-                                        message: format!(
-                                            "{} arguments of function {} of type '{}' but expected ['{}']",
-                                            numeral_string(i),
-                                            name,
-                                            t,
-                                            expected_type
+                        }
+                        FunctionCallType::Array => {
+                            if !t.equal_array_of(expected_type) {
+                                self.push_diagnostic(Diagnostic {
+                                    message: format!(
+                                        "{} arguments of function {} of type '{}' but expected ['{}']",
+                                        numeral_string(i),
+                                        name,
+                                        t,
+                                        expected_type
+                                    ),
+                                    severity: Some(DiagnosticSeverity::ERROR),
+                                    range: name.to_range(),
+                                    code: Some(ErrorCode::ArgumentType.into()),
+                                    related_information: Some(vec![related(
+                                        arg.to_range(),
+                                        format!("This is of type '{t}'."),
+                                    )]),
+                                    ..Default::default()
+                                });
+                                return None;
+                            }
+                            // Set when the call type became `Array`
+                            let expected_length = args_array_length.unwrap();
+                            let Some(given_length) = t.get_length_array() else {
+                                self.push_diagnostic_unknown_length(name, i, arg, &t);
+                                return None;
+                            };
+                            if given_length != expected_length {
+                                self.push_diagnostic(Diagnostic {
+                                    message: format!(
+                                        "{} arguments of function {} is an array of length {} but expected length {}.",
+                                        numeral_string(i),
+                                        name,
+                                        given_length,
+                                        expected_length
+                                    ),
+                                    severity: Some(DiagnosticSeverity::ERROR),
+                                    range: name.to_range(),
+                                    code: Some(ErrorCode::ArgumentArrayLength.into()),
+                                    related_information: Some(vec![
+                                        related(
+                                            args[0].to_range(),
+                                            format!("This is of length {expected_length}."),
                                         ),
-                                        severity: Some(DiagnosticSeverity::ERROR),
-                                        range: name.to_range(),
-                                        ..Default::default()
-                                    });
-                                    return None;
-                                } else {
-                                    let expected_length = args_array_length?;
-                                    let given_length = t.get_length_array()?;
-                                    if given_length != expected_length {
-                                        self.push_diagnostic(Diagnostic {
-                                            message: format!(
-                                                "{} arguments of function {} is an array of length {} but expected length {}.",
-                                                numeral_string(i),
-                                                name,
-                                                given_length,
-                                                expected_length
-                                             ),
-                                            severity: Some(DiagnosticSeverity::ERROR),
-                                            range: name.to_range(),
-                                            ..Default::default()
-                                        });
-                                        return None;
-                                    }
-                                }
+                                        related(
+                                            arg.to_range(),
+                                            format!("This is of length {given_length}."),
+                                        ),
+                                    ]),
+                                    ..Default::default()
+                                });
+                                return None;
                             }
-                        },
-                        None => {
-                            self.push_diagnostic(Diagnostic {
-                                message: format!(
-                                    "{} arguments of function {} does not type check.",
-                                    numeral_string(i),
-                                    name,
-                                ),
-                                severity: Some(DiagnosticSeverity::ERROR),
-                                range: name.to_range(),
-                                ..Default::default()
-                            });
-                            return None;
                         }
                     }
                 }
@@ -632,11 +1015,22 @@ impl<'a> CheckerInfo<'a> {
                     )),
                 }
             }
+            None if name.fragment() == CONDACT => self.get_type_condact(node, name, args),
+            // Written by the lowering of automata, the outputs of the restarted call
+            None if name.fragment() == RESTART
+                && let [_, _, call] = &args[..] =>
+            {
+                self.get_type_expression(node, call)
+            }
+            None if let Some(builtin) = Builtin::from_name(&name.fragment()) => {
+                self.get_type_builtin(node, builtin, name, args)
+            }
             None => {
                 self.push_diagnostic(Diagnostic {
                     message: format!("Function '{}' never defined.", name,),
                     severity: Some(DiagnosticSeverity::ERROR),
                     range: name.to_range(),
+                    code: Some(ErrorCode::UnknownNode.into()),
                     ..Default::default()
                 });
                 None
@@ -644,7 +1038,185 @@ impl<'a> CheckerInfo<'a> {
         }
     }
 
-    fn push_diagnostic_call(&mut self, name: &Span, i: usize, expected_type: &VarType, t: VarType) {
+    /// Type of `condact(c, f(args), defaults...)`, the outputs of `f`.
+    /// There is one default per output of `f`, they are the outputs until
+    /// `c` holds for the first time.
+    fn get_type_condact(&mut self, node: &Node, name: &Span, args: &[Expr]) -> Option<VarType> {
+        let not_a_call = |arg: &Expr| Diagnostic {
+            message: format!("Second argument of '{CONDACT}' must be a call to a node."),
+            severity: Some(DiagnosticSeverity::ERROR),
+            range: name.to_range(),
+            code: Some(ErrorCode::ArgumentType.into()),
+            related_information: Some(vec![related(
+                arg.to_range(),
+                "This is not a node call.".to_string(),
+            )]),
+            ..Default::default()
+        };
+        let [cond, call, defaults @ ..] = args else {
+            self.push_diagnostic(Diagnostic {
+                message: format!(
+                    "Expected at least 2 arguments for function '{CONDACT}' but got {} arguments.",
+                    args.len()
+                ),
+                severity: Some(DiagnosticSeverity::ERROR),
+                range: name.to_range(),
+                code: Some(ErrorCode::ArgumentCount.into()),
+                ..Default::default()
+            });
+            return None;
+        };
+        let Expr::FCall { name: callee, .. } = call else {
+            self.push_diagnostic(not_a_call(call));
+            return None;
+        };
+        let Some(ft) = self.types.get_node_type(callee).cloned() else {
+            self.push_diagnostic(not_a_call(call));
+            return None;
+        };
+        if defaults.len() != ft.outputs.len() {
+            self.push_diagnostic(Diagnostic {
+                message: format!(
+                    "Expected {} default values for the outputs of '{}' but got {}.",
+                    ft.outputs.len(),
+                    callee,
+                    defaults.len()
+                ),
+                severity: Some(DiagnosticSeverity::ERROR),
+                range: name.to_range(),
+                code: Some(ErrorCode::ArgumentCount.into()),
+                ..Default::default()
+            });
+            return None;
+        }
+        let t = self.get_type_expression(node, call)?;
+        // A lifted call is activated by an array of conditions
+        let simple = VarType::tuple_from_vec(ft.outputs.values().cloned().collect());
+        let first_output = match &t.inner {
+            InnerVarType::Tuple(v) => v.first().cloned(),
+            t => Some(t.clone()),
+        };
+        let expected_cond = match first_output {
+            Some(InnerVarType::Array { len, .. }) if t.inner != simple.inner => VarType {
+                initialized: true,
+                inner: InnerVarType::Array {
+                    t: Box::new(InnerVarType::Bool),
+                    len,
+                },
+            },
+            _ => VarType {
+                initialized: true,
+                inner: InnerVarType::Bool,
+            },
+        };
+        let t_cond = self.get_type_expression(node, cond)?;
+        if t_cond != expected_cond {
+            self.push_diagnostic_call(name, 0, cond, &expected_cond, t_cond);
+            return None;
+        }
+        for (i, (default, (_, expected))) in defaults.iter().zip(ft.outputs.iter()).enumerate() {
+            let t_default = self.get_type_expression(node, default)?;
+            let t_default = self.coerce_int_literal(default, t_default, expected)?;
+            if !t_default.initialized || !t_default.equal_without_pre(expected) {
+                self.push_diagnostic_call(name, i + 2, default, expected, t_default);
+                return None;
+            }
+        }
+        Some(VarType {
+            initialized: true,
+            inner: t.inner,
+        })
+    }
+
+    /// Type of a call to a built-in function, its arguments all have the
+    /// same type : a scalar accepted by the function or an array of it.
+    fn get_type_builtin(
+        &mut self,
+        node: &Node,
+        builtin: Builtin,
+        name: &Span,
+        args: &[Expr],
+    ) -> Option<VarType> {
+        // A call without arguments has the unit argument
+        let args = match args {
+            [Expr::Lit(Value::Unit)] => &[],
+            args => args,
+        };
+        if args.len() != builtin.arity() {
+            self.push_diagnostic(Diagnostic {
+                message: format!(
+                    "Expected {} arguments for function '{}' but got {} arguments.",
+                    builtin.arity(),
+                    builtin.signature(),
+                    args.len()
+                ),
+                severity: Some(DiagnosticSeverity::ERROR),
+                range: name.to_range(),
+                code: Some(ErrorCode::ArgumentCount.into()),
+                ..Default::default()
+            });
+            return None;
+        }
+        let mut types = vec![];
+        for arg in args.iter() {
+            types.push(self.get_type_expression(node, arg)?);
+        }
+        // Integer literals take the type of the other arguments
+        let expected = types
+            .iter()
+            .zip(args.iter())
+            .find(|(_, arg)| int_literal(arg).is_none())
+            .map_or(types[0].clone(), |(t, _)| t.clone());
+        let mut initialized = true;
+        for (i, (arg, t)) in args.iter().zip(types).enumerate() {
+            let t = self.coerce_int_literal(arg, t, &expected)?;
+            let scalar = match &t.inner {
+                InnerVarType::Array { t, .. } => t,
+                t => t,
+            };
+            let error = if !builtin.accepts(scalar) && builtin.accepts(&InnerVarType::Int) {
+                "a numeric type or an array of it".to_string()
+            } else if !builtin.accepts(scalar) {
+                "'float' or an array of it".to_string()
+            } else if !t.equal_without_pre(&expected) {
+                format!("'{}' like the other arguments", expected.inner)
+            } else {
+                initialized = initialized && t.initialized;
+                continue;
+            };
+            self.push_diagnostic(Diagnostic {
+                message: format!(
+                    "{} arguments of function '{}' of type '{}' but expected {}.",
+                    numeral_string(i),
+                    builtin.signature(),
+                    t,
+                    error
+                ),
+                severity: Some(DiagnosticSeverity::ERROR),
+                range: name.to_range(),
+                code: Some(ErrorCode::ArgumentType.into()),
+                related_information: Some(vec![related(
+                    arg.to_range(),
+                    format!("This is of type '{t}'."),
+                )]),
+                ..Default::default()
+            });
+            return None;
+        }
+        Some(VarType {
+            initialized,
+            inner: expected.inner,
+        })
+    }
+
+    fn push_diagnostic_call(
+        &mut self,
+        name: &Span,
+        i: usize,
+        arg: &Expr,
+        expected_type: &VarType,
+        t: VarType,
+    ) {
         self.push_diagnostic(Diagnostic {
             message: format!(
                 "{} arguments of function {} of type '{}' but expected '{}'",
@@ -655,6 +1227,29 @@ impl<'a> CheckerInfo<'a> {
             ),
             severity: Some(DiagnosticSeverity::ERROR),
             range: name.to_range(),
+            code: Some(ErrorCode::ArgumentType.into()),
+            related_information: Some(vec![related(
+                arg.to_range(),
+                format!("This is of type '{t}'."),
+            )]),
+            ..Default::default()
+        });
+    }
+
+    fn push_diagnostic_unknown_length(&mut self, name: &Span, i: usize, arg: &Expr, t: &VarType) {
+        self.push_diagnostic(Diagnostic {
+            message: format!(
+                "{} arguments of function {} is an array whose length is not known at compile time.",
+                numeral_string(i),
+                name,
+            ),
+            severity: Some(DiagnosticSeverity::ERROR),
+            range: name.to_range(),
+            code: Some(ErrorCode::UnknownArrayLength.into()),
+            related_information: Some(vec![related(
+                arg.to_range(),
+                format!("This is of type '{t}'."),
+            )]),
             ..Default::default()
         });
     }
@@ -665,31 +1260,54 @@ impl<'a> CheckerInfo<'a> {
                 message: format!("Need more type information on {}", var),
                 severity: Some(DiagnosticSeverity::ERROR),
                 range: var.to_range(),
+                code: Some(ErrorCode::CyclicInference.into()),
                 ..Default::default()
             });
+            self.failed_vars.insert((node.name.clone(), var.clone()));
             return None;
         } else {
             self.push_new_search(var.clone());
         }
-        let var = self.last_search().unwrap();
+        let var = self.last_search().unwrap().clone();
         for (name, expr) in node.let_bindings.iter() {
-            if name == var {
-                let var_type = self.get_type_expression(node, expr);
+            if name == &var {
+                // Integer literals take the declared sized integer type
+                let declared = node
+                    .outputs
+                    .iter()
+                    .chain(node.vars.iter())
+                    .find(|(v, _)| v == name);
+                let var_type = match declared {
+                    Some((_, t)) => self
+                        .get_type_expression(node, expr)
+                        .and_then(|t2| self.coerce_int_literal(expr, t2, t)),
+                    None => self.get_type_expression(node, expr),
+                };
+                if var_type.is_none() {
+                    self.failed_vars.insert((node.name.clone(), var.clone()));
+                }
                 self.types
                     .insert_local_type(&node.name, name.clone(), var_type.clone());
                 self.pop_search();
                 return var_type;
             }
         }
+        self.pop_search();
         self.push_diagnostic(Diagnostic {
             message: format!("No equation found for '{}'", var),
             severity: Some(DiagnosticSeverity::ERROR),
             range: var.to_range(),
+            code: Some(ErrorCode::UnknownVariable.into()),
             ..Default::default()
         });
+        self.failed_vars.insert((node.name.clone(), var));
         None
     }
     fn get_type_var(&mut self, node: &Node, var: &Span, should_search: bool) -> Option<VarType> {
+        // Its diagnostic has already been emitted
+        if self.failed_vars.contains(&(node.name.clone(), var.clone())) {
+            return None;
+        }
         if should_search {
             return self.search_type_var(node, var);
         }
@@ -704,15 +1322,18 @@ impl<'a> CheckerInfo<'a> {
                     message: format!("No Equation found for '{}'", var),
                     severity: Some(DiagnosticSeverity::ERROR),
                     range: var.to_range(),
+                    code: Some(ErrorCode::UnknownVariable.into()),
                     ..Default::default()
                 });
+                self.failed_vars.insert((node.name.clone(), var.clone()));
                 None
             }
         }
     }
 
     /// Setup partial Local Type in the Checker
-    /// - Check that variables are not defined twice in left of equations
+    /// - Equations defining an already known variable are ignored,
+    ///   they are reported by [check_definitions]
     fn setup_local_types(&mut self, node: &Node) {
         // insert all inputs types
         for (name, t) in node.inputs.iter() {
@@ -721,30 +1342,30 @@ impl<'a> CheckerInfo<'a> {
         }
 
         for (name, _) in node.let_bindings.iter() {
-            if self.types.contains_key_local_type(&node.name, name) {
-                self.diagnostics.push(Diagnostic {
-                    message: format!("Equation for '{}' already defined.", name),
-                    severity: Some(DiagnosticSeverity::ERROR),
-                    range: name.to_range(),
-                    ..Default::default()
-                });
-            } else {
+            if !self.types.contains_key_local_type(&node.name, name) {
                 self.types.insert_local_type(&node.name, name.clone(), None);
             }
         }
-        for (name, t) in node.outputs.iter() {
+        for (name, t) in node.outputs.iter().chain(node.vars.iter()) {
             self.types
                 .insert_local_type(&node.name, name.clone(), Some(t.clone()));
         }
     }
 
-    fn check_cycle_from_expr(&mut self, node: &Node, seen: &mut Vec<String>, expr: &Expr) {
+    fn check_cycle_from_expr(
+        &mut self,
+        node: &Node,
+        seen: &mut Vec<String>,
+        reported: &mut Vec<Vec<String>>,
+        expr: &Expr,
+    ) {
         match expr {
-            // In those 2 cases we cut the chase because
-            // `fby` and `pre` cut temporal cycle.
+            // In those 3 cases we cut the chase because
+            // `fby`, `pre` and `yesterday` cut temporal cycle.
             Expr::BinOp { op: BinOp::Fby, .. }
             | Expr::UnaryOp {
-                op: UnaryOp::Pre, ..
+                op: UnaryOp::Pre | UnaryOp::Yesterday,
+                ..
             } => {}
             Expr::BinOp {
                 lhs,
@@ -752,24 +1373,24 @@ impl<'a> CheckerInfo<'a> {
                 span_op: _,
                 rhs,
             } => {
-                self.check_cycle_from_expr(node, seen, lhs);
-                self.check_cycle_from_expr(node, seen, rhs);
+                self.check_cycle_from_expr(node, seen, reported, lhs);
+                self.check_cycle_from_expr(node, seen, reported, rhs);
             }
             Expr::UnaryOp {
                 op: _,
                 span_op: _,
                 rhs,
             } => {
-                self.check_cycle_from_expr(node, seen, rhs);
+                self.check_cycle_from_expr(node, seen, reported, rhs);
             }
             Expr::If { cond, yes, no } => {
-                self.check_cycle_from_expr(node, seen, cond);
-                self.check_cycle_from_expr(node, seen, yes);
-                self.check_cycle_from_expr(node, seen, no);
+                self.check_cycle_from_expr(node, seen, reported, cond);
+                self.check_cycle_from_expr(node, seen, reported, yes);
+                self.check_cycle_from_expr(node, seen, reported, no);
             }
             Expr::Index { expr, index } => {
-                self.check_cycle_from_expr(node, seen, expr);
-                self.check_cycle_from_expr(node, seen, index);
+                self.check_cycle_from_expr(node, seen, reported, expr);
+                self.check_cycle_from_expr(node, seen, reported, index);
             }
             Expr::Array(exprs)
             | Expr::Tuple(exprs)
@@ -778,53 +1399,89 @@ impl<'a> CheckerInfo<'a> {
                 args: exprs,
             } => {
                 for expr in exprs {
-                    self.check_cycle_from_expr(node, seen, expr);
+                    self.check_cycle_from_expr(node, seen, reported, expr);
                 }
             }
             Expr::Variable(span) => {
                 let var = span.fragment();
-                if seen.contains(&var) {
+                if let Some(start) = seen.iter().position(|v| v == &var) {
+                    // The same cycle can be reached from several outputs
+                    let mut cycle = seen[start..].to_vec();
+                    cycle.sort();
+                    if reported.contains(&cycle) {
+                        return;
+                    }
+                    reported.push(cycle);
+
                     let mut s = String::new();
-                    for v in seen.iter() {
-                        s.push_str(&format!("{v} - "));
+                    for v in seen[start..].iter() {
+                        s.push_str(&format!("{v} -> "));
                     }
                     self.push_diagnostic(Diagnostic {
                         message: format!(
-                            "Cycle found here with variable {span} comming from {}.
-                            All cycle : {s}
-                            ",
+                            "Cycle found here with variable {span} comming from {}. All cycle : {s}{span}",
                             seen.first().unwrap()
                         ),
                         severity: Some(DiagnosticSeverity::ERROR),
                         range: span.to_range(),
+                        code: Some(ErrorCode::CyclicDefinition.into()),
                         ..Default::default()
                     });
                 } else {
                     seen.push(var);
-                    self.check_cycle_from(node, seen);
+                    self.check_cycle_from(node, seen, reported);
                     seen.pop();
                 }
             }
             Expr::Lit(_) => {}
         }
     }
-    fn check_cycle_from(&mut self, node: &Node, seen: &mut Vec<String>) {
+    fn check_cycle_from(
+        &mut self,
+        node: &Node,
+        seen: &mut Vec<String>,
+        reported: &mut Vec<Vec<String>>,
+    ) {
         for (name, expr) in node.let_bindings.iter() {
             if &name.fragment() == seen.last().unwrap() {
-                self.check_cycle_from_expr(node, seen, expr);
+                self.check_cycle_from_expr(node, seen, reported, expr);
+            }
+        }
+        // Variables of an automaton depend on their equations in every state
+        // and on the strong transitions selecting the active state
+        for automaton in node.automata.iter() {
+            if automaton
+                .defined()
+                .iter()
+                .all(|name| &name.fragment() != seen.last().unwrap())
+            {
+                continue;
+            }
+            for state in automaton.states.iter() {
+                for transition in state.unless.iter() {
+                    self.check_cycle_from_expr(node, seen, reported, &transition.cond);
+                }
+                for (name, expr) in state.let_bindings.iter() {
+                    if &name.fragment() == seen.last().unwrap() {
+                        self.check_cycle_from_expr(node, seen, reported, expr);
+                    }
+                }
             }
         }
     }
     fn check_cycle(&mut self, node: &Node) {
         let mut seen: Vec<String> = vec![];
+        let mut reported: Vec<Vec<String>> = vec![];
         for (out, _) in node.outputs.iter() {
             seen.push(out.fragment());
-            self.check_cycle_from(node, &mut seen);
+            self.check_cycle_from(node, &mut seen, &mut reported);
             seen.pop();
         }
     }
 
-    fn check_node(&mut self, node: &Node) {
+    /// Check the types of a node, returns `false` if the node
+    /// has a cycle, in which case types of its equations are not computed.
+    fn check_node(&mut self, node: &Node) -> bool {
         // self.set_current_node(&node.name);
         self.setup_local_types(node);
 
@@ -832,32 +1489,96 @@ impl<'a> CheckerInfo<'a> {
         self.check_cycle(node);
         // Stop Here if check_cycle has found a cycle
         if self.number_diagnostics() > number_diags {
-            return;
+            return false;
         }
 
-        for (out, t) in node.outputs.iter() {
-            match &self.get_type_var(node, out, true) {
-                Some(t2) => {
-                    if t != t2 {
-                        self.push_diagnostic(Diagnostic {
-                            message: format!(
-                                "'{}' is supposed to be of type '{}', found '{}'.",
-                                out, t, t2
-                            ),
-                            severity: Some(DiagnosticSeverity::ERROR),
-                            range: out.to_range(),
-                            ..Default::default()
-                        });
-                    }
+        for (out, t) in node.outputs.iter().chain(node.vars.iter()) {
+            let equation = node.let_bindings.iter().find(|(name, _)| name == out);
+            // Missing equations are reported by [check_definitions]
+            if equation.is_none() {
+                continue;
+            }
+            // When the type is not found, the diagnostic has already been emitted
+            if let Some(t2) = &self.get_type_var(node, out, true)
+                && t != t2
+            {
+                self.push_diagnostic(Diagnostic {
+                    message: format!(
+                        "'{}' is supposed to be of type '{}', found '{}'.",
+                        out, t, t2
+                    ),
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    range: out.to_range(),
+                    code: Some(ErrorCode::OutputTypeMismatch.into()),
+                    related_information: equation.map(|(_, expr)| {
+                        vec![related(expr.to_range(), format!("This is of type '{t2}'."))]
+                    }),
+                    ..Default::default()
+                });
+            }
+        }
+        for automaton in node.automata.iter() {
+            self.check_automaton(node, automaton);
+        }
+        for (span_assert, expr) in node.asserts.iter() {
+            self.check_assert(node, "assert", span_assert, expr);
+        }
+        if let Some(contract) = &node.contract {
+            for (span, expr) in contract.assumes.iter() {
+                self.check_assert(node, "assume", span, expr);
+            }
+            for (span, expr) in contract.guarantees.iter() {
+                self.check_assert(node, "guarantee", span, expr);
+            }
+            for mode in contract.modes.iter() {
+                for (span, expr) in mode.requires.iter() {
+                    self.check_assert(node, "require", span, expr);
+                }
+                for (span, expr) in mode.ensures.iter() {
+                    self.check_assert(node, "ensure", span, expr);
                 }
-                None => {
+            }
+        }
+        true
+    }
+
+    /// Equations of every state have the declared type of their variable,
+    /// transition conditions are boolean flows
+    fn check_automaton(&mut self, node: &Node, automaton: &Automaton) {
+        for state in automaton.states.iter() {
+            for transition in state.unless.iter().chain(state.until.iter()) {
+                let keyword = transition.span_keyword.fragment();
+                self.check_assert(node, &keyword, &transition.span_keyword, &transition.cond);
+            }
+            for (name, expr) in state.let_bindings.iter() {
+                // Undeclared variables are reported by [check_definitions]
+                let Some((_, t)) = node
+                    .outputs
+                    .iter()
+                    .chain(node.vars.iter())
+                    .find(|(v, _)| v == name)
+                else {
+                    continue;
+                };
+                let Some(t2) = self
+                    .get_type_expression(node, expr)
+                    .and_then(|t2| self.coerce_int_literal(expr, t2, t))
+                else {
+                    continue;
+                };
+                if t != &t2 {
                     self.push_diagnostic(Diagnostic {
                         message: format!(
-                            "Error while checking the type of '{}', expected : '{}'.",
-                            out, t
+                            "'{name}' is supposed to be of type '{t}', found '{t2}' in state '{}'.",
+                            state.name
                         ),
                         severity: Some(DiagnosticSeverity::ERROR),
-                        range: out.to_range(),
+                        range: name.to_range(),
+                        code: Some(ErrorCode::OutputTypeMismatch.into()),
+                        related_information: Some(vec![related(
+                            expr.to_range(),
+                            format!("This is of type '{t2}'."),
+                        )]),
                         ..Default::default()
                     });
                 }
@@ -865,6 +1586,44 @@ impl<'a> CheckerInfo<'a> {
         }
     }
 
+    /// Assertions, contract clauses and transition conditions are boolean
+    /// flows, known from the first instant
+    fn check_assert(&mut self, node: &Node, keyword: &str, span_assert: &Span, expr: &Expr) {
+        let Some(t) = self.get_type_expression(node, expr) else {
+            return;
+        };
+        let (code, message) = match &t {
+            VarType {
+                initialized: true,
+                inner: InnerVarType::Bool,
+            } => return,
+            VarType {
+                initialized: false,
+                inner: InnerVarType::Bool,
+            } => (
+                ErrorCode::NotInitialized,
+                format!(
+                    "'{keyword}' is not initialized at first instant, use '->' to give it an initial value."
+                ),
+            ),
+            t => (
+                ErrorCode::ExpectedBool,
+                format!("'{keyword}' expects type 'bool' but got '{t}'."),
+            ),
+        };
+        self.push_diagnostic(Diagnostic {
+            message,
+            severity: Some(DiagnosticSeverity::ERROR),
+            range: span_assert.to_range(),
+            code: Some(code.into()),
+            related_information: Some(vec![related(
+                expr.to_range(),
+                format!("This is of type '{t}'."),
+            )]),
+            ..Default::default()
+        });
+    }
+
     // Get the type of each nodes definition
     fn get_nodes_types(&mut self, nodes: &[Node]) {
         for node in nodes.iter() {
@@ -880,6 +1639,7 @@ impl<'a> CheckerInfo<'a> {
                     ),
                     severity: Some(DiagnosticSeverity::ERROR),
                     range: node.name.to_range(),
+                    code: Some(ErrorCode::DuplicateNode.into()),
                     ..Default::default()
                 });
                 continue;
@@ -906,24 +1666,56 @@ impl<'a> CheckerInfo<'a> {
     //
     // The check is modular for every node
     // given the types of all the nodes.
-    fn check_ast(&mut self, nodes: &[Node]) {
+    // The first `prelude_len` nodes come from the prelude and get no hints.
+    fn check_ast(&mut self, nodes: &[Node], prelude_len: usize) {
         self.get_nodes_types(nodes);
 
-        for node in nodes.iter() {
-            self.check_node(node);
-            self.push_type_hint_equation(node);
+        // Nodes can be declared in any order, called nodes are checked first
+        let (order, diags) = CallGraph::new(nodes).topological_order();
+        for diag in diags {
+            self.push_diagnostic(diag);
+        }
+        for (i, node) in order.into_iter().map(|i| (i, &nodes[i])) {
+            for diag in check_definitions(node) {
+                self.push_diagnostic(diag);
+            }
+            if self.check_node(node) && i >= prelude_len {
+                self.push_type_hint_equation(node);
+            }
         }
     }
 }
 
 impl Ast {
     pub fn check(&mut self) -> (Vec<Diagnostic>, Vec<InlayHint>) {
-        let Self { nodes, types } = self;
+        let Self {
+            nodes,
+            overflow,
+            prelude: _,
+            prelude_len,
+            types,
+        } = self;
+        // Checking again starts from the types of no node
+        *types = AstTypes::new();
         let (diags, hints) = {
-            let mut checker = CheckerInfo::new(types);
-            checker.check_ast(nodes);
+            let mut checker = CheckerInfo::new(types, *overflow);
+            checker.check_ast(nodes, *prelude_len);
             (checker.diagnostics, checker.hints)
         };
         (diags, hints)
     }
+    /// Type of `expr` in `node` given by the checker from the types of a
+    /// checked program, `types` are the types of [Ast::check]. `None` when
+    /// the node was not checked, like the nodes written by the interpreter.
+    pub(crate) fn type_of(
+        &self,
+        types: &mut AstTypes,
+        node: &Node,
+        expr: &Expr,
+    ) -> Option<VarType> {
+        if !types.node_defined(&node.name) {
+            return None;
+        }
+        CheckerInfo::new(types, self.overflow).get_type_expression(node, expr)
+    }
 }
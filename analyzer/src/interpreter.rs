@@ -1,10 +1,21 @@
 pub mod compiler;
 
 pub mod constant_propagate;
+pub mod csv;
 pub mod expr_index;
 
 pub mod instant;
+pub mod monitor;
+pub mod node_state;
+pub mod program;
+pub mod random;
+pub mod runtime;
+pub mod runtime_error;
+pub mod timeline;
+pub mod trace;
+pub mod vcd;
 
 pub mod compiled_expr;
 pub mod compiled_node;
 pub mod compiled_value;
+pub mod register_node;
@@ -1,9 +1,10 @@
 use crate::{
-    ast::{binop::BinOp, literal::Value, unary_op::UnaryOp},
-    interpreter::{expr_index::ExprIndex, instant::Instant},
+    ast::{binop::BinOp, builtin::Builtin, integer::Overflow, literal::Value, unary_op::UnaryOp},
+    interpreter::expr_index::ExprIndex,
 };
+use serde_derive::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum CompiledExpr {
     Input,
     Output,
@@ -26,6 +27,26 @@ pub enum CompiledExpr {
     },
     Variable(ExprIndex),
     Lit(Value),
+    /// Call of a built-in function on scalar arguments
+    Builtin {
+        builtin: Builtin,
+        args: Vec<ExprIndex>,
+    },
+    /// Step of the node instance number `instance` on `args`, only at the
+    /// instants where `cond` holds when it is given. Its memories are
+    /// forgotten before the steps where `reset` holds.
+    Call {
+        instance: usize,
+        args: Vec<ExprIndex>,
+        cond: Option<ExprIndex>,
+        reset: Option<ExprIndex>,
+    },
+    /// Output number `index` of the instance stepped by `call`
+    CallOutput {
+        call: ExprIndex,
+        instance: usize,
+        index: usize,
+    },
 }
 
 impl std::fmt::Display for CompiledExpr {
@@ -45,6 +66,31 @@ impl std::fmt::Display for CompiledExpr {
             CompiledExpr::If { cond, yes, no } => {
                 write!(f, "if {} then {} else {}", cond, yes, no)
             }
+            CompiledExpr::Builtin { builtin, args } => {
+                let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+                write!(f, "{builtin}({})", args.join(", "))
+            }
+            CompiledExpr::Call {
+                instance,
+                args,
+                cond,
+                reset,
+            } => {
+                let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+                write!(f, "call #{instance}({})", args.join(", "))?;
+                if let Some(cond) = cond {
+                    write!(f, " every {cond}")?;
+                }
+                match reset {
+                    Some(reset) => write!(f, " restart every {reset}"),
+                    None => Ok(()),
+                }
+            }
+            CompiledExpr::CallOutput {
+                call,
+                instance: _,
+                index,
+            } => write!(f, "{call}.{index}"),
         }
     }
 }
@@ -62,41 +108,48 @@ impl CompiledExpr {
                 lhs: i1, rhs: i2, ..
             } => vec![*i1, *i2],
             CompiledExpr::If { cond, yes, no } => vec![*cond, *yes, *no],
+            CompiledExpr::Builtin { args, .. } => args.clone(),
+            CompiledExpr::Call {
+                args, cond, reset, ..
+            } => args.iter().chain(cond).chain(reset).copied().collect(),
+            CompiledExpr::CallOutput { call, .. } => vec![*call],
             // CompiledExpr::Array(items) | CompiledExpr::Tuple(items) => items.clone(),
         }
     }
 
-    pub fn compute_one_step(&self, values: &[Option<Value>], instant: &Instant) -> Option<Value> {
+    /// Value of the expression at this instant, `->` is resolved by the
+    /// schedules of [CompiledNode](crate::interpreter::compiled_node::CompiledNode)
+    pub fn compute_one_step(&self, values: &[Option<Value>], overflow: Overflow) -> Option<Value> {
         match self {
-            CompiledExpr::Input => None,
-            CompiledExpr::Output => {
-                unreachable!()
-            }
+            // An output without equation has no value
+            CompiledExpr::Input | CompiledExpr::Output => None,
+            // Computed by [CompiledNode::step](crate::interpreter::compiled_node::CompiledNode::step)
+            CompiledExpr::Call { .. } | CompiledExpr::CallOutput { .. } => unreachable!(),
             CompiledExpr::Pre { src } => values[*src].clone(),
             CompiledExpr::BinOp { lhs, op, rhs } => {
                 let lv = values[*lhs].clone()?;
-                if op == &BinOp::Arrow && instant.is_init() {
-                    return Some(lv.clone());
-                }
                 let rv = values[*rhs].clone()?;
-                op.apply(&lv, &rv, Some(*instant))
+                op.apply(&lv, &rv, None, overflow)
             }
             CompiledExpr::UnaryOp { op, rhs } => {
                 let rv = &values[*rhs].clone()?;
-                op.apply(rv, Some(*instant))
+                op.apply(rv, None, overflow)
             }
             CompiledExpr::Variable(expr_index) => values[*expr_index].clone(),
             CompiledExpr::Lit(value) => Some(value.clone()),
-            CompiledExpr::If { cond, yes, no } => {
-                let cv = values[*cond].clone()?;
-                let yes = values[*yes].clone()?;
-                let no = values[*no].clone()?;
-
-                match cv {
-                    Value::Bool(true) => Some(yes),
-                    Value::Bool(false) => Some(no),
-                    _ => None,
-                }
+            // Only the chosen branch needs a value, the other one can be
+            // a `pre` at the first instant
+            CompiledExpr::If { cond, yes, no } => match values[*cond].clone()? {
+                Value::Bool(true) => values[*yes].clone(),
+                Value::Bool(false) => values[*no].clone(),
+                _ => None,
+            },
+            CompiledExpr::Builtin { builtin, args } => {
+                let args = args
+                    .iter()
+                    .map(|arg| values[*arg].clone())
+                    .collect::<Option<Vec<_>>>()?;
+                builtin.apply(&args)
             }
         }
     }
@@ -1,32 +1,105 @@
 use crate::{
-    ast::literal::Value,
-    interpreter::{compiled_expr::CompiledExpr, expr_index::ExprIndex, instant::Instant},
+    ast::integer::Overflow,
+    interpreter::{
+        compiled_expr::CompiledExpr, compiled_value::Scalar, expr_index::ExprIndex,
+        monitor::Monitor, node_state::NodeState, runtime_error::Origin, trace::Trace,
+    },
+    parser::span::Span,
 };
+use serde_derive::{Deserialize, Serialize};
+use std::rc::Rc;
 
+pub mod activate;
+pub mod origin;
+pub mod registers;
 pub mod schedule;
+pub mod snapshot;
 pub mod step;
+pub mod validate;
 
-#[derive(Debug, Clone)]
-pub struct CompiledNode {
+/// Text of an expression, the source it was compiled from, the named
+/// flows of the node it computes and its type when it fits in a register
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExprInfo {
+    pub text: String,
+    pub span: Option<Span>,
+    pub flows: Vec<String>,
+    /// Given by the type checker, `None` for the types that do not fit in a
+    /// register and in a program not checked
+    pub scalar: Option<Scalar>,
+}
+
+impl std::fmt::Display for ExprInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.text.fmt(f)
+    }
+}
+
+/// Expressions, schedules and monitors of a node, shared by all its
+/// instances
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompiledCode {
+    /// Name of the compiled node
+    name: String,
     exprs: Vec<CompiledExpr>,
-    infos: Vec<String>,
+    infos: Vec<ExprInfo>,
     inputs: Vec<ExprIndex>,
     outputs: Vec<ExprIndex>,
-    values: Vec<Option<Value>>,
-    instant: Instant,
+    /// Expressions computed at the first instant in order, with `->` resolved
+    init_step: Vec<(ExprIndex, CompiledExpr)>,
+    /// Expressions computed at the other instants in order
+    step: Vec<(ExprIndex, CompiledExpr)>,
+    /// `pre` and their source, updated in order once the outputs are known
+    memories: Vec<(ExprIndex, ExprIndex)>,
+    /// Code of the instances stepped by [CompiledExpr::Call], a node called
+    /// several times is compiled once. Saved apart by
+    /// [CompiledProgram](crate::interpreter::program::CompiledProgram).
+    #[serde(skip)]
+    instances: Vec<Rc<CompiledCode>>,
+    /// Boolean flows checked at every step: assertions and contracts
+    monitors: Vec<(ExprIndex, Monitor)>,
+    overflow: Overflow,
+    /// Expressions before it are not shared by [CompiledCode::push_expr]
+    #[serde(skip)]
+    memo_start: ExprIndex,
+    /// Source of the expressions pushed
+    #[serde(skip)]
+    span: Option<Span>,
+}
+
+/// Instance of a [CompiledCode] with its own memory
+#[derive(Debug, Clone)]
+pub struct CompiledNode {
+    code: Rc<CompiledCode>,
+    /// Values of the expressions and instant, the state replaced by
+    /// [CompiledNode::restore]
+    state: NodeState,
+    /// Origin of the outputs without value at the last step
+    faults: Vec<Option<Origin>>,
+    /// Instances of the called nodes, they have their own memory
+    instances: Vec<CompiledNode>,
+    /// Values of the named flows recorded at every step once started
+    trace: Option<Trace>,
 }
 
 impl std::fmt::Display for CompiledNode {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "Instant = {}", self.instant)?;
+        writeln!(f, "Instant = {}", self.state.instant)?;
         writeln!(f, "loop {{")?;
         let width = self
+            .code
             .exprs
             .iter()
             .map(|e| format!("{e}").len())
             .max()
             .unwrap();
-        for ((i, expr), value) in self.exprs.iter().enumerate().zip(self.values.iter()) {
+        for ((i, expr), value) in self
+            .code
+            .exprs
+            .iter()
+            .enumerate()
+            .zip(self.state.values.iter())
+        {
             write!(
                 f,
                 "\t{i:<3} -   {:<width$} >> {} // {:10}",
@@ -35,26 +108,34 @@ impl std::fmt::Display for CompiledNode {
                     Some(v) => format!("{v}"),
                     None => String::from("None"),
                 },
-                self.infos[i]
+                self.code.infos[i]
             )?;
-            if i != self.exprs.len() - 1 {
+            if i != self.code.exprs.len() - 1 {
                 writeln!(f)?;
             }
         }
         writeln!(f, "\n}}")?;
 
         write!(f, "input = [")?;
-        for (i, input) in self.inputs.iter().enumerate() {
+        for (i, input) in self.code.inputs.iter().enumerate() {
             write!(f, "{input}")?;
-            if i != self.inputs.len() - 1 {
+            if i != self.code.inputs.len() - 1 {
                 write!(f, ", ")?;
             }
         }
         writeln!(f, "]")?;
         write!(f, "output = [")?;
-        for (i, output) in self.outputs.iter().enumerate() {
+        for (i, output) in self.code.outputs.iter().enumerate() {
             write!(f, "{output}")?;
-            if i != self.outputs.len() - 1 {
+            if i != self.code.outputs.len() - 1 {
+                write!(f, ", ")?;
+            }
+        }
+        writeln!(f, "]")?;
+        write!(f, "monitors = [")?;
+        for (i, (index, _)) in self.code.monitors.iter().enumerate() {
+            write!(f, "{index}")?;
+            if i != self.code.monitors.len() - 1 {
                 write!(f, ", ")?;
             }
         }
@@ -62,21 +143,28 @@ impl std::fmt::Display for CompiledNode {
     }
 }
 
-impl Default for CompiledNode {
+impl Default for CompiledCode {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl CompiledNode {
+impl CompiledCode {
     pub fn new() -> Self {
         Self {
+            name: String::new(),
             exprs: Vec::new(),
             infos: Vec::new(),
             inputs: vec![],
             outputs: vec![],
-            values: vec![],
-            instant: Instant::INIT,
+            init_step: vec![],
+            step: vec![],
+            memories: vec![],
+            instances: vec![],
+            monitors: vec![],
+            overflow: Overflow::default(),
+            memo_start: 0,
+            span: None,
         }
     }
 
@@ -88,11 +176,24 @@ impl CompiledNode {
     }
     pub fn insert_expr(&mut self, index: usize, expr: CompiledExpr) {
         self.exprs.insert(index, expr);
-        self.values.push(None);
     }
     pub fn insert_info(&mut self, index: usize, info: String) {
-        self.infos.insert(index, info);
-        self.values.push(None);
+        let span = self.span.clone();
+        self.infos.insert(
+            index,
+            ExprInfo {
+                text: info,
+                span,
+                flows: vec![],
+                scalar: None,
+            },
+        );
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
     }
     pub fn set_inputs(&mut self, inputs: Vec<ExprIndex>) {
         self.inputs = inputs;
@@ -100,8 +201,37 @@ impl CompiledNode {
     pub fn set_outputs(&mut self, outputs: Vec<ExprIndex>) {
         self.outputs = outputs;
     }
-    pub fn set_values_to_none(&mut self) {
-        self.values = vec![None; self.len()];
+    pub fn outputs(&self) -> &[ExprIndex] {
+        &self.outputs
+    }
+    pub fn push_monitor(&mut self, index: ExprIndex, monitor: Monitor) {
+        self.monitors.push((index, monitor));
+    }
+    /// Code of the instances of the called nodes
+    pub fn instances(&self) -> &[Rc<CompiledCode>] {
+        &self.instances
+    }
+    pub(crate) fn set_instances(&mut self, instances: Vec<Rc<CompiledCode>>) {
+        self.instances = instances;
+    }
+    /// Returns the number of the instance
+    pub fn push_instance(&mut self, instance: Rc<CompiledCode>) -> usize {
+        self.instances.push(instance);
+        self.instances.len() - 1
+    }
+    pub fn monitors_len(&self) -> usize {
+        self.monitors.len()
+    }
+    /// Returns the previous source
+    pub fn set_span(&mut self, span: Option<Span>) -> Option<Span> {
+        std::mem::replace(&mut self.span, span)
+    }
+    /// Returns the previous start of the memoisation
+    pub fn set_memo_start(&mut self, start: ExprIndex) -> ExprIndex {
+        std::mem::replace(&mut self.memo_start, start)
+    }
+    pub fn set_overflow(&mut self, overflow: Overflow) {
+        self.overflow = overflow;
     }
     pub fn replace_expr(&mut self, expr: CompiledExpr, index: ExprIndex) {
         self.exprs[index] = expr;
@@ -111,16 +241,22 @@ impl CompiledNode {
     }
     pub fn push_back_expr_core(
         exprs: &mut Vec<CompiledExpr>,
-        infos: &mut Vec<String>,
+        infos: &mut Vec<ExprInfo>,
         expr: CompiledExpr,
-        info: String,
+        info: ExprInfo,
     ) -> ExprIndex {
         exprs.push(expr);
         infos.push(info);
         exprs.len() - 1
     }
     pub fn push_expr_core(&mut self, expr: CompiledExpr, info: String) -> ExprIndex {
-        CompiledNode::push_back_expr_core(&mut self.exprs, &mut self.infos, expr, info)
+        let info = ExprInfo {
+            text: info,
+            span: self.span.clone(),
+            flows: vec![],
+            scalar: None,
+        };
+        CompiledCode::push_back_expr_core(&mut self.exprs, &mut self.infos, expr, info)
     }
 
     /// Memoisation of compilation, if we push something already compiled this
@@ -128,15 +264,93 @@ impl CompiledNode {
     pub fn push_expr(&mut self, expr: CompiledExpr, info: String) -> ExprIndex {
         if expr != CompiledExpr::Output
                 && expr != CompiledExpr::Input // maybe not useful
-                && let Some(i) = self.exprs.iter().position(|x| x == &expr)
+                && let Some(i) = self.exprs[self.memo_start..].iter().position(|x| x == &expr)
         {
-            i
+            self.memo_start + i
         } else {
             self.push_expr_core(expr, info)
         }
     }
 
+    /// Named flows and the expression computing them, the inputs then the
+    /// outputs and the local variables
+    pub(crate) fn traced_flows(&self) -> Vec<(String, ExprIndex)> {
+        let position = |index: &ExprIndex| {
+            let input = self.inputs.iter().position(|input| input == index);
+            let output = self.outputs.iter().position(|output| output == index);
+            match (input, output) {
+                (Some(input), _) => (0, input),
+                (None, Some(output)) => (1, output),
+                (None, None) => (2, *index),
+            }
+        };
+        let mut flows: Vec<(String, ExprIndex)> = self
+            .infos
+            .iter()
+            .enumerate()
+            .flat_map(|(index, info)| info.flows.iter().map(move |name| (name.clone(), index)))
+            .collect();
+        flows.sort_by_key(|(_, index)| position(index));
+        flows
+    }
+    /// `index` computes the flow `name` of the node
+    pub fn name_flow(&mut self, index: ExprIndex, name: String) {
+        self.infos[index].flows.push(name);
+    }
+    /// Type of the register of `index`, an expression shared by
+    /// [CompiledCode::push_expr] keeps the first type given
+    pub fn type_expr(&mut self, index: ExprIndex, scalar: Option<Scalar>) {
+        let info = &mut self.infos[index];
+        info.scalar = info.scalar.or(scalar);
+    }
+    pub fn scalar(&self, index: ExprIndex) -> Option<Scalar> {
+        self.infos[index].scalar
+    }
     pub fn add_info(&mut self, index: ExprIndex, info: String) {
-        self.infos[index] = format!("{} - {}", info, self.infos[index]);
+        self.infos[index].text = format!("{} - {}", info, self.infos[index]);
+    }
+}
+
+impl CompiledNode {
+    /// Instance of `code` before its first step
+    pub fn new(code: Rc<CompiledCode>) -> Self {
+        Self {
+            state: NodeState::new(vec![None; code.len()]),
+            faults: vec![None; code.outputs.len()],
+            instances: code.instances.iter().cloned().map(Self::new).collect(),
+            trace: None,
+            code,
+        }
+    }
+    pub fn code(&self) -> &Rc<CompiledCode> {
+        &self.code
+    }
+    pub fn name(&self) -> &str {
+        &self.code.name
+    }
+
+    /// Record the named flows at every step from now on, the flows of the
+    /// instances are recorded in their own trace
+    pub fn start_trace(&mut self) {
+        self.start_trace_at(self.code.name.clone(), self.state.steps);
+    }
+    fn start_trace_at(&mut self, name: String, start: usize) {
+        self.trace = Some(Trace::new(name, self.code.traced_flows(), start));
+        for (i, instance) in self.instances.iter_mut().enumerate() {
+            let name = format!("{}_{i}", instance.code.name);
+            instance.start_trace_at(name, start);
+        }
+    }
+    /// Trace of the flows of this node, without its instances
+    pub fn trace(&self) -> Option<&Trace> {
+        self.trace.as_ref()
+    }
+    /// Stop recording and return the trace with the traces of the instances
+    pub fn take_trace(&mut self) -> Option<Trace> {
+        let mut trace = self.trace.take()?;
+        for instance in self.instances.iter_mut() {
+            trace.push_instance(instance.take_trace()?);
+        }
+        Some(trace)
     }
 }
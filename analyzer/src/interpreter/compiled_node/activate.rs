@@ -0,0 +1,73 @@
+use crate::{
+    ast::{binop::BinOp, unary_op::UnaryOp},
+    interpreter::{
+        compiled_expr::CompiledExpr, compiled_node::CompiledCode, expr_index::ExprIndex,
+    },
+};
+
+impl CompiledCode {
+    /// Activation condition of the expressions from `start`, a node instance
+    /// called with `condact`:
+    /// - a `pre` keeps its value when `cond` does not hold
+    /// - an `->` takes its left side until `cond` held once, given by `never`
+    /// - a monitor from `monitors_start` is only checked when `cond` holds
+    pub fn activate(
+        &mut self,
+        start: ExprIndex,
+        monitors_start: usize,
+        cond: ExprIndex,
+        never: ExprIndex,
+    ) {
+        for index in start..self.len() {
+            match self.exprs[index].clone() {
+                CompiledExpr::Pre { src } => {
+                    let info = self.infos[index].text.clone();
+                    let src = self.push_expr_core(
+                        CompiledExpr::If {
+                            cond,
+                            yes: src,
+                            no: index,
+                        },
+                        info,
+                    );
+                    self.replace_expr(CompiledExpr::Pre { src }, index);
+                }
+                CompiledExpr::BinOp {
+                    lhs,
+                    op: BinOp::Arrow,
+                    rhs,
+                } => self.replace_expr(
+                    CompiledExpr::If {
+                        cond: never,
+                        yes: lhs,
+                        no: rhs,
+                    },
+                    index,
+                ),
+                _ => {}
+            }
+        }
+        if self.monitors.len() == monitors_start {
+            return;
+        }
+        let not_cond = self.push_expr_core(
+            CompiledExpr::UnaryOp {
+                op: UnaryOp::Not,
+                rhs: cond,
+            },
+            format!("not {cond}"),
+        );
+        for i in monitors_start..self.monitors.len() {
+            let index = self.monitors[i].0;
+            let info = self.infos[index].text.clone();
+            self.monitors[i].0 = self.push_expr_core(
+                CompiledExpr::BinOp {
+                    lhs: not_cond,
+                    op: BinOp::Or,
+                    rhs: index,
+                },
+                info,
+            );
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use crate::{
+    ast::{binop::BinOp, literal::Value, unary_op::UnaryOp},
+    interpreter::{
+        compiled_expr::CompiledExpr,
+        compiled_node::CompiledNode,
+        expr_index::ExprIndex,
+        runtime_error::{Dataflow, Origin, RuntimeErrorKind},
+    },
+    parser::span::Span,
+};
+
+impl Dataflow for CompiledNode {
+    fn has_value(&self, index: ExprIndex) -> bool {
+        self.state.values[index].is_some()
+    }
+    fn reads(&self, index: ExprIndex) -> Vec<ExprIndex> {
+        match &self.code.exprs[index] {
+            CompiledExpr::BinOp {
+                lhs,
+                op: BinOp::Arrow,
+                rhs,
+            } => vec![if self.state.instant.is_init() {
+                *lhs
+            } else {
+                *rhs
+            }],
+            CompiledExpr::If { cond, yes, no } => match self.state.values[*cond] {
+                Some(Value::Bool(true)) => vec![*yes],
+                Some(Value::Bool(false)) => vec![*no],
+                _ => vec![*cond],
+            },
+            expr => expr.get_neighbours(),
+        }
+    }
+    fn input(&self, index: ExprIndex) -> Option<usize> {
+        self.code.inputs.iter().position(|input| *input == index)
+    }
+    fn is_memory(&self, index: ExprIndex) -> bool {
+        matches!(self.code.exprs[index], CompiledExpr::Pre { .. })
+    }
+    fn instance_origin(&self, index: ExprIndex) -> Option<(Origin, &[ExprIndex])> {
+        let CompiledExpr::CallOutput {
+            call,
+            instance,
+            index,
+        } = &self.code.exprs[index]
+        else {
+            return None;
+        };
+        let CompiledExpr::Call { args, .. } = &self.code.exprs[*call] else {
+            return None;
+        };
+        let origin = self.instances[*instance].faults[*index].clone()?;
+        Some((origin, args))
+    }
+    fn fault(&self, index: ExprIndex) -> RuntimeErrorKind {
+        let is_int = |index: &ExprIndex| {
+            matches!(
+                self.state.values[*index],
+                Some(Value::Int(_) | Value::SizedInt(_, _))
+            )
+        };
+        match &self.code.exprs[index] {
+            CompiledExpr::BinOp {
+                op: BinOp::Div,
+                rhs,
+                ..
+            } if matches!(
+                self.state.values[*rhs],
+                Some(Value::Int(0) | Value::SizedInt(_, 0))
+            ) =>
+            {
+                RuntimeErrorKind::DivisionByZero
+            }
+            CompiledExpr::BinOp { lhs, op, .. }
+                if is_int(lhs)
+                    && matches!(
+                        op,
+                        BinOp::Add
+                            | BinOp::Sub
+                            | BinOp::Mult
+                            | BinOp::Div
+                            | BinOp::Land
+                            | BinOp::Lor
+                            | BinOp::Lxor
+                            | BinOp::Shl
+                            | BinOp::Shr
+                    ) =>
+            {
+                RuntimeErrorKind::Overflow
+            }
+            CompiledExpr::UnaryOp {
+                op:
+                    UnaryOp::Inv
+                    | UnaryOp::Abs
+                    | UnaryOp::ToInt
+                    | UnaryOp::Floor
+                    | UnaryOp::Round
+                    | UnaryOp::ToSized(_),
+                ..
+            } => RuntimeErrorKind::Overflow,
+            _ => RuntimeErrorKind::NoValue,
+        }
+    }
+    fn span(&self, index: ExprIndex) -> Option<Span> {
+        self.code.infos[index].span.clone()
+    }
+}
@@ -0,0 +1,152 @@
+use crate::{
+    ast::{binop::BinOp, unary_op::UnaryOp},
+    interpreter::{
+        compiled_expr::CompiledExpr,
+        compiled_node::CompiledCode,
+        compiled_value::Scalar,
+        expr_index::ExprIndex,
+        register_node::{RegisterCode, RegisterNode, instruction::Instruction},
+    },
+};
+use std::rc::Rc;
+
+impl CompiledCode {
+    /// Same node on registers, with the types given by the checker.
+    ///
+    /// `None` when a flow is not a scalar, like a string or an array given
+    /// as input, or when the program was not checked.
+    pub fn registers(&self) -> Option<RegisterNode> {
+        Some(RegisterNode::new(Rc::new(self.register_code(&mut vec![])?)))
+    }
+
+    /// Instructions of the node, `built` are the codes of the instances
+    /// already on registers: a node called several times is lowered once
+    fn register_code(
+        &self,
+        built: &mut Vec<(Rc<CompiledCode>, Rc<RegisterCode>)>,
+    ) -> Option<RegisterCode> {
+        let types: Vec<Scalar> = self
+            .infos
+            .iter()
+            .map(|info| info.scalar)
+            .collect::<Option<_>>()?;
+        let mut instances = vec![];
+        for instance in self.instances.iter() {
+            let code = match built.iter().find(|(code, _)| Rc::ptr_eq(code, instance)) {
+                Some((_, code)) => code.clone(),
+                None => {
+                    let code = Rc::new(instance.register_code(built)?);
+                    built.push((instance.clone(), code.clone()));
+                    code
+                }
+            };
+            instances.push(code);
+        }
+        let lower = |schedule: &[(ExprIndex, CompiledExpr)]| {
+            schedule
+                .iter()
+                .map(|(index, expr)| Some((*index, instruction(expr, types[*index], &types)?)))
+                .collect::<Option<Vec<_>>>()
+        };
+        Some(RegisterCode::new(
+            self.name.clone(),
+            lower(&self.init_step)?,
+            lower(&self.step)?,
+            self.memories.clone(),
+            self.infos.iter().map(|info| info.span.clone()).collect(),
+            types,
+            self.inputs.clone(),
+            self.outputs.clone(),
+            instances,
+            self.monitors.clone(),
+            self.traced_flows(),
+            self.overflow,
+        ))
+    }
+}
+
+/// Instruction computing `expr` of type `t`, `->` is resolved by the schedules
+fn instruction(expr: &CompiledExpr, t: Scalar, types: &[Scalar]) -> Option<Instruction> {
+    Some(match expr {
+        CompiledExpr::Input | CompiledExpr::Output => return None,
+        CompiledExpr::Lit(value) => Instruction::Lit(Scalar::to_register(value)?),
+        CompiledExpr::Pre { src } | CompiledExpr::Variable(src) => Instruction::Copy(*src),
+        CompiledExpr::BinOp { lhs, op, rhs } => {
+            let (lhs, rhs) = (*lhs, *rhs);
+            match op {
+                BinOp::Eq | BinOp::Neq => Instruction::Eq {
+                    neq: op == &BinOp::Neq,
+                    scalar: types[lhs],
+                    lhs,
+                    rhs,
+                },
+                BinOp::And => Instruction::And { lhs, rhs },
+                BinOp::Or => Instruction::Or { lhs, rhs },
+                BinOp::Bit => Instruction::Bit {
+                    kind: types[lhs].kind(),
+                    lhs,
+                    rhs,
+                },
+                BinOp::Add | BinOp::Sub | BinOp::Mult | BinOp::Div if t == Scalar::Float => {
+                    Instruction::Float { op: *op, lhs, rhs }
+                }
+                BinOp::Add
+                | BinOp::Sub
+                | BinOp::Mult
+                | BinOp::Div
+                | BinOp::Land
+                | BinOp::Lor
+                | BinOp::Lxor
+                | BinOp::Shl
+                | BinOp::Shr => Instruction::Int {
+                    op: *op,
+                    kind: t.kind(),
+                    lhs,
+                    rhs,
+                },
+                BinOp::Arrow | BinOp::Caret | BinOp::Fby | BinOp::Since | BinOp::Triggered => {
+                    return None;
+                }
+            }
+        }
+        CompiledExpr::UnaryOp {
+            op:
+                UnaryOp::Pre | UnaryOp::Len | UnaryOp::Once | UnaryOp::Historically | UnaryOp::Yesterday,
+            ..
+        } => return None,
+        CompiledExpr::UnaryOp { op, rhs } => Instruction::Unary {
+            op: *op,
+            scalar: types[*rhs],
+            rhs: *rhs,
+        },
+        CompiledExpr::If { cond, yes, no } => Instruction::If {
+            cond: *cond,
+            yes: *yes,
+            no: *no,
+        },
+        CompiledExpr::Builtin { builtin, args } => Instruction::Builtin {
+            builtin: *builtin,
+            scalar: t,
+            args: args.clone(),
+        },
+        CompiledExpr::Call {
+            instance,
+            args,
+            cond,
+            reset,
+        } => Instruction::Call {
+            instance: *instance,
+            args: args.clone(),
+            cond: *cond,
+            reset: *reset,
+        },
+        CompiledExpr::CallOutput {
+            call: _,
+            instance,
+            index,
+        } => Instruction::CallOutput {
+            instance: *instance,
+            index: *index,
+        },
+    })
+}
@@ -1,17 +1,20 @@
-use crate::interpreter::{
-    compiled_expr::CompiledExpr, compiled_node::CompiledNode, expr_index::ExprIndex,
-    instant::Instant,
+use crate::{
+    ast::binop::BinOp,
+    interpreter::{
+        compiled_expr::CompiledExpr,
+        compiled_node::{CompiledCode, ExprInfo},
+        expr_index::ExprIndex,
+    },
 };
-use colored::Colorize;
 
-impl CompiledNode {
+impl CompiledCode {
     pub fn move_into(
         &self,
         exprs: &mut Vec<CompiledExpr>,
-        infos: &mut Vec<String>,
+        infos: &mut Vec<ExprInfo>,
         index: usize,
     ) -> ExprIndex {
-        CompiledNode::push_back_expr_core(
+        CompiledCode::push_back_expr_core(
             exprs,
             infos,
             self.exprs[index].clone(),
@@ -32,8 +35,20 @@ impl CompiledNode {
 
         pile.push(start);
     }
+    /// A `pre` reading another `pre` is updated before it
+    fn schedule_pre(&self, start: usize, done: &mut [bool], pile: &mut Vec<usize>) {
+        if done[start] {
+            return;
+        }
+        done[start] = true;
+        for (index, expr) in self.exprs.iter().enumerate() {
+            if matches!(expr, CompiledExpr::Pre { src } if *src == start) {
+                self.schedule_pre(index, done, pile);
+            }
+        }
+        pile.push(start);
+    }
     pub fn schedule(&self) -> Self {
-        eprintln!("{}", ">> SCHEDULE".blue());
         use CompiledExpr::*;
         let number_expression = self.len();
 
@@ -43,10 +58,15 @@ impl CompiledNode {
 
         let mut done = vec![false; number_expression];
         let mut pile: Vec<ExprIndex> = vec![];
-        // We do a BFS on the CompiledNode
-        for index in 0..self.exprs.len() {
+        // We do a BFS on the CompiledCode, the `pre` are updated once
+        // every other expression has read their value
+        let is_pre = |index: usize| matches!(self.exprs[index], Pre { .. });
+        for index in (0..self.exprs.len()).filter(|index| !is_pre(*index)) {
             self.bfs(index, &mut done, &mut pile);
         }
+        for index in (0..self.exprs.len()).filter(|index| is_pre(*index)) {
+            self.schedule_pre(index, &mut done, &mut pile);
+        }
 
         // Rearrange the expression according to the `pile` vector
 
@@ -54,17 +74,6 @@ impl CompiledNode {
             new_index[*past] = Some(new);
         }
 
-        eprintln!("> PILE : ");
-        for (i, index) in pile.iter().enumerate() {
-            eprintln!("\t{i} - {index}");
-        }
-        eprintln!("> New Index : ");
-        for (i, index) in new_index.iter().enumerate() {
-            if let Some(index) = index {
-                eprintln!("\t{i} - {index}");
-            }
-        }
-
         for past in pile.iter() {
             self.move_into(&mut exprs, &mut infos, *past);
         }
@@ -87,12 +96,25 @@ impl CompiledNode {
                     *yes = new_index[*yes].unwrap();
                     *no = new_index[*no].unwrap();
                 }
+                Builtin { args, .. } => {
+                    args.iter_mut().for_each(|i| *i = new_index[*i].unwrap());
+                }
+                Call {
+                    args, cond, reset, ..
+                } => {
+                    args.iter_mut()
+                        .chain(cond)
+                        .chain(reset)
+                        .for_each(|i| *i = new_index[*i].unwrap());
+                }
+                CallOutput { call, .. } => {
+                    *call = new_index[*call].unwrap();
+                }
                 Input | Output | Lit(_) => {}
             }
         }
 
-        let values = vec![None; exprs.len()];
-        let outputs = self
+        let outputs: Vec<ExprIndex> = self
             .outputs
             .iter()
             .map(|index| new_index[*index].unwrap())
@@ -102,14 +124,132 @@ impl CompiledNode {
             .iter()
             .map(|index| new_index[*index].unwrap())
             .collect();
+        let monitors: Vec<_> = self
+            .monitors
+            .iter()
+            .map(|(index, monitor)| (new_index[*index].unwrap(), monitor.clone()))
+            .collect();
+
+        // A `pre` stores its source at every instant, an instance is stepped
+        // and a named flow is computed even when they are not read
+        let roots: Vec<ExprIndex> = (0..exprs.len())
+            .filter(|index| {
+                matches!(exprs[*index], Pre { .. } | Call { .. }) || !infos[*index].flows.is_empty()
+            })
+            .chain(outputs.iter().copied())
+            .chain(monitors.iter().map(|(index, _)| *index))
+            .collect();
+        let init_step = specialize(&exprs, &roots, true);
+        let step = specialize(&exprs, &roots, false);
+        let memories = exprs
+            .iter()
+            .enumerate()
+            .filter_map(|(index, expr)| match expr {
+                Pre { src } => Some((index, *src)),
+                _ => None,
+            })
+            .collect();
 
-        CompiledNode {
+        CompiledCode {
+            name: self.name.clone(),
             exprs,
             infos,
             inputs,
             outputs,
-            values,
-            instant: Instant::INIT,
+            init_step,
+            step,
+            memories,
+            instances: self.instances.clone(),
+            monitors,
+            overflow: self.overflow,
+            memo_start: 0,
+            span: None,
+        }
+    }
+}
+
+/// Schedule of the first instant or of the other ones: `->` is replaced by
+/// the side it takes and only the expressions read by `roots` are computed,
+/// in the order of `exprs`. The `pre` are updated apart, after the others.
+fn specialize(
+    exprs: &[CompiledExpr],
+    roots: &[ExprIndex],
+    init: bool,
+) -> Vec<(ExprIndex, CompiledExpr)> {
+    let exprs: Vec<CompiledExpr> = exprs
+        .iter()
+        .map(|expr| match expr {
+            CompiledExpr::BinOp {
+                lhs,
+                op: BinOp::Arrow,
+                rhs,
+            } => CompiledExpr::Variable(if init { *lhs } else { *rhs }),
+            expr => expr.clone(),
+        })
+        .collect();
+    let mut live = vec![false; exprs.len()];
+    let mut pile = roots.to_vec();
+    while let Some(index) = pile.pop() {
+        if !live[index] {
+            live[index] = true;
+            pile.extend(exprs[index].get_neighbours());
         }
     }
+    exprs
+        .into_iter()
+        .enumerate()
+        .filter(|(index, expr)| {
+            live[*index] && !matches!(expr, CompiledExpr::Input | CompiledExpr::Pre { .. })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        ast::{binop::BinOp, literal::Value},
+        interpreter::compiled_expr::CompiledExpr,
+        parser::{lustre_parser::lustre_parse, span::Span},
+    };
+
+    #[test]
+    fn arrow_resolved() {
+        let ast = lustre_parse(
+            "node f(x: int) returns (y: int);
+let
+    y = 7 -> x * 2;
+tel",
+        )
+        .unwrap();
+        let mut node = ast.compile(Span::new(0, 0, "f".to_string()));
+        let computes = |schedule: &[(usize, CompiledExpr)],
+                        expr: &dyn Fn(&CompiledExpr) -> bool| {
+            schedule.iter().any(|(_, e)| expr(e))
+        };
+        let mult = |e: &CompiledExpr| {
+            matches!(
+                e,
+                CompiledExpr::BinOp {
+                    op: BinOp::Mult,
+                    ..
+                }
+            )
+        };
+        let seven = |e: &CompiledExpr| e == &CompiledExpr::Lit(Value::Int(7));
+        let arrow = |e: &CompiledExpr| {
+            matches!(
+                e,
+                CompiledExpr::BinOp {
+                    op: BinOp::Arrow,
+                    ..
+                }
+            )
+        };
+        let code = node.code().clone();
+        assert!(computes(&code.init_step, &seven) && !computes(&code.init_step, &mult));
+        assert!(computes(&code.step, &mult) && !computes(&code.step, &seven));
+        assert!(!computes(&code.init_step, &arrow) && !computes(&code.step, &arrow));
+        assert_eq!(node.step(vec![Value::Int(1)]), Ok(vec![Value::Int(7)]));
+        assert_eq!(node.step(vec![Value::Int(2)]), Ok(vec![Value::Int(4)]));
+    }
 }
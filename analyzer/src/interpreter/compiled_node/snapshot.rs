@@ -0,0 +1,71 @@
+use crate::interpreter::{compiled_node::CompiledNode, node_state::Snapshot};
+
+impl CompiledNode {
+    /// State of the node now, to come back to it with [CompiledNode::restore]
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            state: self.state.clone(),
+            instances: self.instances.iter().map(|i| i.snapshot()).collect(),
+        }
+    }
+    /// Go back to the state of `snapshot`, `false` when it was taken on
+    /// another node and nothing changes. The trace is recorded again from
+    /// the next step, and starts there when it is before the start.
+    pub fn restore(&mut self, snapshot: &Snapshot) -> bool {
+        if !self.fits(snapshot) {
+            return false;
+        }
+        self.restore_unchecked(snapshot);
+        true
+    }
+    fn restore_unchecked(&mut self, snapshot: &Snapshot) {
+        self.state = snapshot.state.clone();
+        for (instance, snapshot) in self.instances.iter_mut().zip(snapshot.instances.iter()) {
+            instance.restore_unchecked(snapshot);
+        }
+    }
+    /// Whether `snapshot` was taken on this node
+    fn fits(&self, snapshot: &Snapshot) -> bool {
+        snapshot.state.values.len() == self.state.values.len()
+            && snapshot.instances.len() == self.instances.len()
+            && self
+                .instances
+                .iter()
+                .zip(snapshot.instances.iter())
+                .all(|(instance, snapshot)| instance.fits(snapshot))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        ast::literal::Value,
+        interpreter::node_state::Snapshot,
+        test::{compile_node, with_counter},
+    };
+
+    const MAIN: &str = "node main(x: int) returns (n: int, m: int);
+let
+    n = counter(x);
+    m = 0 -> pre n;
+tel
+";
+
+    #[test]
+    fn restore_serialized_snapshot() {
+        let ast = with_counter(MAIN);
+        let mut node = compile_node(&ast, "main");
+        node.step(vec![Value::Int(1)]).unwrap();
+        let json = serde_json::to_string(&node.snapshot()).unwrap();
+        let after: Vec<_> = (2..5).map(|x| node.step(vec![Value::Int(x)])).collect();
+
+        let snapshot: Snapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(snapshot.steps(), 1);
+        assert!(node.restore(&snapshot));
+        let again: Vec<_> = (2..5).map(|x| node.step(vec![Value::Int(x)])).collect();
+        assert_eq!(after, again);
+
+        let counter = compile_node(&ast, "counter");
+        assert!(!node.restore(&counter.snapshot()));
+    }
+}
@@ -1,52 +1,249 @@
 use crate::{
     ast::literal::Value,
-    interpreter::{compiled_expr::CompiledExpr, compiled_node::CompiledNode},
+    interpreter::{
+        compiled_expr::CompiledExpr,
+        compiled_node::CompiledNode,
+        expr_index::ExprIndex,
+        instant::Instant,
+        runtime_error::{Dataflow, RuntimeError, RuntimeErrorKind},
+    },
 };
-use colored::Colorize;
 
 impl CompiledNode {
-    pub fn step(&mut self, inputs: Vec<Value>) -> Vec<Value> {
-        // println!("{} >>\n{}\n", "COMPILE".blue(), &self);
-        let Self {
-            exprs: vec,
-            infos: info,
-            inputs: inputs_index,
-            outputs: outputs_index,
-            values,
-            instant,
-        } = self;
-        for (index, val) in inputs_index.iter().zip(inputs.into_iter()) {
-            values[*index] = Some(val);
-        }
-        for (pos, expr) in vec.iter().enumerate() {
-            if expr == &CompiledExpr::Input {
-                continue;
-            }
-            values[pos] = expr.compute_one_step(values, instant);
-        }
-        eprintln!(
-            "{} >>\n{}\n",
-            "COMPILE".blue(),
-            Self {
-                exprs: vec.clone(),
-                infos: info.clone(),
-                inputs: inputs_index.clone(),
-                outputs: outputs_index.clone(),
-                values: values.clone(),
-                instant: *instant,
-            }
-        );
-        let mut res = vec![];
-        for output in outputs_index.iter() {
-            match values[*output].clone() {
-                Some(v) => res.push(v),
-                None => {
-                    panic!(">> Output at index {} is NONE.", output);
+    /// Compute the outputs of one instant. The monitors are checked once the
+    /// whole instant is computed, instances included: assertions and
+    /// assumptions before guarantees, then the outputs
+    pub fn step(&mut self, inputs: Vec<Value>) -> Result<Vec<Value>, RuntimeError> {
+        if inputs.len() != self.code.inputs.len() {
+            let kind = RuntimeErrorKind::InputCount {
+                expected: self.code.inputs.len(),
+                found: inputs.len(),
+            };
+            return Err(RuntimeError {
+                kind,
+                instant: self.state.steps,
+                span: None,
+            });
+        }
+        self.run(inputs.into_iter().map(Some).collect(), self.state.steps)?;
+        if let Some(fault) = self.faults.iter().flatten().next() {
+            return Err(fault.clone().error(self.state.steps));
+        }
+        let res = self
+            .code
+            .outputs
+            .iter()
+            .flat_map(|output| self.state.values[*output].clone())
+            .collect();
+        self.state.steps += 1;
+        Ok(res)
+    }
+
+    /// One instant of the node, the violations are reported at the instant
+    /// `steps` of the program
+    fn run(&mut self, inputs: Vec<Option<Value>>, steps: usize) -> Result<(), RuntimeError> {
+        for (index, val) in self.code.inputs.iter().zip(inputs.into_iter()) {
+            self.state.values[*index] = val;
+        }
+        let schedule = if self.state.instant.is_init() {
+            &self.code.init_step
+        } else {
+            &self.code.step
+        };
+        for (pos, expr) in schedule.iter() {
+            self.state.values[*pos] = match expr {
+                CompiledExpr::Call {
+                    instance,
+                    args,
+                    cond,
+                    reset,
+                } => {
+                    let holds =
+                        |index: &ExprIndex| self.state.values[*index] == Some(Value::Bool(true));
+                    if cond.as_ref().is_none_or(holds) {
+                        if reset.as_ref().is_some_and(holds) {
+                            self.instances[*instance].restart();
+                        }
+                        let args = args
+                            .iter()
+                            .map(|arg| self.state.values[*arg].clone())
+                            .collect();
+                        self.instances[*instance]
+                            .run(args, steps)
+                            .map_err(|error| error.called_by(&self.code.name))?;
+                    }
+                    Some(Value::Unit)
+                }
+                CompiledExpr::CallOutput {
+                    call: _,
+                    instance,
+                    index,
+                } => {
+                    let instance = &self.instances[*instance];
+                    instance.state.values[instance.code.outputs[*index]].clone()
                 }
+                expr => expr.compute_one_step(&self.state.values, self.code.overflow),
+            };
+        }
+        if let Some(trace) = &mut self.trace {
+            trace.record(steps, &self.state.values);
+        }
+        let (guarantees, assumptions): (Vec<_>, Vec<_>) = self
+            .code
+            .monitors
+            .iter()
+            .partition(|(_, monitor)| monitor.is_guarantee());
+        for (index, monitor) in assumptions.into_iter().chain(guarantees) {
+            match self.state.values[*index] {
+                Some(Value::Bool(false)) => {
+                    return Err(monitor.violation(steps, self.code.infos[*index].span.clone()));
+                }
+                // A monitor that cannot be computed is not known to hold
+                None => return Err(self.origin(*index).error(steps)),
+                _ => (),
             }
         }
+        // The origins are found before the `pre` forget the values read
+        for i in 0..self.code.outputs.len() {
+            self.faults[i] = match self.state.values[self.code.outputs[i]] {
+                Some(_) => None,
+                None => Some(self.origin(self.code.outputs[i])),
+            };
+        }
+        for (pre, src) in self.code.memories.iter() {
+            self.state.values[*pre] = self.state.values[*src].clone();
+        }
+        self.state.instant.step();
+        Ok(())
+    }
+    /// Forget the memories of the node and of its instances, the next step
+    /// is a first instant
+    fn restart(&mut self) {
+        self.state.values = vec![None; self.state.values.len()];
+        self.state.instant = Instant::INIT;
+        for instance in self.instances.iter_mut() {
+            instance.restart();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        ast::literal::Value,
+        interpreter::compiled_node::CompiledCode,
+        parser::span::Span,
+        test::{compile_node, with_counter},
+    };
+    use std::rc::Rc;
+
+    const MAIN: &str = "node main(x: int, c: bool) returns (a: int, b: int);
+let
+    a = counter(x) + counter(2 * x);
+    b = condact(c, counter(x), 0);
+tel";
+
+    const AUTOMATON: &str = "node main(go: bool) returns (y: int);
+let
+    automaton
+        initial state Off
+            let
+                y = 0;
+            tel
+            until go restart On;
+        state On
+            let
+                y = counter(1);
+            tel
+            until not go restart Off;
+    end;
+tel";
+
+    const DIAMOND: &str = "node left(x: int) returns (y: int);
+let
+    y = counter(x) + counter(x + 1);
+tel
+
+node right(x: int) returns (y: int);
+let
+    y = counter(2 * x);
+tel
+
+node main(x: int) returns (y: int);
+let
+    y = left(x) + right(x) + left(x + 2);
+tel";
+
+    /// Number of expressions of `code` and of the code of its instances,
+    /// each code counted once
+    fn size(code: &Rc<CompiledCode>, seen: &mut Vec<Rc<CompiledCode>>) -> usize {
+        if seen.iter().any(|other| Rc::ptr_eq(other, code)) {
+            return 0;
+        }
+        seen.push(code.clone());
+        let instances: usize = code.instances.iter().map(|i| size(i, seen)).sum();
+        code.len() + instances
+    }
 
-        self.instant.step();
-        res
+    #[test]
+    fn modular_and_inlined() {
+        let ast = with_counter(MAIN);
+        let main = Span::new(0, 0, "main".to_string());
+        let mut modular = ast.compile(main.clone());
+        let mut inlined = ast.compile_inlined(main.clone());
+        let mut registers = ast.compile_registers(main).unwrap();
+        assert_eq!(modular.instances.len(), 3);
+        assert!(inlined.instances.is_empty());
+        for (x, c) in [(1, false), (2, true), (3, false), (4, true), (5, true)] {
+            let inputs = vec![Value::Int(x), Value::Bool(c)];
+            let outputs = modular.step(inputs.clone());
+            assert_eq!(outputs, inlined.step(inputs.clone()));
+            assert_eq!(outputs, registers.step(inputs));
+        }
+        assert_eq!(
+            modular.step(vec![Value::Int(6), Value::Bool(true)]),
+            Ok(vec![Value::Int(63), Value::Int(17)])
+        );
+    }
+    #[test]
+    fn calls_in_a_restarted_state() {
+        let ast = with_counter(AUTOMATON);
+        let main = Span::new(0, 0, "main".to_string());
+        let mut modular = ast.compile(main.clone());
+        let mut inlined = ast.compile_inlined(main.clone());
+        let mut registers = ast.compile_registers(main).unwrap();
+        let mut ys = vec![];
+        for go in [true, true, true, false, true, true] {
+            let inputs = vec![Value::Bool(go)];
+            let outputs = modular.step(inputs.clone());
+            assert_eq!(outputs, inlined.step(inputs.clone()));
+            assert_eq!(outputs, registers.step(inputs));
+            ys.extend(outputs.unwrap());
+        }
+        let expected = [0, 1, 2, 3, 0, 1];
+        assert_eq!(ys, expected.map(Value::Int));
+    }
+    #[test]
+    fn code_shared_by_instances() {
+        let ast = with_counter(DIAMOND);
+        let mut node = compile_node(&ast, "main");
+        let code = node.code().clone();
+        let counters: Vec<_> = code.instances.iter().flat_map(|i| &i.instances).collect();
+        assert_eq!(counters.len(), 5);
+        assert!(
+            counters
+                .iter()
+                .all(|counter| Rc::ptr_eq(counter, counters[0]))
+        );
+        assert!(Rc::ptr_eq(&code.instances[0], &code.instances[2]));
+        let nodes: usize = ["main", "left", "right", "counter"]
+            .map(|name| compile_node(&ast, name).code().len())
+            .iter()
+            .sum();
+        assert_eq!(size(&code, &mut vec![]), nodes);
+        // Each instance still has its own memory
+        assert_eq!(node.instances.len(), 3);
+        node.step(vec![Value::Int(1)]).unwrap();
+        assert_eq!(node.step(vec![Value::Int(1)]), Ok(vec![Value::Int(24)]));
     }
 }
@@ -0,0 +1,76 @@
+use crate::interpreter::{compiled_expr::CompiledExpr, compiled_node::CompiledCode};
+
+impl CompiledCode {
+    /// Check that the code of a node read from a saved program only refers
+    /// to its own expressions, instances and outputs
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        let len = self.exprs.len();
+        let invalid = |what: String| Err(format!("node '{}': {what}", self.name));
+        if self.infos.len() != len {
+            return invalid(format!("{} infos for {len} expressions", self.infos.len()));
+        }
+        let flows = self.inputs.iter().chain(self.outputs.iter());
+        let memories = self.memories.iter().flat_map(|(pre, src)| [pre, src]);
+        let monitors = self.monitors.iter().map(|(index, _)| index);
+        let schedules = self.init_step.iter().chain(self.step.iter());
+        let indices = flows
+            .chain(memories)
+            .chain(monitors)
+            .chain(schedules.clone().map(|(index, _)| index));
+        if let Some(index) = indices.copied().find(|index| *index >= len) {
+            return invalid(format!("expression {index} out of {len}"));
+        }
+        let exprs = self.exprs.iter().chain(schedules.map(|(_, expr)| expr));
+        for expr in exprs {
+            if let Err(what) = self.check_expr(expr) {
+                return invalid(format!("{what} in '{expr}'"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Number of inputs and outputs of the node
+    pub(crate) fn flow_counts(&self) -> (usize, usize) {
+        (self.inputs.len(), self.outputs.len())
+    }
+
+    /// Whether `expr` reads expressions and instances of the node
+    fn check_expr(&self, expr: &CompiledExpr) -> Result<(), String> {
+        let len = self.exprs.len();
+        if let Some(index) = expr.get_neighbours().into_iter().find(|i| *i >= len) {
+            return Err(format!("expression {index} out of {len}"));
+        }
+        let instance = |instance: usize| {
+            self.instances.get(instance).ok_or(format!(
+                "instance {instance} out of {}",
+                self.instances.len()
+            ))
+        };
+        match expr {
+            CompiledExpr::Call {
+                instance: i, args, ..
+            } => {
+                let inputs = instance(*i)?.inputs.len();
+                if args.len() != inputs {
+                    return Err(format!("{} arguments for {inputs} inputs", args.len()));
+                }
+            }
+            CompiledExpr::CallOutput {
+                call,
+                instance: i,
+                index,
+            } => {
+                let outputs = instance(*i)?.outputs.len();
+                if *index >= outputs {
+                    return Err(format!("output {index} out of {outputs}"));
+                }
+                if !matches!(self.exprs[*call], CompiledExpr::Call { instance, .. } if instance == *i)
+                {
+                    return Err(format!("expression {call} is not a call of instance {i}"));
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+}
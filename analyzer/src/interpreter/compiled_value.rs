@@ -1,6 +1,100 @@
-union _CompiledValue {
-    unit: (),
-    integer: i64,
-    float: f64,
-    bool: bool,
+use crate::{ast::integer::IntKind, ast::literal::Value, parser::var_type::InnerVarType};
+use serde_derive::{Deserialize, Serialize};
+
+/// Untyped scalar of a register, its type is known by the instructions
+/// reading it: integers and characters are stored as `i64`, floats by
+/// their bits and booleans as `0` or `1`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CompiledValue(u64);
+
+impl CompiledValue {
+    pub fn from_int(i: i64) -> Self {
+        Self(i as u64)
+    }
+    pub fn from_float(f: f64) -> Self {
+        Self(f.to_bits())
+    }
+    pub fn from_bool(b: bool) -> Self {
+        Self(b as u64)
+    }
+    pub fn int(self) -> i64 {
+        self.0 as i64
+    }
+    pub fn float(self) -> f64 {
+        f64::from_bits(self.0)
+    }
+    pub fn bool(self) -> bool {
+        self.0 != 0
+    }
+}
+
+/// Type of a register, given by the type checker at compile time
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Scalar {
+    Unit,
+    Bool,
+    Int,
+    Sized(IntKind),
+    Float,
+    Char,
+}
+
+impl Scalar {
+    /// `None` for the types that do not fit in a register
+    pub fn from_type(t: &InnerVarType) -> Option<Self> {
+        match t {
+            InnerVarType::Unit => Some(Scalar::Unit),
+            InnerVarType::Bool => Some(Scalar::Bool),
+            InnerVarType::Int => Some(Scalar::Int),
+            InnerVarType::SizedInt(kind) => Some(Scalar::Sized(*kind)),
+            InnerVarType::Float => Some(Scalar::Float),
+            InnerVarType::Char => Some(Scalar::Char),
+            InnerVarType::String | InnerVarType::Tuple(_) | InnerVarType::Array { .. } => None,
+        }
+    }
+    /// Kind of the integer arithmetic, `int` is a 64 bits integer
+    pub fn kind(self) -> IntKind {
+        match self {
+            Scalar::Sized(kind) => kind,
+            _ => IntKind::Int64,
+        }
+    }
+    /// Whether `value` is of this type, only such values are put in a
+    /// register of this type. An `int` is taken by the sized integers, like
+    /// an integer literal by the checker.
+    pub fn fits(self, value: &Value) -> bool {
+        match (self, value) {
+            (Scalar::Sized(kind), Value::SizedInt(value_kind, _)) => kind == *value_kind,
+            (Scalar::Sized(_), Value::Int(_)) => true,
+            (Scalar::Unit, Value::Unit)
+            | (Scalar::Bool, Value::Bool(_))
+            | (Scalar::Int, Value::Int(_))
+            | (Scalar::Float, Value::Float(_))
+            | (Scalar::Char, Value::Char(_)) => true,
+            _ => false,
+        }
+    }
+    pub fn to_register(value: &Value) -> Option<CompiledValue> {
+        match value {
+            Value::Unit => Some(CompiledValue::default()),
+            Value::Bool(b) => Some(CompiledValue::from_bool(*b)),
+            Value::Int(i) | Value::SizedInt(_, i) => Some(CompiledValue::from_int(*i)),
+            Value::Float(f) => Some(CompiledValue::from_float(*f)),
+            Value::Char(c) => Some(CompiledValue::from_int(*c as i64)),
+            Value::String(_) | Value::Tuple(_) | Value::Array(_) => None,
+        }
+    }
+    pub fn to_value(self, register: CompiledValue) -> Value {
+        match self {
+            Scalar::Unit => Value::Unit,
+            Scalar::Bool => Value::Bool(register.bool()),
+            Scalar::Int => Value::Int(register.int()),
+            Scalar::Sized(kind) => Value::SizedInt(kind, register.int()),
+            Scalar::Float => Value::Float(register.float()),
+            // A saved program may type another integer as a character
+            Scalar::Char => Value::Char(
+                char::from_u32(register.int() as u32).unwrap_or(char::REPLACEMENT_CHARACTER),
+            ),
+        }
+    }
 }
@@ -1,17 +1,42 @@
 use crate::{
     ast::{
-        ast::Ast, binop::BinOp, expression::Expr, literal::Value, node::Node, unary_op::UnaryOp,
+        ast::Ast,
+        ast_types::AstTypes,
+        binop::BinOp,
+        builtin::{Builtin, CONDACT, RESTART},
+        expression::Expr,
+        literal::Value,
+        node::Node,
+        unary_op::UnaryOp,
     },
+    checker::infer_types::InferLen,
     interpreter::{
-        compiled_expr::CompiledExpr, compiled_node::CompiledNode, expr_index::ExprIndex,
+        compiled_expr::CompiledExpr,
+        compiled_node::{CompiledCode, CompiledNode},
+        compiled_value::Scalar,
+        expr_index::ExprIndex,
+        monitor::Monitor,
+        register_node::RegisterNode,
+        runtime::Runtime,
+    },
+    parser::{
+        span::Span,
+        var_type::{InnerVarType, VarType},
     },
-    parser::span::Span,
 };
 use colored::Colorize;
-use std::collections::HashMap;
+use std::{collections::HashMap, rc::Rc};
 
 pub struct Compiler {
-    pub ast: CompiledNode,
+    pub ast: CompiledCode,
+    /// Inline the called nodes in their caller instead of compiling them
+    /// into instances with their own memory
+    pub inline: bool,
+    /// Code of the nodes called so far, shared by their instances
+    compiled: HashMap<String, Rc<CompiledCode>>,
+    /// Types of the checked program, the expressions take the type of the
+    /// source they are compiled from
+    types: AstTypes,
 }
 
 impl Default for Compiler {
@@ -23,22 +48,41 @@ impl Default for Compiler {
 impl Compiler {
     pub fn new() -> Self {
         Self {
-            ast: CompiledNode::new(),
+            ast: CompiledCode::new(),
+            inline: false,
+            compiled: HashMap::new(),
+            types: AstTypes::new(),
         }
     }
-    pub fn schedule(&self) -> CompiledNode {
+    pub fn schedule(&self) -> CompiledCode {
         self.ast.schedule()
     }
 }
 
 impl Ast {
     pub fn compile(&self, node_name: Span) -> CompiledNode {
-        let mut compiler = Compiler::new();
+        self.compile_with(Compiler::new(), node_name)
+    }
+    /// [Ast::compile] with the called nodes inlined
+    pub fn compile_inlined(&self, node_name: Span) -> CompiledNode {
+        let compiler = Compiler {
+            inline: true,
+            ..Compiler::new()
+        };
+        self.compile_with(compiler, node_name)
+    }
+    fn compile_with(&self, mut compiler: Compiler, node_name: Span) -> CompiledNode {
         compiler.compile_ast(self, node_name);
-        eprintln!("{} >>\n{}\n", "RAW COMPILE :".blue(), compiler.ast);
-        let result = compiler.schedule();
-        eprintln!("{} >>\n{}\n", "COMPILE SCHEDULED :".green(), result);
-        result
+        CompiledNode::new(Rc::new(compiler.schedule()))
+    }
+    /// [Ast::compile] run on registers when the flows of the node are scalars
+    pub fn compile_runtime(&self, node_name: Span) -> Runtime {
+        Runtime::new(self.compile(node_name).code().clone())
+    }
+    /// [Ast::compile] on registers, `None` when a flow of the node is not a
+    /// scalar or the program is not checked
+    pub fn compile_registers(&self, node_name: Span) -> Option<RegisterNode> {
+        self.compile(node_name).code().registers()
     }
 }
 
@@ -57,11 +101,276 @@ impl Compiler {
         iexpr
             .into_iter()
             .map(|ie| {
+                let pre = self
+                    .ast
+                    .push_expr(CompiledExpr::Pre { src: ie }, format!("set {expr}"));
+                self.ast.type_expr(pre, self.ast.scalar(ie));
+                pre
+            })
+            .collect()
+    }
+    /// `init -> pre src`, the source is given later with
+    /// [CompiledCode::replace_expr] on the returned `Pre` when it is `None`
+    fn compile_init_pre(
+        &mut self,
+        init: bool,
+        src: Option<ExprIndex>,
+        info: &str,
+    ) -> (ExprIndex, ExprIndex) {
+        let init = self
+            .ast
+            .push_expr(CompiledExpr::Lit(Value::Bool(init)), info.to_string());
+        let pre = match src {
+            Some(src) => self
+                .ast
+                .push_expr(CompiledExpr::Pre { src }, format!("set {info}")),
+            // Not memoised, it is not the same as another `Pre` until its source is known
+            None => self
+                .ast
+                .push_expr_core(CompiledExpr::Pre { src: init }, format!("set {info}")),
+        };
+        let arrow = self.ast.push_expr(
+            CompiledExpr::BinOp {
+                lhs: init,
+                op: BinOp::Arrow,
+                rhs: pre,
+            },
+            info.to_string(),
+        );
+        for index in [init, pre, arrow] {
+            self.ast.type_expr(index, Some(Scalar::Bool));
+        }
+        (arrow, pre)
+    }
+    /// Lowering of the past temporal operators on booleans, with
+    /// `always` for `historically` and `triggered`:
+    /// - `once b` is `o = b or (false -> pre o)`
+    /// - `historically b` is `h = b and (true -> pre h)`
+    /// - `a since b` is `s = b or (a and (false -> pre s))`
+    /// - `a triggered b` is `t = b and (a or (true -> pre t))`
+    fn compile_past(
+        &mut self,
+        always: bool,
+        lhs: Option<ExprIndex>,
+        rhs: ExprIndex,
+        info: &str,
+    ) -> ExprIndex {
+        let (outer, inner) = if always {
+            (BinOp::And, BinOp::Or)
+        } else {
+            (BinOp::Or, BinOp::And)
+        };
+        let (previous, pre) = self.compile_init_pre(always, None, info);
+        let previous = match lhs {
+            Some(lhs) => self.ast.push_expr(
+                CompiledExpr::BinOp {
+                    lhs,
+                    op: inner,
+                    rhs: previous,
+                },
+                info.to_string(),
+            ),
+            None => previous,
+        };
+        let res = self.ast.push_expr(
+            CompiledExpr::BinOp {
+                lhs: rhs,
+                op: outer,
+                rhs: previous,
+            },
+            info.to_string(),
+        );
+        self.ast.type_expr(previous, Some(Scalar::Bool));
+        self.ast.type_expr(res, Some(Scalar::Bool));
+        self.ast.replace_expr(CompiledExpr::Pre { src: res }, pre);
+        res
+    }
+    /// Instance of the node `name` called by `node` on the arguments `iargs`,
+    /// stepped only when `cond` holds if it is given and the call is not inlined
+    fn compile_call(
+        &mut self,
+        ast: &Ast,
+        node: &Node,
+        name: &Span,
+        iargs: &[ExprIndex],
+        cond: Option<ExprIndex>,
+        reset: Option<ExprIndex>,
+    ) -> Vec<ExprIndex> {
+        // Thanks to type checking
+        let callee = ast.nodes.iter().find(|node| &node.name == name).unwrap();
+        if self.inline {
+            let (inputs_node, outputs_node) =
+                self.compile_node(ast, callee, Some(node.name.fragment()));
+            for (input_node, arg) in inputs_node.iter().zip(iargs.iter()) {
                 self.ast
-                    .push_expr(CompiledExpr::Pre { src: ie }, format!("set {expr}"))
+                    .replace_expr(CompiledExpr::Variable(*arg), *input_node);
+            }
+            return outputs_node;
+        }
+
+        let code = self.compile_callee(ast, callee);
+        let outputs = code.outputs().to_vec();
+        let instance = self.ast.push_instance(code.clone());
+        // Not memoised, every call owns its instance
+        let call = self.ast.push_expr_core(
+            CompiledExpr::Call {
+                instance,
+                args: iargs.to_vec(),
+                cond,
+                reset,
+            },
+            format!("{} : {name}", "CALL".yellow()),
+        );
+        self.ast.type_expr(call, Some(Scalar::Unit));
+        outputs
+            .into_iter()
+            .enumerate()
+            .map(|(index, output)| {
+                let res = self.ast.push_expr_core(
+                    CompiledExpr::CallOutput {
+                        call,
+                        instance,
+                        index,
+                    },
+                    format!("{name}.{index}"),
+                );
+                self.ast.type_expr(res, code.scalar(output));
+                res
             })
             .collect()
     }
+    /// Code of `callee` shared by all its instances, compiled at its first call
+    fn compile_callee(&mut self, ast: &Ast, callee: &Node) -> Rc<CompiledCode> {
+        let name = callee.name.fragment();
+        if let Some(code) = self.compiled.get(&name) {
+            return code.clone();
+        }
+        let mut compiler = Compiler {
+            compiled: std::mem::take(&mut self.compiled),
+            types: std::mem::take(&mut self.types),
+            ..Compiler::new()
+        };
+        // The assumptions of the callee are blamed on its caller at runtime
+        let (inputs, outputs) = compiler.compile_node(ast, callee, None);
+        compiler.ast.set_name(name.clone());
+        compiler.ast.set_inputs(inputs);
+        compiler.ast.set_outputs(outputs);
+        compiler.ast.set_overflow(ast.overflow);
+        let code = Rc::new(compiler.schedule());
+        self.compiled = compiler.compiled;
+        self.types = compiler.types;
+        self.compiled.insert(name, code.clone());
+        code
+    }
+    /// Instance of the node `name` activated by `cond`, with:
+    /// - `never = true -> pre (never and not cond)`, true until `cond` holds
+    /// - the memories of the instance frozen while `cond` does not hold, by
+    ///   not stepping it or by [CompiledCode::activate] when it is inlined
+    /// - `out = if cond then f_out else if never then default else pre out`
+    #[allow(clippy::too_many_arguments)]
+    fn compile_condact(
+        &mut self,
+        ast: &Ast,
+        node: &Node,
+        name: &Span,
+        iargs: &[ExprIndex],
+        cond: ExprIndex,
+        defaults: &[ExprIndex],
+        info: &str,
+    ) -> Vec<ExprIndex> {
+        let (never, pre_never) = self.compile_init_pre(true, None, info);
+        let not_cond = self.ast.push_expr(
+            CompiledExpr::UnaryOp {
+                op: UnaryOp::Not,
+                rhs: cond,
+            },
+            info.to_string(),
+        );
+        let src = self.ast.push_expr(
+            CompiledExpr::BinOp {
+                lhs: never,
+                op: BinOp::And,
+                rhs: not_cond,
+            },
+            info.to_string(),
+        );
+        self.ast.type_expr(not_cond, Some(Scalar::Bool));
+        self.ast.type_expr(src, Some(Scalar::Bool));
+        self.ast.replace_expr(CompiledExpr::Pre { src }, pre_never);
+
+        let outputs = if self.inline {
+            // The instance shares no expression with its caller, its memories
+            // are rewritten by [CompiledCode::activate]
+            let start = self.ast.back_index();
+            let monitors_start = self.ast.monitors_len();
+            let memo_start = self.ast.set_memo_start(start);
+            let outputs = self.compile_call(ast, node, name, iargs, None, None);
+            self.ast.activate(start, monitors_start, cond, never);
+            self.ast.set_memo_start(memo_start);
+            outputs
+        } else {
+            // The instance keeps its memories while it is not stepped
+            self.compile_call(ast, node, name, iargs, Some(cond), None)
+        };
+
+        outputs
+            .into_iter()
+            .zip(defaults)
+            .map(|(output, default)| {
+                let pre = self
+                    .ast
+                    .push_expr_core(CompiledExpr::Pre { src: output }, format!("set {info}"));
+                let held = self.ast.push_expr(
+                    CompiledExpr::If {
+                        cond: never,
+                        yes: *default,
+                        no: pre,
+                    },
+                    info.to_string(),
+                );
+                let res = self.ast.push_expr(
+                    CompiledExpr::If {
+                        cond,
+                        yes: output,
+                        no: held,
+                    },
+                    info.to_string(),
+                );
+                for index in [pre, held, res] {
+                    self.ast.type_expr(index, self.ast.scalar(output));
+                }
+                self.ast.replace_expr(CompiledExpr::Pre { src: res }, pre);
+                res
+            })
+            .collect()
+    }
+    /// Instance of the node `name` in a state of an automaton, active when
+    /// `cond` holds and restarted when `first` holds: it is not stepped or its
+    /// memories are frozen by [CompiledCode::activate] while `cond` does not
+    /// hold, and `first` replaces the `never` of [Compiler::compile_condact]
+    fn compile_restart(
+        &mut self,
+        ast: &Ast,
+        node: &Node,
+        name: &Span,
+        iargs: &[ExprIndex],
+        cond: ExprIndex,
+        first: ExprIndex,
+    ) -> Vec<ExprIndex> {
+        if !self.inline {
+            return self.compile_call(ast, node, name, iargs, Some(cond), Some(first));
+        }
+        let start = self.ast.back_index();
+        let monitors_start = self.ast.monitors_len();
+        let memo_start = self.ast.set_memo_start(start);
+        let outputs = self.compile_call(ast, node, name, iargs, None, None);
+        self.ast.activate(start, monitors_start, cond, first);
+        self.ast.set_memo_start(memo_start);
+        outputs
+    }
+    /// The expressions pushed for `expr` have its source, the operator or the
+    /// name it is spanned by, or the source of the enclosing expression. The
+    /// expressions computing `expr` take its type given by the checker.
     fn compile_expr(
         &mut self,
         ast: &Ast,
@@ -70,6 +379,32 @@ impl Compiler {
         outputs: &[ExprIndex],
         vars: &mut HashMap<Span, Vec<ExprIndex>>,
         expr: &Expr,
+    ) -> Vec<ExprIndex> {
+        let span = match expr {
+            Expr::BinOp { span_op, .. } | Expr::UnaryOp { span_op, .. } => Some(span_op),
+            Expr::FCall { name, .. } | Expr::Variable(name) => Some(name),
+            _ => None,
+        };
+        let previous = span.map(|span| self.ast.set_span(Some(span.clone())));
+        let res = self.compile_expr_core(ast, node, inputs, outputs, vars, expr);
+        if let Some(previous) = previous {
+            self.ast.set_span(previous);
+        }
+        if let Some(t) = ast.type_of(&mut self.types, node, expr) {
+            for (index, scalar) in res.iter().zip(scalars(&t.inner)) {
+                self.ast.type_expr(*index, scalar);
+            }
+        }
+        res
+    }
+    fn compile_expr_core(
+        &mut self,
+        ast: &Ast,
+        node: &Node,
+        inputs: &[ExprIndex],
+        outputs: &[ExprIndex],
+        vars: &mut HashMap<Span, Vec<ExprIndex>>,
+        expr: &Expr,
     ) -> Vec<ExprIndex> {
         let info = format!("{expr}");
         match expr {
@@ -95,6 +430,19 @@ impl Compiler {
                     })
                     .collect()
             }
+            Expr::BinOp {
+                lhs,
+                op: op @ (BinOp::Since | BinOp::Triggered),
+                span_op: _,
+                rhs,
+            } => {
+                let ilhs = self.compile_expr(ast, node, inputs, outputs, vars, lhs);
+                let irhs = self.compile_expr(ast, node, inputs, outputs, vars, rhs);
+                ilhs.into_iter()
+                    .zip(irhs)
+                    .map(|(il, ir)| self.compile_past(op == &BinOp::Triggered, Some(il), ir, &info))
+                    .collect()
+            }
             Expr::BinOp {
                 lhs,
                 op,
@@ -122,6 +470,26 @@ impl Compiler {
                 span_op: _,
                 rhs,
             } => self.compile_pre(ast, node, inputs, outputs, vars, rhs),
+            Expr::UnaryOp {
+                op: UnaryOp::Yesterday,
+                span_op: _,
+                rhs,
+            } => {
+                let irhs = self.compile_expr(ast, node, inputs, outputs, vars, rhs);
+                irhs.into_iter()
+                    .map(|ir| self.compile_init_pre(false, Some(ir), &info).0)
+                    .collect()
+            }
+            Expr::UnaryOp {
+                op: op @ (UnaryOp::Once | UnaryOp::Historically),
+                span_op: _,
+                rhs,
+            } => {
+                let irhs = self.compile_expr(ast, node, inputs, outputs, vars, rhs);
+                irhs.into_iter()
+                    .map(|ir| self.compile_past(op == &UnaryOp::Historically, None, ir, &info))
+                    .collect()
+            }
             Expr::UnaryOp {
                 op,
                 span_op: _,
@@ -159,6 +527,59 @@ impl Compiler {
                 }
                 res
             }
+            Expr::FCall { name, args }
+                if ast.nodes.iter().all(|node| &node.name != name)
+                    && let Some(builtin) = Builtin::from_name(&name.fragment()) =>
+            {
+                let iargs: Vec<Vec<ExprIndex>> = args
+                    .iter()
+                    .map(|e| self.compile_expr(ast, node, inputs, outputs, vars, e))
+                    .collect();
+                // Arrays are flattened, the call is applied on each element
+                (0..iargs[0].len())
+                    .map(|i| {
+                        self.ast.push_expr(
+                            CompiledExpr::Builtin {
+                                builtin,
+                                args: iargs.iter().map(|arg| arg[i]).collect(),
+                            },
+                            info.clone(),
+                        )
+                    })
+                    .collect()
+            }
+            Expr::FCall { name, args }
+                if name.fragment() == CONDACT
+                    && ast.nodes.iter().all(|node| &node.name != name) =>
+            {
+                // Thanks to type checking
+                let [cond, Expr::FCall { name, args }, defaults @ ..] = &args[..] else {
+                    unreachable!()
+                };
+                let cond = self.compile_expr(ast, node, inputs, outputs, vars, cond)[0];
+                let mut idefaults = vec![];
+                for e in defaults.iter() {
+                    idefaults.extend(self.compile_expr(ast, node, inputs, outputs, vars, e));
+                }
+                let mut iargs = vec![];
+                for e in args.iter() {
+                    iargs.extend(self.compile_expr(ast, node, inputs, outputs, vars, e));
+                }
+                self.compile_condact(ast, node, name, &iargs, cond, &idefaults, &info)
+            }
+            Expr::FCall { name, args } if name.fragment() == RESTART => {
+                // Written by the lowering of automata
+                let [cond, first, Expr::FCall { name, args }] = &args[..] else {
+                    unreachable!()
+                };
+                let cond = self.compile_expr(ast, node, inputs, outputs, vars, cond)[0];
+                let first = self.compile_expr(ast, node, inputs, outputs, vars, first)[0];
+                let mut iargs = vec![];
+                for e in args.iter() {
+                    iargs.extend(self.compile_expr(ast, node, inputs, outputs, vars, e));
+                }
+                self.compile_restart(ast, node, name, &iargs, cond, first)
+            }
             Expr::FCall { name, args } => {
                 let mut iargs = vec![];
                 // Flatten operation : todo check
@@ -167,19 +588,7 @@ impl Compiler {
                         iargs.push(index)
                     }
                 }
-                for node in ast.nodes.iter() {
-                    if &node.name == name {
-                        let (inputs_node, outputs_node) = self.compile_node(ast, node);
-
-                        for (input_node, arg) in inputs_node.iter().zip(iargs.iter()) {
-                            self.ast
-                                .replace_expr(CompiledExpr::Variable(*arg), *input_node);
-                        }
-                        return outputs_node;
-                    }
-                }
-                // Thanks to type checking
-                unreachable!()
+                self.compile_call(ast, node, name, &iargs, None, None)
             }
             Expr::Variable(var) => {
                 if let Some(i) = node.outputs.iter().position(|(x, _)| x == var) {
@@ -223,13 +632,43 @@ impl Compiler {
         vars: &mut HashMap<Span, Vec<ExprIndex>>,
         var: &Span,
     ) -> Vec<ExprIndex> {
-        if let Some(index) = vars.get(var) {
-            return index.clone();
+        let declared = node
+            .outputs
+            .iter()
+            .chain(node.vars.iter())
+            .find(|(name, _)| name == var);
+        match vars.get(var) {
+            // The variable refers to itself through a `pre`, its value is
+            // given later with [CompiledCode::replace_expr] on placeholders
+            Some(index) if index.is_empty() => {
+                let width = declared.map_or(1, |(_, t)| width(&t.inner));
+                let placeholders: Vec<ExprIndex> = (0..width)
+                    .map(|_| {
+                        self.ast
+                            .push_expr_core(CompiledExpr::Output, var.fragment())
+                    })
+                    .collect();
+                vars.insert(var.clone(), placeholders.clone());
+                return placeholders;
+            }
+            Some(index) => return index.clone(),
+            None => {}
         }
         for (var_name, expr) in node.let_bindings.iter() {
             if var == var_name {
+                vars.insert(var.clone(), vec![]);
                 let index = self.compile_expr(ast, node, inputs, outputs, vars, expr);
-                vars.insert(var.clone(), index.clone());
+                let index = match declared {
+                    Some((_, t)) => self.convert_to_declared(index, t, format!("{expr}")),
+                    None => index,
+                };
+                if let Some(placeholders) = vars.insert(var.clone(), index.clone()) {
+                    for (placeholder, index) in placeholders.into_iter().zip(index.iter()) {
+                        self.ast
+                            .replace_expr(CompiledExpr::Variable(*index), placeholder);
+                        self.ast.type_expr(placeholder, self.ast.scalar(*index));
+                    }
+                }
                 return index;
             }
         }
@@ -237,20 +676,63 @@ impl Compiler {
         unreachable!()
     }
 
-    fn compile_node(&mut self, ast: &Ast, node: &Node) -> (Vec<ExprIndex>, Vec<ExprIndex>) {
+    /// Integer literals checked against a sized integer type are still `int`
+    /// values, they get the declared type of the variable they flow into.
+    fn convert_to_declared(
+        &mut self,
+        index: Vec<ExprIndex>,
+        t: &VarType,
+        info: String,
+    ) -> Vec<ExprIndex> {
+        let kind = match &t.inner {
+            InnerVarType::SizedInt(kind) => *kind,
+            InnerVarType::Array { t, .. } => match **t {
+                InnerVarType::SizedInt(kind) => kind,
+                _ => return index,
+            },
+            _ => return index,
+        };
+        index
+            .into_iter()
+            .map(|i| {
+                let sized = self.ast.push_expr(
+                    CompiledExpr::UnaryOp {
+                        op: UnaryOp::ToSized(kind),
+                        rhs: i,
+                    },
+                    info.clone(),
+                );
+                self.ast.type_expr(sized, Some(Scalar::Sized(kind)));
+                sized
+            })
+            .collect()
+    }
+
+    /// Compile a node instance, `caller` is the node calling it if any
+    fn compile_node(
+        &mut self,
+        ast: &Ast,
+        node: &Node,
+        caller: Option<String>,
+    ) -> (Vec<ExprIndex>, Vec<ExprIndex>) {
         let mut inputs_index = vec![];
-        for (input, _) in node.inputs.iter() {
-            inputs_index.push(
-                self.ast
-                    .push_expr(CompiledExpr::Input, format!("{} : {}", "IN".green(), input)),
-            );
+        let mut inputs_values = vec![];
+        for (input, t) in node.inputs.iter() {
+            let index = self
+                .ast
+                .push_expr(CompiledExpr::Input, format!("{} : {}", "IN".green(), input));
+            self.ast.type_expr(index, Scalar::from_type(&t.inner));
+            inputs_index.push(index);
+            inputs_values.extend(self.convert_to_declared(vec![index], t, input.fragment()));
         }
         let mut outputs_index = vec![];
-        for (output, _) in node.outputs.iter() {
-            outputs_index.push(self.ast.push_expr(
+        for (output, t) in node.outputs.iter() {
+            let index = self.ast.push_expr(
                 CompiledExpr::Output,
                 format!("{} : {}", "OUT".blue(), output),
-            ));
+            );
+            self.ast.type_expr(index, Scalar::from_type(&t.inner));
+            outputs_index.push(index);
         }
 
         let mut vars = HashMap::new();
@@ -258,7 +740,7 @@ impl Compiler {
             let iexpr = self.compile_var(
                 ast,
                 node,
-                &inputs_index,
+                &inputs_values,
                 &outputs_index,
                 &mut vars,
                 var_name,
@@ -268,17 +750,166 @@ impl Compiler {
             self.ast
                 .replace_expr(CompiledExpr::Variable(iexpr[0]), outputs_index[i]);
         }
+        let mut monitors = vec![];
+        for (_, expr) in node.asserts.iter() {
+            let assertion = format!("{}: assert {expr}", node.name);
+            monitors.push((expr.clone(), Monitor::Assert(assertion)));
+        }
+        if let Some(contract) = &node.contract {
+            for (_, expr) in contract.assumes.iter() {
+                monitors.push((
+                    expr.clone(),
+                    Monitor::Assume {
+                        assumption: format!("{}: assume {expr}", node.name),
+                        caller: caller.clone(),
+                    },
+                ));
+            }
+            for (_, expr) in contract.guarantees.iter() {
+                let guarantee = format!("{}: guarantee {expr}", node.name);
+                monitors.push((expr.clone(), Monitor::Guarantee(guarantee)));
+            }
+            for mode in contract.modes.iter() {
+                // `ensure e` is a guarantee when the mode is active: `not (r1 and r2 ...) or e`
+                let active =
+                    mode.requires
+                        .iter()
+                        .map(|(_, expr)| expr.clone())
+                        .reduce(|lhs, rhs| Expr::BinOp {
+                            lhs: Box::new(lhs),
+                            op: BinOp::And,
+                            span_op: mode.span_mode.clone(),
+                            rhs: Box::new(rhs),
+                        });
+                for (_, expr) in mode.ensures.iter() {
+                    let guarantee = format!("{}: mode {}: ensure {expr}", node.name, mode.name);
+                    let expr = match &active {
+                        Some(active) => Expr::BinOp {
+                            lhs: Box::new(Expr::UnaryOp {
+                                op: UnaryOp::Not,
+                                span_op: mode.span_mode.clone(),
+                                rhs: Box::new(active.clone()),
+                            }),
+                            op: BinOp::Or,
+                            span_op: mode.span_mode.clone(),
+                            rhs: Box::new(expr.clone()),
+                        },
+                        None => expr.clone(),
+                    };
+                    monitors.push((expr, Monitor::Guarantee(guarantee)));
+                }
+            }
+        }
+        for (expr, monitor) in monitors {
+            for index in
+                self.compile_expr(ast, node, &inputs_values, &outputs_index, &mut vars, &expr)
+            {
+                self.ast.push_monitor(index, monitor.clone());
+            }
+        }
+        // The flows of an inlined node are not named in its caller
+        if caller.is_none() || !self.inline {
+            // The local variables read by no output are traced too
+            for (var, _) in node.vars.iter() {
+                self.compile_var(ast, node, &inputs_values, &outputs_index, &mut vars, var);
+            }
+            self.name_flows(node, &inputs_index, &outputs_index, &vars);
+        }
         (inputs_index, outputs_index)
     }
+    /// Name the inputs, outputs and local variables of the compiled node, a
+    /// flow made of several expressions is named by `name.i`
+    fn name_flows(
+        &mut self,
+        node: &Node,
+        inputs: &[ExprIndex],
+        outputs: &[ExprIndex],
+        vars: &HashMap<Span, Vec<ExprIndex>>,
+    ) {
+        let inputs = node
+            .inputs
+            .iter()
+            .zip(inputs)
+            .map(|((name, _), index)| (name, vec![*index]));
+        let outputs = node
+            .outputs
+            .iter()
+            .zip(outputs)
+            .map(|((name, _), index)| (name, vec![*index]));
+        let locals = node
+            .vars
+            .iter()
+            .filter_map(|(name, _)| Some((name, vars.get(name)?.clone())));
+        for (name, indexes) in inputs.chain(outputs).chain(locals) {
+            if let [index] = indexes[..] {
+                self.ast.name_flow(index, name.fragment());
+                continue;
+            }
+            for (i, index) in indexes.into_iter().enumerate() {
+                self.ast.name_flow(index, format!("{name}.{i}"));
+            }
+        }
+    }
     pub fn compile_ast(&mut self, ast: &Ast, node_name: Span) {
+        // Automata are compiled through their lowering into equations
+        let lowered;
+        let ast = if ast.nodes.iter().any(|node| !node.automata.is_empty()) {
+            lowered = Ast {
+                nodes: ast
+                    .nodes
+                    .iter()
+                    .map(|node| node.lower_automata(ast))
+                    .collect(),
+                ..ast.clone()
+            };
+            &lowered
+        } else {
+            ast
+        };
+        // The variables of the lowering take their declared type
+        self.types = ast.types.clone();
+        for node in ast.nodes.iter() {
+            for (var, t) in node.vars.iter() {
+                if !self.types.contains_key_local_type(&node.name, var) {
+                    self.types
+                        .insert_local_type(&node.name, var.clone(), Some(t.clone()));
+                }
+            }
+        }
         for node in ast.nodes.iter() {
             if node_name == node.name {
-                let (inputs, outputs) = self.compile_node(ast, node);
+                let (inputs, outputs) = self.compile_node(ast, node, None);
+                self.ast.set_name(node.name.fragment());
                 self.ast.set_inputs(inputs);
                 self.ast.set_outputs(outputs);
-                self.ast.set_values_to_none();
+                self.ast.set_overflow(ast.overflow);
                 return;
             }
         }
     }
 }
+
+/// Types of the scalar values of a flow of type `t`, `None` for the ones
+/// that do not fit in a register
+fn scalars(t: &InnerVarType) -> Vec<Option<Scalar>> {
+    match t {
+        InnerVarType::Tuple(types) => types.iter().flat_map(scalars).collect(),
+        InnerVarType::Array {
+            t,
+            len: InferLen::Known(len),
+        } => scalars(t).repeat(*len),
+        t => vec![Scalar::from_type(t)],
+    }
+}
+
+/// Number of scalar values of a variable of type `t`
+fn width(t: &InnerVarType) -> usize {
+    match t {
+        InnerVarType::Tuple(types) => types.iter().map(width).sum(),
+        InnerVarType::Array {
+            t,
+            len: InferLen::Known(len),
+        } => *len * width(t),
+        _ => 1,
+    }
+}
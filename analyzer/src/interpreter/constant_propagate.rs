@@ -1,6 +1,14 @@
 use crate::{
-    ast::{ast::Ast, binop::BinOp, expression::Expr, literal::Value, node::Node},
+    ast::{
+        ast::Ast,
+        binop::BinOp,
+        builtin::{Builtin, CONDACT},
+        expression::Expr,
+        literal::Value,
+        node::Node,
+    },
     checker::function_type::{FunctionCallType, FunctionType},
+    interpreter::{runtime::Runtime, runtime_error::RuntimeError},
     parser::span::{PositionEnd, Span},
 };
 use lsp_types::{InlayHint, InlayHintLabel, Position};
@@ -11,6 +19,9 @@ pub struct PropagaterConst {
     ast: Ast,
     seen_equations: HashMap<Span, Option<Value>>,
     hints: Vec<InlayHint>,
+    /// Nodes called on constant inputs, compiled once and cloned before
+    /// their first step at every call
+    compiled: HashMap<String, Runtime>,
 }
 
 impl PropagaterConst {
@@ -19,6 +30,7 @@ impl PropagaterConst {
             ast: Ast::new(),
             seen_equations: HashMap::new(),
             hints: vec![],
+            compiled: HashMap::new(),
         }
     }
 }
@@ -50,6 +62,11 @@ impl Ast {
         propagater_const.const_ast(self);
         (propagater_const.ast, propagater_const.hints)
     }
+    /// Value of `expr` inside `node` if it is constant.
+    pub(crate) fn const_value(&self, node: &Node, expr: &Expr) -> Option<Value> {
+        let mut propagater_const = PropagaterConst::new();
+        propagater_const.const_expr(self, node, expr).get_value()
+    }
 }
 
 impl PropagaterConst {
@@ -90,7 +107,7 @@ impl PropagaterConst {
                     }
                 };
 
-                match op.apply(&lv, &rv, None) {
+                match op.apply(&lv, &rv, None, ast.overflow) {
                     Some(v) => return Expr::Lit(v),
                     None => return fallback(lhs, rhs),
                 }
@@ -107,7 +124,7 @@ impl PropagaterConst {
                     Some(v) => v,
                     None => return fallback(rhs),
                 };
-                match op.apply(&rv, None) {
+                match op.apply(&rv, None, ast.overflow) {
                     Some(v) => return Expr::Lit(v),
                     None => return fallback(rhs),
                 }
@@ -124,6 +141,12 @@ impl PropagaterConst {
                     .map(|e| self.const_expr(ast, node, e))
                     .collect(),
             ),
+            Expr::FCall { name, args }
+                if name.fragment() == CONDACT
+                    && ast.nodes.iter().all(|node| &node.name != name) =>
+            {
+                self.const_condact(ast, node, name, args)
+            }
             Expr::FCall { name, args } => {
                 let mut args_are_const = true;
                 let mut const_args = vec![];
@@ -151,60 +174,26 @@ impl PropagaterConst {
                     };
                 }
 
-                let func_type = ast
-                    .nodes
-                    .iter()
-                    .fold(None, |acc, node| match acc {
-                        Some(_) => acc,
-                        None => {
-                            if &node.name == name {
-                                let (ftype, _) = FunctionType::get_function_type(node);
-                                Some(ftype)
-                            } else {
-                                None
-                            }
-                        }
-                    })
-                    .unwrap(); // Ok because of type checking
-
-                // unwrap ok because of type checking
-                // This crash (sometimes) ! (because argument of some functions are empty)
-                // dbg!();
-                let call_type = func_type.function_call_type(&inputs).unwrap();
-
-                // Compile & Interpret the function because arguments are constant
-                let mut compile_ast = ast.compile(name.clone());
-
-                match call_type {
-                    FunctionCallType::Simple => {
-                        Expr::Lit(Value::tuple_from_vec(compile_ast.step(inputs)))
-                    }
-                    FunctionCallType::Array => {
-                        // OK unwrap because every arguments is an array because of typechecking
-                        let array_inputs = Value::unwrap_array(inputs).unwrap();
-                        // We know this is ok because no function has 0 arguments (always at least unit)
-                        let number_steps = array_inputs[0].len();
-
-                        let mut array_outputs = vec![];
-
-                        for instant in 0..number_steps {
-                            let mut input = vec![];
-                            for x in array_inputs.iter() {
-                                input.push(x[instant].clone())
-                            }
-
-                            for (i, res) in compile_ast.step(input).into_iter().enumerate() {
-                                if instant == 0 {
-                                    array_outputs.push(vec![]);
-                                }
-                                array_outputs[i].push(res);
-                            }
-                        }
-                        Expr::Lit(Value::tuple_from_vec(
-                            array_outputs.into_iter().map(Value::Array).collect(),
-                        ))
-                    }
+                // Nodes hide the built-in functions of the same name
+                if ast.nodes.iter().all(|node| &node.name != name)
+                    && let Some(builtin) = Builtin::from_name(&name.fragment())
+                {
+                    return match builtin.apply(&inputs) {
+                        Some(v) => Expr::Lit(v),
+                        None => Expr::FCall {
+                            name: name.clone(),
+                            args: const_args,
+                        },
+                    };
                 }
+
+                // Ok because of type checking
+                let callee = ast.nodes.iter().find(|node| &node.name == name).unwrap();
+                let call = Expr::FCall {
+                    name: name.clone(),
+                    args: const_args,
+                };
+                self.step_node(ast, callee, inputs, name, call)
             }
             Expr::Variable(var) => match self.const_var(ast, node, var) {
                 Some(val) => Expr::Lit(val),
@@ -232,6 +221,125 @@ impl PropagaterConst {
         }
     }
 
+    /// Outputs of `callee` on constant inputs, a call on arrays is stepped
+    /// as a time series. Calls violating an assertion or a contract are
+    /// reported at `name` and `call` is kept.
+    fn step_node(
+        &mut self,
+        ast: &Ast,
+        callee: &Node,
+        inputs: Vec<Value>,
+        name: &Span,
+        call: Expr,
+    ) -> Expr {
+        let (func_type, _) = FunctionType::get_function_type(callee);
+
+        // unwrap ok because of type checking
+        // This crash (sometimes) ! (because argument of some functions are empty)
+        let call_type = func_type.function_call_type(&inputs).unwrap();
+
+        // Compile & Interpret the function because arguments are constant,
+        // on registers when its flows are scalars
+        let mut runtime = self
+            .compiled
+            .entry(callee.name.fragment())
+            .or_insert_with(|| ast.compile_runtime(callee.name.clone()))
+            .clone();
+
+        let violated = |propagater: &mut Self, error: RuntimeError| {
+            propagater.push_hint(name.position_end(), format!(">> {error}"));
+            call.clone()
+        };
+        match call_type {
+            FunctionCallType::Simple => match runtime.step(inputs) {
+                Ok(outputs) => Expr::Lit(Value::tuple_from_vec(outputs)),
+                Err(error) => violated(self, error),
+            },
+            FunctionCallType::Array => {
+                // OK unwrap because every arguments is an array because of typechecking
+                let array_inputs = Value::unwrap_array(inputs).unwrap();
+                // We know this is ok because no function has 0 arguments (always at least unit)
+                let number_steps = array_inputs[0].len();
+
+                let mut array_outputs = vec![];
+
+                for instant in 0..number_steps {
+                    let mut input = vec![];
+                    for x in array_inputs.iter() {
+                        input.push(x[instant].clone())
+                    }
+
+                    let outputs = match runtime.step(input) {
+                        Ok(outputs) => outputs,
+                        Err(error) => return violated(self, error),
+                    };
+                    for (i, res) in outputs.into_iter().enumerate() {
+                        if instant == 0 {
+                            array_outputs.push(vec![]);
+                        }
+                        array_outputs[i].push(res);
+                    }
+                }
+                Expr::Lit(Value::tuple_from_vec(
+                    array_outputs.into_iter().map(Value::Array).collect(),
+                ))
+            }
+        }
+    }
+
+    /// `condact(c, f(args), defaults...)` on constants is the call of a node
+    /// taking `c` and the inputs of `f`, the call of `f` is not folded alone.
+    fn const_condact(&mut self, ast: &Ast, node: &Node, name: &Span, args: &[Expr]) -> Expr {
+        // Thanks to type checking
+        let [
+            cond,
+            Expr::FCall {
+                name: callee,
+                args: callee_args,
+            },
+            defaults @ ..,
+        ] = args
+        else {
+            unreachable!()
+        };
+        let cond = self.const_expr(ast, node, cond);
+        let callee_args: Vec<Expr> = callee_args
+            .iter()
+            .map(|e| self.const_expr(ast, node, e))
+            .collect();
+        let defaults: Vec<Expr> = defaults
+            .iter()
+            .map(|e| self.const_expr(ast, node, e))
+            .collect();
+        let call = Expr::FCall {
+            name: name.clone(),
+            args: std::iter::once(cond.clone())
+                .chain(std::iter::once(Expr::FCall {
+                    name: callee.clone(),
+                    args: callee_args.clone(),
+                }))
+                .chain(defaults.iter().cloned())
+                .collect(),
+        };
+        let inputs = std::iter::once(&cond)
+            .chain(callee_args.iter())
+            .map(|e| e.get_value())
+            .collect::<Option<Vec<_>>>();
+        let (Some(inputs), true) = (inputs, defaults.iter().all(|e| e.get_value().is_some()))
+        else {
+            return call;
+        };
+        if callee_args == [Expr::Lit(Value::Unit)] {
+            return call;
+        }
+        // Ok because of type checking
+        let callee = ast.nodes.iter().find(|node| &node.name == callee).unwrap();
+        let wrapper = callee.condact_wrapper(name, defaults);
+        let mut ast = ast.clone();
+        ast.nodes.push(wrapper.clone());
+        self.step_node(&ast, &wrapper, inputs, name, call)
+    }
+
     fn const_var(&mut self, ast: &Ast, node: &Node, var: &Span) -> Option<Value> {
         if let Some(val) = self.seen_equations.get(var) {
             return val.clone();
@@ -287,6 +395,9 @@ impl PropagaterConst {
     }
 
     pub fn const_ast(&mut self, ast: &Ast) {
+        self.ast.overflow = ast.overflow;
+        self.ast.prelude = ast.prelude;
+        self.ast.prelude_len = ast.prelude_len;
         for node in ast.nodes.iter() {
             self.seen_equations = HashMap::new();
             self.const_node(ast, node);
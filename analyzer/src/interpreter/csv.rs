@@ -0,0 +1,378 @@
+use crate::{
+    ast::literal::Value,
+    checker::{function_type::FunctionType, infer_types::InferLen},
+    parser::{literal::literal, span::LSpan, var_type::InnerVarType},
+};
+use std::io::{self, Write};
+
+/// Error in a CSV file, `row` and `column` count from 1 and the header is
+/// the first row
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvError {
+    pub row: usize,
+    /// `None` when the error is about the whole row
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl std::fmt::Display for CsvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.column {
+            Some(column) => write!(f, "row {}, column {column}: {}", self.row, self.message),
+            None => write!(f, "row {}: {}", self.row, self.message),
+        }
+    }
+}
+
+impl std::error::Error for CsvError {}
+
+impl FunctionType {
+    /// Inputs of the node at every instant, read from a CSV file with a
+    /// header naming the inputs in any order and one row per instant
+    pub fn read_stimulus(&self, csv: &str) -> Result<Vec<Vec<Value>>, CsvError> {
+        let error = |row, column, message| CsvError {
+            row,
+            column,
+            message,
+        };
+        let mut lines = csv
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line))
+            .filter(|(_, line)| !line.trim().is_empty());
+        let Some((row, header)) = lines.next() else {
+            return Err(error(1, None, "missing header".to_string()));
+        };
+        let header =
+            split(header).map_err(|(column, message)| error(row, Some(column), message))?;
+        // Position of the column of every input
+        let mut columns = vec![None; self.inputs.len()];
+        for (column, name) in header.iter().enumerate() {
+            let name = name.trim();
+            match self
+                .inputs
+                .keys()
+                .position(|input| input.fragment() == name)
+            {
+                Some(input) if columns[input].is_some() => {
+                    let message = format!("input '{name}' has several columns");
+                    return Err(error(row, Some(column + 1), message));
+                }
+                Some(input) => columns[input] = Some(column),
+                None => {
+                    let message = format!("unknown input '{name}'");
+                    return Err(error(row, Some(column + 1), message));
+                }
+            }
+        }
+        if let Some(missing) = columns.iter().position(Option::is_none) {
+            let (name, _) = self.inputs.get_index(missing).unwrap();
+            return Err(error(
+                row,
+                None,
+                format!("missing column for input '{name}'"),
+            ));
+        }
+
+        let mut instants = vec![];
+        for (row, line) in lines {
+            let cells =
+                split(line).map_err(|(column, message)| error(row, Some(column), message))?;
+            if cells.len() != header.len() {
+                let message = format!("expected {} values, found {}", header.len(), cells.len());
+                return Err(error(row, None, message));
+            }
+            let mut inputs = vec![];
+            for ((_, t), column) in self.inputs.iter().zip(columns.iter().flatten()) {
+                let value = parse_value(cells[*column].trim(), &t.inner)
+                    .map_err(|message| error(row, Some(column + 1), message))?;
+                inputs.push(value);
+            }
+            instants.push(inputs);
+        }
+        Ok(instants)
+    }
+
+    /// Inputs of one instant written in the order of the inputs and separated
+    /// by commas, like `1, [2, 3], true`
+    pub fn read_inputs(&self, line: &str) -> Result<Vec<Value>, String> {
+        let items = items_of(line)?;
+        if items.len() != self.inputs.len() {
+            return Err(format!(
+                "expected {} values, found {}",
+                self.inputs.len(),
+                items.len()
+            ));
+        }
+        self.inputs
+            .iter()
+            .zip(items)
+            .map(|((name, t), item)| {
+                parse_value(item, &t.inner).map_err(|message| format!("{name}: {message}"))
+            })
+            .collect()
+    }
+
+    /// CSV file with a header naming the outputs and one row per instant
+    pub fn write_outputs(&self, outputs: &[Vec<Value>], w: &mut impl Write) -> io::Result<()> {
+        let header: Vec<String> = self.outputs.keys().map(|name| name.fragment()).collect();
+        write_row(w, header)?;
+        for values in outputs {
+            write_row(w, values.iter().map(cell).collect())?;
+        }
+        Ok(())
+    }
+}
+
+/// Cells of a line, a cell can be quoted with `"` to hold commas and `""`
+/// stands for a quote inside it. The error has the column of the cell.
+fn split(line: &str) -> Result<Vec<String>, (usize, String)> {
+    let mut cells = vec![String::new()];
+    let mut chars = line.chars().peekable();
+    let mut quoted = false;
+    while let Some(c) = chars.next() {
+        let cell = cells.last_mut().unwrap();
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                cell.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if cell.trim().is_empty() => {
+                cell.clear();
+                quoted = true;
+            }
+            ',' if !quoted => cells.push(String::new()),
+            c => cell.push(c),
+        }
+    }
+    if quoted {
+        return Err((cells.len(), "unterminated quoted value".to_string()));
+    }
+    Ok(cells)
+}
+
+fn write_row(w: &mut impl Write, cells: Vec<String>) -> io::Result<()> {
+    let cells: Vec<String> = cells
+        .into_iter()
+        .map(|cell| {
+            if cell.contains([',', '"', '\n']) || cell.trim() != cell {
+                format!("\"{}\"", cell.replace('"', "\"\""))
+            } else {
+                cell
+            }
+        })
+        .collect();
+    writeln!(w, "{}", cells.join(","))
+}
+
+/// Cell of a value read back by [FunctionType::read_stimulus], characters
+/// and strings are not quoted unless they are inside an array or a tuple
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Char(c) => c.to_string(),
+        Value::String(s) => s.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// Value of type `t` written in `text`, arrays are written `[a, b]` and
+/// tuples `(a, b)`
+fn parse_value(text: &str, t: &InnerVarType) -> Result<Value, String> {
+    let expected = || format!("expected {t}, found '{text}'");
+    match t {
+        InnerVarType::Unit if text == "()" => Ok(Value::Unit),
+        InnerVarType::Bool => match text {
+            "true" | "1" => Ok(Value::Bool(true)),
+            "false" | "0" => Ok(Value::Bool(false)),
+            _ => Err(expected()),
+        },
+        InnerVarType::Int => text.parse().map(Value::Int).map_err(|_| expected()),
+        InnerVarType::SizedInt(kind) => {
+            let i: i64 = text.parse().map_err(|_| expected())?;
+            if kind.contains(i) {
+                Ok(Value::SizedInt(*kind, i))
+            } else {
+                Err(format!("{i} does not fit in {kind}"))
+            }
+        }
+        InnerVarType::Float => text.parse().map(Value::Float).map_err(|_| expected()),
+        InnerVarType::Char => match (quoted(text), &text.chars().collect::<Vec<_>>()[..]) {
+            (Some(Value::Char(c)), _) | (None, &[c]) => Ok(Value::Char(c)),
+            _ => Err(expected()),
+        },
+        InnerVarType::String => match quoted(text) {
+            Some(Value::String(s)) => Ok(Value::String(s)),
+            _ => Ok(Value::String(text.to_string())),
+        },
+        InnerVarType::Array { t, len } => {
+            let items = text
+                .strip_prefix('[')
+                .and_then(|text| text.strip_suffix(']'))
+                .ok_or_else(expected)?;
+            let items = items_of(items)?;
+            if let InferLen::Known(len) = len
+                && *len != items.len()
+            {
+                return Err(format!("expected {len} items, found {}", items.len()));
+            }
+            let items: Result<_, _> = items.into_iter().map(|item| parse_value(item, t)).collect();
+            Ok(Value::Array(items?))
+        }
+        InnerVarType::Tuple(types) => {
+            let items = text
+                .strip_prefix('(')
+                .and_then(|text| text.strip_suffix(')'))
+                .ok_or_else(expected)?;
+            let items = items_of(items)?;
+            if items.len() != types.len() {
+                return Err(format!(
+                    "expected {} items, found {}",
+                    types.len(),
+                    items.len()
+                ));
+            }
+            let items: Result<_, _> = items
+                .into_iter()
+                .zip(types)
+                .map(|(item, t)| parse_value(item, t))
+                .collect();
+            Ok(Value::Tuple(items?))
+        }
+        InnerVarType::Unit => Err(expected()),
+    }
+}
+
+/// Character or string literal taking the whole `text`
+fn quoted(text: &str) -> Option<Value> {
+    match literal(LSpan::new(text)) {
+        Ok((rest, value)) if rest.fragment().is_empty() => Some(value),
+        _ => None,
+    }
+}
+
+/// Items separated by commas outside of brackets, parentheses and quotes
+fn items_of(text: &str) -> Result<Vec<&str>, String> {
+    if text.trim().is_empty() {
+        return Ok(vec![]);
+    }
+    let mut items = vec![];
+    let (mut depth, mut quote, mut escaped, mut start) = (0, None, false, 0);
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (Some(_), _) if escaped => escaped = false,
+            (Some(_), '\\') => escaped = true,
+            (Some(q), c) if q == c => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '[' | '(') => depth += 1,
+            (None, ']' | ')') => depth -= 1,
+            (None, ',') if depth == 0 => {
+                items.push(text[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if depth != 0 || quote.is_some() {
+        return Err(format!("unbalanced '{text}'"));
+    }
+    items.push(text[start..].trim());
+    Ok(items)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        ast::{integer::IntKind, literal::Value},
+        checker::function_type::FunctionType,
+        interpreter::csv::CsvError,
+        parser::lustre_parser::lustre_parse,
+    };
+
+    const PROGRAM: &str =
+        "node main(gain: int8, xs: int^2, label: string, on: bool) returns (sum: int, tag: string);
+let
+    sum = if on then xs[0] + xs[1] else 0;
+    tag = label;
+tel
+";
+
+    fn main() -> FunctionType {
+        lustre_parse(PROGRAM)
+            .unwrap()
+            .function_type("main")
+            .unwrap()
+    }
+
+    #[test]
+    fn stimulus() {
+        let csv = "on, xs, gain, label
+true, \"[1, -2]\", -3, hello
+0,\"[4,5]\",127,\"a, \"\"b\"\"\"
+";
+        let inputs = vec![
+            vec![
+                Value::SizedInt(IntKind::Int8, -3),
+                Value::Array(vec![Value::Int(1), Value::Int(-2)]),
+                Value::String("hello".to_string()),
+                Value::Bool(true),
+            ],
+            vec![
+                Value::SizedInt(IntKind::Int8, 127),
+                Value::Array(vec![Value::Int(4), Value::Int(5)]),
+                Value::String("a, \"b\"".to_string()),
+                Value::Bool(false),
+            ],
+        ];
+        assert_eq!(main().read_stimulus(csv), Ok(inputs));
+    }
+    #[test]
+    fn errors_point_at_cells() {
+        let error = |row, column: Option<usize>, message: &str| {
+            Err(CsvError {
+                row,
+                column,
+                message: message.to_string(),
+            })
+        };
+        let header = "gain,xs,label,on\n";
+        assert_eq!(
+            main().read_stimulus(&format!("{header}1,\"[1, 2]\",a,true\n1,\"[1]\",b,true")),
+            error(3, Some(2), "expected 2 items, found 1")
+        );
+        assert_eq!(
+            main().read_stimulus(&format!("{header}\n200,\"[1, 2]\",a,true")),
+            error(3, Some(1), "200 does not fit in int8")
+        );
+        assert_eq!(
+            main().read_stimulus(&format!("{header}1,\"[1, 2]\",a,yes")),
+            error(2, Some(4), "expected bool, found 'yes'")
+        );
+        assert_eq!(
+            main().read_stimulus(&format!("{header}1,\"[1, 2]\",a")),
+            error(2, None, "expected 4 values, found 3")
+        );
+        assert_eq!(
+            main().read_stimulus("gain,xs,label,off"),
+            error(1, Some(4), "unknown input 'off'")
+        );
+        assert_eq!(
+            main().read_stimulus("gain,xs,label"),
+            error(1, None, "missing column for input 'on'")
+        );
+    }
+    #[test]
+    fn outputs() {
+        let outputs = vec![
+            vec![Value::Int(-1), Value::String("a, b".to_string())],
+            vec![Value::Int(2), Value::String("c".to_string())],
+        ];
+        let mut csv = vec![];
+        main().write_outputs(&outputs, &mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "sum,tag\n-1,\"a, b\"\n2,c\n"
+        );
+    }
+}
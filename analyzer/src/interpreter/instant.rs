@@ -1,26 +1,24 @@
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-pub enum Instant {
-    Initial,
-    NonInitial,
-}
+use serde_derive::{Deserialize, Serialize};
+
+/// Number of the instant of a node, counted from `0` at its first step
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Instant(usize);
 
 impl std::fmt::Display for Instant {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Instant::Initial => write!(f, "init"),
-            Instant::NonInitial => write!(f, "not init"),
-        }
+        write!(f, "{}", self.0)
     }
 }
 impl Instant {
-    pub const INIT: Self = Self::Initial;
+    pub const INIT: Self = Self(0);
 
     pub fn step(&mut self) {
-        if let Self::Initial = self {
-            *self = Self::NonInitial;
-        }
+        self.0 += 1;
     }
     pub fn is_init(&self) -> bool {
-        self == &Self::Initial
+        self.0 == 0
+    }
+    pub fn count(&self) -> usize {
+        self.0
     }
 }
@@ -0,0 +1,131 @@
+use crate::{
+    interpreter::runtime_error::{RuntimeError, RuntimeErrorKind},
+    parser::span::Span,
+};
+use serde_derive::{Deserialize, Serialize};
+
+/// Boolean flow checked at every step, with the text it was compiled from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Monitor {
+    Assert(String),
+    /// Contract assumption, blamed on `caller` when violated
+    Assume {
+        assumption: String,
+        caller: Option<String>,
+    },
+    Guarantee(String),
+}
+
+impl Monitor {
+    /// Guarantees only matter once every assumption holds
+    pub fn is_guarantee(&self) -> bool {
+        matches!(self, Monitor::Guarantee(_))
+    }
+    pub fn violation(&self, instant: usize, span: Option<Span>) -> RuntimeError {
+        let kind = match self {
+            Monitor::Assert(assertion) => RuntimeErrorKind::AssertionFailed {
+                assertion: assertion.clone(),
+            },
+            Monitor::Assume { assumption, caller } => RuntimeErrorKind::AssumptionFailed {
+                assumption: assumption.clone(),
+                caller: caller.clone(),
+            },
+            Monitor::Guarantee(guarantee) => RuntimeErrorKind::GuaranteeFailed {
+                guarantee: guarantee.clone(),
+            },
+        };
+        RuntimeError {
+            kind,
+            instant,
+            span,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        ast::literal::Value,
+        interpreter::runtime_error::{RuntimeError, RuntimeErrorKind},
+        parser::span::Span,
+        test::checked,
+    };
+
+    const PROGRAM: &str = "node inv(x: int) returns (y: int);
+(*@contract
+    assume x != 0;
+    guarantee y != 0;
+*)
+let
+    y = 1 / x;
+tel
+
+node main(x: int) returns (y: int);
+let
+    y = inv(x - 1);
+tel
+
+node scaled(x: int) returns (y: int);
+let
+    assert 10 / x != 0;
+    y = x;
+tel
+";
+
+    fn run(node: &str, input: i64) -> Result<Vec<Value>, RuntimeErrorKind> {
+        let ast = checked(PROGRAM);
+        let name = ast
+            .nodes
+            .iter()
+            .find(|n| n.name.fragment() == node)
+            .unwrap();
+        ast.compile(name.name.clone())
+            .step(vec![Value::Int(input)])
+            .map_err(|error: RuntimeError| {
+                assert_eq!(error.instant, 0);
+                error.kind
+            })
+    }
+
+    #[test]
+    fn assumption_blames_caller() {
+        assert_eq!(run("main", 2), Ok(vec![Value::Int(1)]));
+        assert_eq!(
+            run("main", 1),
+            Err(RuntimeErrorKind::AssumptionFailed {
+                assumption: "inv: assume x != 0".to_string(),
+                caller: Some("main".to_string()),
+            })
+        );
+        assert_eq!(
+            run("inv", 0),
+            Err(RuntimeErrorKind::AssumptionFailed {
+                assumption: "inv: assume x != 0".to_string(),
+                caller: None,
+            })
+        );
+    }
+
+    #[test]
+    fn guarantee_violated() {
+        assert_eq!(
+            run("inv", 2),
+            Err(RuntimeErrorKind::GuaranteeFailed {
+                guarantee: "inv: guarantee y != 0".to_string(),
+            })
+        );
+    }
+    #[test]
+    fn monitor_without_value() {
+        assert_eq!(run("scaled", 0), Err(RuntimeErrorKind::DivisionByZero));
+        let ast = checked(PROGRAM);
+        let mut registers = ast
+            .compile_registers(Span::new(0, 0, "scaled".to_string()))
+            .unwrap();
+        let error = registers.step(vec![Value::Int(0)]).unwrap_err();
+        assert_eq!(
+            (error.kind, error.span.unwrap().fragment()),
+            (RuntimeErrorKind::DivisionByZero, "/".to_string())
+        );
+    }
+}
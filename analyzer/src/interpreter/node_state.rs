@@ -0,0 +1,44 @@
+use crate::{ast::literal::Value, interpreter::instant::Instant};
+use serde_derive::{Deserialize, Serialize};
+
+/// Runtime state of a [CompiledNode](crate::interpreter::compiled_node::CompiledNode),
+/// the values of its expressions with the `pre` holding its memory, and its
+/// instant
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeState {
+    pub(crate) values: Vec<Option<Value>>,
+    pub(crate) instant: Instant,
+    /// Number of steps done so far
+    pub(crate) steps: usize,
+}
+
+impl Default for NodeState {
+    fn default() -> Self {
+        Self::new(vec![])
+    }
+}
+
+impl NodeState {
+    pub(crate) fn new(values: Vec<Option<Value>>) -> Self {
+        Self {
+            values,
+            instant: Instant::INIT,
+            steps: 0,
+        }
+    }
+}
+
+/// States of a node and of its instances, taken by
+/// [CompiledNode::snapshot](crate::interpreter::compiled_node::CompiledNode::snapshot)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub(crate) state: NodeState,
+    pub(crate) instances: Vec<Snapshot>,
+}
+
+impl Snapshot {
+    /// Number of steps of the node when the snapshot was taken
+    pub fn steps(&self) -> usize {
+        self.state.steps
+    }
+}
@@ -0,0 +1,305 @@
+use crate::{
+    ast::ast::Ast,
+    checker::function_type::FunctionType,
+    interpreter::{compiled_node::CompiledCode, runtime::Runtime},
+    parser::{span::Span, var_type::VarType},
+};
+use lsp_types::{Diagnostic, DiagnosticSeverity};
+use serde_derive::{Deserialize, Serialize};
+use std::rc::Rc;
+
+/// Version of the format of [CompiledProgram], increased at every change of
+/// the compiled nodes
+pub const FORMAT_VERSION: u32 = 3;
+
+/// Scheduled node with the types of its inputs and outputs, saved as JSON to
+/// be run without the source of the program
+#[derive(Debug, Clone)]
+pub struct CompiledProgram {
+    inputs: Vec<(String, VarType)>,
+    outputs: Vec<(String, VarType)>,
+    /// The expressions, their schedules and debug infos, with the code of
+    /// the called nodes
+    code: Rc<CompiledCode>,
+}
+
+/// [CompiledProgram] as saved, the code of a node called several times is
+/// saved once
+#[derive(Serialize, Deserialize)]
+struct SavedProgram {
+    version: u32,
+    inputs: Vec<(String, VarType)>,
+    outputs: Vec<(String, VarType)>,
+    /// Code of every node after the code of the nodes it calls, the node
+    /// of the program is the last one
+    codes: Vec<CompiledCode>,
+    /// Positions in `codes` of the code of the instances of each code
+    instances: Vec<Vec<usize>>,
+}
+
+/// Only the version is read first, to tell a program of another version from
+/// a broken file
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+}
+
+#[derive(Debug)]
+pub enum ProgramError {
+    /// The program was saved by another version of the compiler
+    Version {
+        found: u32,
+    },
+    Json(serde_json::Error),
+    /// The program refers to expressions, instances or flows it does not have
+    Invalid(String),
+    /// The source has no node of this name
+    NoNode(String),
+    /// Errors of the type checker on the source
+    Check(Vec<Diagnostic>),
+}
+
+impl std::fmt::Display for ProgramError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProgramError::Version { found } => write!(
+                f,
+                "compiled program of version {found}, expected version {FORMAT_VERSION}"
+            ),
+            ProgramError::Json(error) => write!(f, "invalid compiled program: {error}"),
+            ProgramError::Invalid(message) => write!(f, "invalid compiled program: {message}"),
+            ProgramError::NoNode(name) => write!(f, "no node '{name}'"),
+            ProgramError::Check(errors) => write!(f, "{} errors in the program", errors.len()),
+        }
+    }
+}
+
+impl std::error::Error for ProgramError {}
+
+impl Ast {
+    /// [Ast::compile] with the types of the node, once the program is
+    /// checked without error
+    pub fn compile_program(&mut self, name: &str) -> Result<CompiledProgram, ProgramError> {
+        let (diags, _) = self.check();
+        let errors: Vec<Diagnostic> = diags
+            .into_iter()
+            .filter(|diag| diag.severity == Some(DiagnosticSeverity::ERROR))
+            .collect();
+        if !errors.is_empty() {
+            return Err(ProgramError::Check(errors));
+        }
+        let function_type = self
+            .function_type(name)
+            .ok_or_else(|| ProgramError::NoNode(name.to_string()))?;
+        let flows = |flows: &indexmap::IndexMap<Span, VarType>| {
+            flows
+                .iter()
+                .map(|(name, t)| (name.fragment(), t.clone()))
+                .collect()
+        };
+        Ok(CompiledProgram {
+            inputs: flows(&function_type.inputs),
+            outputs: flows(&function_type.outputs),
+            code: self
+                .compile(Span::new(0, 0, name.to_string()))
+                .code()
+                .clone(),
+        })
+    }
+}
+
+impl CompiledProgram {
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        let mut codes = vec![];
+        save(&self.code, &mut codes);
+        let position = |code: &Rc<CompiledCode>| {
+            codes
+                .iter()
+                .position(|saved| Rc::ptr_eq(saved, code))
+                .unwrap()
+        };
+        let instances = codes
+            .iter()
+            .map(|code| code.instances().iter().map(position).collect())
+            .collect();
+        serde_json::to_string(&SavedProgram {
+            version: FORMAT_VERSION,
+            inputs: self.inputs.clone(),
+            outputs: self.outputs.clone(),
+            codes: codes.iter().map(|code| (**code).clone()).collect(),
+            instances,
+        })
+    }
+    pub fn from_json(json: &str) -> Result<Self, ProgramError> {
+        let header: Header = serde_json::from_str(json).map_err(ProgramError::Json)?;
+        if header.version != FORMAT_VERSION {
+            return Err(ProgramError::Version {
+                found: header.version,
+            });
+        }
+        let saved: SavedProgram = serde_json::from_str(json).map_err(ProgramError::Json)?;
+        Self::load(saved).map_err(ProgramError::Invalid)
+    }
+    /// The codes only call the ones before them and run without panic, and
+    /// the node takes the flows of the program
+    fn load(saved: SavedProgram) -> Result<Self, String> {
+        if saved.codes.len() != saved.instances.len() {
+            return Err(format!(
+                "{} codes with the instances of {}",
+                saved.codes.len(),
+                saved.instances.len()
+            ));
+        }
+        let mut codes: Vec<Rc<CompiledCode>> = vec![];
+        for (mut code, instances) in saved.codes.into_iter().zip(saved.instances) {
+            let instances = instances
+                .into_iter()
+                .map(|i| {
+                    let len = codes.len();
+                    let code = codes
+                        .get(i)
+                        .ok_or(format!("code {i} called by code {len}"))?;
+                    Ok(code.clone())
+                })
+                .collect::<Result<_, String>>()?;
+            code.set_instances(instances);
+            code.validate()?;
+            codes.push(Rc::new(code));
+        }
+        let code = codes.pop().ok_or("no node")?;
+        let (inputs, outputs) = code.flow_counts();
+        if (inputs, outputs) != (saved.inputs.len(), saved.outputs.len()) {
+            return Err(format!(
+                "node with {inputs} inputs and {outputs} outputs for {} inputs and {} outputs",
+                saved.inputs.len(),
+                saved.outputs.len()
+            ));
+        }
+        Ok(Self {
+            inputs: saved.inputs,
+            outputs: saved.outputs,
+            code,
+        })
+    }
+
+    /// Node before its first step, on registers when its flows are scalars
+    pub fn node(&self) -> Runtime {
+        Runtime::new(self.code.clone())
+    }
+    /// Types of the inputs and outputs, to read and write their values
+    pub fn function_type(&self) -> FunctionType {
+        let flows = |flows: &[(String, VarType)]| {
+            flows
+                .iter()
+                .map(|(name, t)| (Span::new(0, 0, name.clone()), t.clone()))
+                .collect()
+        };
+        FunctionType::new(flows(&self.inputs), flows(&self.outputs))
+    }
+}
+
+/// Push the code of the nodes called by `code` and then `code`, each once
+fn save(code: &Rc<CompiledCode>, codes: &mut Vec<Rc<CompiledCode>>) {
+    if codes.iter().any(|saved| Rc::ptr_eq(saved, code)) {
+        return;
+    }
+    for instance in code.instances() {
+        save(instance, codes);
+    }
+    codes.push(code.clone());
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        ast::literal::Value,
+        interpreter::{
+            program::{CompiledProgram, FORMAT_VERSION, ProgramError},
+            runtime::Runtime,
+        },
+        parser::lustre_parser::lustre_parse,
+        test::with_counter,
+    };
+
+    const MAIN: &str = "node main(x: int, c: bool) returns (n: int, f: float);
+let
+    assert x != 100;
+    n = condact(c, counter(x), 0);
+    f = float(n) / 2.0;
+tel
+";
+
+    #[test]
+    fn run_without_source() {
+        let mut ast = with_counter(MAIN);
+        let json = ast.compile_program("main").unwrap().to_json().unwrap();
+        let program = CompiledProgram::from_json(&json).unwrap();
+        let function_type = program.function_type();
+        let inputs = function_type
+            .read_stimulus("x,c\n1,true\n2,false\n3,true\n100,true")
+            .unwrap();
+
+        let mut node = program.node();
+        assert!(matches!(node, Runtime::Registers(_)));
+        let mut source = ast.compile_program("main").unwrap().node();
+        for inputs in inputs {
+            let outputs = node.step(inputs.clone());
+            assert_eq!(outputs, source.step(inputs));
+        }
+        assert_eq!(
+            program.node().step(vec![Value::Int(4), Value::Bool(true)]),
+            Ok(vec![Value::Int(4), Value::Float(2.0)])
+        );
+        assert_eq!(function_type.outputs.len(), 2);
+    }
+    #[test]
+    fn other_version() {
+        let mut ast = with_counter(MAIN);
+        let json = ast.compile_program("main").unwrap().to_json().unwrap();
+        let version = format!("\"version\":{FORMAT_VERSION}");
+        let json = json.replacen(&version, "\"version\":0", 1);
+        assert!(matches!(
+            CompiledProgram::from_json(&json),
+            Err(ProgramError::Version { found: 0 })
+        ));
+        assert!(matches!(
+            CompiledProgram::from_json("{}"),
+            Err(ProgramError::Json(_))
+        ));
+    }
+    #[test]
+    fn invalid_program() {
+        let mut ast = with_counter(MAIN);
+        let json = ast.compile_program("main").unwrap().to_json().unwrap();
+        assert!(!json.contains("\"state\"") && !json.contains("\"memo_start\""));
+
+        let variable = "{\"Variable\":";
+        let at = json.find(variable).unwrap() + variable.len();
+        let mut broken = json.clone();
+        broken.replace_range(at..at + json[at..].find('}').unwrap(), "99");
+        assert!(matches!(
+            CompiledProgram::from_json(&broken),
+            Err(ProgramError::Invalid(_))
+        ));
+
+        let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        value["inputs"].as_array_mut().unwrap().pop();
+        assert!(matches!(
+            CompiledProgram::from_json(&value.to_string()),
+            Err(ProgramError::Invalid(_))
+        ));
+    }
+    #[test]
+    fn unchecked_source() {
+        let mut ast = with_counter(MAIN);
+        assert!(matches!(
+            ast.compile_program("other"),
+            Err(ProgramError::NoNode(_))
+        ));
+        let mut ast = lustre_parse("node main(x: int) returns (y: bool); let y = x; tel").unwrap();
+        assert!(matches!(
+            ast.compile_program("main"),
+            Err(ProgramError::Check(errors)) if errors.len() == 1
+        ));
+    }
+}
@@ -0,0 +1,92 @@
+use crate::{
+    ast::literal::Value, checker::function_type::FunctionType, checker::infer_types::InferLen,
+    parser::var_type::InnerVarType,
+};
+
+/// Pseudo-random generator of input values, a run is replayed from its seed
+#[derive(Debug, Clone)]
+pub struct Random(u64);
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        // The state of a xorshift must not be 0
+        Self(seed.max(1))
+    }
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+    /// Integer between `min` and `max` included
+    fn range(&mut self, min: i64, max: i64) -> i64 {
+        let len = (max as i128 - min as i128 + 1) as u128;
+        (min as i128 + (self.next() as u128 % len) as i128) as i64
+    }
+    fn value(&mut self, t: &InnerVarType) -> Value {
+        match t {
+            InnerVarType::Unit => Value::Unit,
+            InnerVarType::Bool => Value::Bool(self.next().is_multiple_of(2)),
+            // Small values, so that a few steps do not overflow
+            InnerVarType::Int => Value::Int(self.range(-100, 100)),
+            InnerVarType::SizedInt(kind) => {
+                Value::SizedInt(*kind, self.range(kind.min(), kind.max()))
+            }
+            InnerVarType::Float => Value::Float(self.range(-10_000, 10_000) as f64 / 100.0),
+            InnerVarType::Char => Value::Char(self.range(' ' as i64, '~' as i64) as u8 as char),
+            InnerVarType::String => {
+                let len = self.range(0, 8);
+                let s = (0..len)
+                    .map(|_| self.range('a' as i64, 'z' as i64) as u8 as char)
+                    .collect();
+                Value::String(s)
+            }
+            InnerVarType::Tuple(types) => {
+                Value::Tuple(types.iter().map(|t| self.value(t)).collect())
+            }
+            InnerVarType::Array { t, len } => {
+                let len = match len {
+                    InferLen::Known(len) => *len,
+                    InferLen::Unknown => self.range(0, 4) as usize,
+                };
+                Value::Array((0..len).map(|_| self.value(t)).collect())
+            }
+        }
+    }
+}
+
+impl FunctionType {
+    /// Inputs of one instant, drawn at random in their types
+    pub fn random_inputs(&self, random: &mut Random) -> Vec<Value> {
+        self.inputs
+            .values()
+            .map(|t| random.value(&t.inner))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{interpreter::random::Random, parser::lustre_parser::lustre_parse};
+
+    #[test]
+    fn inputs_fit_their_types() {
+        let program =
+            "node main(a: uint8, b: int64, c: float^3, d: string, e: char) returns (y: bool);
+let
+    y = true;
+tel
+";
+        let main = lustre_parse(program)
+            .unwrap()
+            .function_type("main")
+            .unwrap();
+        let mut random = Random::new(42);
+        for _ in 0..100 {
+            let inputs = main.random_inputs(&mut random);
+            // Written as typed in the simulator and read back
+            let line: Vec<String> = inputs.iter().map(|value| value.to_string()).collect();
+            assert_eq!(main.read_inputs(&line.join(", ")), Ok(inputs));
+        }
+    }
+}
@@ -0,0 +1,424 @@
+use crate::{
+    ast::{integer::Overflow, literal::Value},
+    interpreter::{
+        compiled_value::{CompiledValue, Scalar},
+        expr_index::ExprIndex,
+        instant::Instant,
+        monitor::Monitor,
+        node_state::{NodeState, Snapshot},
+        runtime_error::{Dataflow, Origin, RuntimeError, RuntimeErrorKind},
+        trace::Trace,
+    },
+    parser::span::Span,
+};
+use std::rc::Rc;
+
+pub mod instruction;
+pub mod origin;
+
+use instruction::Instruction;
+
+/// Instructions of a [CompiledCode](crate::interpreter::compiled_node::CompiledCode)
+/// whose flows are all scalars, shared by all the instances of the node. The
+/// instructions are specialised on the types of their operands when it is
+/// built with [CompiledCode::registers](crate::interpreter::compiled_node::CompiledCode::registers).
+#[derive(Debug)]
+pub struct RegisterCode {
+    /// Name of the node
+    name: String,
+    /// Instructions of the first instant in order
+    init_step: Vec<(ExprIndex, Instruction)>,
+    /// Instructions of the other instants in order
+    step: Vec<(ExprIndex, Instruction)>,
+    /// `pre` and their source, copied in order once the outputs are known
+    memories: Vec<(ExprIndex, ExprIndex)>,
+    /// Source of the expressions of the registers
+    spans: Vec<Option<Span>>,
+    types: Vec<Scalar>,
+    inputs: Vec<ExprIndex>,
+    outputs: Vec<ExprIndex>,
+    /// Code of the instances stepped by [Instruction::Call]
+    instances: Vec<Rc<RegisterCode>>,
+    /// Assertions and assumptions first, then guarantees
+    monitors: Vec<(ExprIndex, Monitor)>,
+    /// Named flows recorded by a trace and their register
+    flows: Vec<(String, ExprIndex)>,
+    overflow: Overflow,
+}
+
+/// A [CompiledNode](crate::interpreter::compiled_node::CompiledNode) run on
+/// unboxed registers, instance of a [RegisterCode] with its own memory
+#[derive(Debug, Clone)]
+pub struct RegisterNode {
+    code: Rc<RegisterCode>,
+    registers: Vec<Option<CompiledValue>>,
+    /// Origin of the outputs without value at the last step
+    faults: Vec<Option<Origin>>,
+    /// Instances of the called nodes, they have their own memory
+    instances: Vec<RegisterNode>,
+    instant: Instant,
+    /// Number of steps done so far
+    steps: usize,
+    /// Values of the named flows recorded at every step once started
+    trace: Option<Trace>,
+}
+
+impl RegisterCode {
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        name: String,
+        init_step: Vec<(ExprIndex, Instruction)>,
+        step: Vec<(ExprIndex, Instruction)>,
+        memories: Vec<(ExprIndex, ExprIndex)>,
+        spans: Vec<Option<Span>>,
+        types: Vec<Scalar>,
+        inputs: Vec<ExprIndex>,
+        outputs: Vec<ExprIndex>,
+        instances: Vec<Rc<RegisterCode>>,
+        mut monitors: Vec<(ExprIndex, Monitor)>,
+        flows: Vec<(String, ExprIndex)>,
+        overflow: Overflow,
+    ) -> Self {
+        monitors.sort_by_key(|(_, monitor)| monitor.is_guarantee());
+        Self {
+            name,
+            init_step,
+            step,
+            memories,
+            spans,
+            types,
+            inputs,
+            outputs,
+            instances,
+            monitors,
+            flows,
+            overflow,
+        }
+    }
+}
+
+impl RegisterNode {
+    /// Instance of `code` before its first step
+    pub fn new(code: Rc<RegisterCode>) -> Self {
+        Self {
+            registers: vec![None; code.types.len()],
+            faults: vec![None; code.outputs.len()],
+            instances: code.instances.iter().cloned().map(Self::new).collect(),
+            instant: Instant::INIT,
+            steps: 0,
+            trace: None,
+            code,
+        }
+    }
+    pub fn name(&self) -> &str {
+        &self.code.name
+    }
+
+    /// Same as [CompiledNode::step](crate::interpreter::compiled_node::CompiledNode::step)
+    pub fn step(&mut self, inputs: Vec<Value>) -> Result<Vec<Value>, RuntimeError> {
+        if inputs.len() != self.code.inputs.len() {
+            let kind = RuntimeErrorKind::InputCount {
+                expected: self.code.inputs.len(),
+                found: inputs.len(),
+            };
+            return Err(RuntimeError {
+                kind,
+                instant: self.steps,
+                span: None,
+            });
+        }
+        let position = self
+            .code
+            .inputs
+            .iter()
+            .zip(inputs.iter())
+            .position(|(index, input)| !self.code.types[*index].fits(input));
+        if let Some(position) = position {
+            return Err(RuntimeError {
+                kind: RuntimeErrorKind::InputType { position },
+                instant: self.steps,
+                span: None,
+            });
+        }
+        let inputs = inputs.iter().map(Scalar::to_register).collect();
+        self.run(inputs, self.steps)?;
+        if let Some(fault) = self.faults.iter().flatten().next() {
+            return Err(fault.clone().error(self.steps));
+        }
+        let res = self
+            .code
+            .outputs
+            .iter()
+            .flat_map(|output| self.value(*output))
+            .collect();
+        self.steps += 1;
+        Ok(res)
+    }
+    /// Value of the register `index`
+    fn value(&self, index: ExprIndex) -> Option<Value> {
+        Some(self.code.types[index].to_value(self.registers[index]?))
+    }
+
+    /// One instant of the node, the violations are reported at the instant
+    /// `steps` of the program
+    fn run(
+        &mut self,
+        inputs: Vec<Option<CompiledValue>>,
+        steps: usize,
+    ) -> Result<(), RuntimeError> {
+        let code = &self.code;
+        for (index, register) in code.inputs.iter().zip(inputs) {
+            self.registers[*index] = register;
+        }
+        let schedule = if self.instant.is_init() {
+            &code.init_step
+        } else {
+            &code.step
+        };
+        for (pos, instruction) in schedule.iter() {
+            self.registers[*pos] = match instruction {
+                Instruction::Call {
+                    instance,
+                    args,
+                    cond,
+                    reset,
+                } => {
+                    let holds = |index: &ExprIndex| {
+                        self.registers[*index] == Some(CompiledValue::from_bool(true))
+                    };
+                    if cond.as_ref().is_none_or(holds) {
+                        if reset.as_ref().is_some_and(holds) {
+                            self.instances[*instance].restart();
+                        }
+                        let args = args.iter().map(|arg| self.registers[*arg]).collect();
+                        self.instances[*instance]
+                            .run(args, steps)
+                            .map_err(|error| error.called_by(&code.name))?;
+                    }
+                    Some(CompiledValue::default())
+                }
+                Instruction::CallOutput { instance, index } => {
+                    let instance = &self.instances[*instance];
+                    instance.registers[instance.code.outputs[*index]]
+                }
+                instruction => instruction.execute(&self.registers, code.overflow),
+            };
+        }
+        if let Some(trace) = &mut self.trace {
+            let values: Vec<Option<Value>> = self
+                .registers
+                .iter()
+                .zip(code.types.iter())
+                .map(|(register, t)| Some(t.to_value((*register)?)))
+                .collect();
+            trace.record(steps, &values);
+        }
+        for (index, monitor) in code.monitors.iter() {
+            match self.registers[*index] {
+                Some(register) if register == CompiledValue::from_bool(false) => {
+                    return Err(monitor.violation(steps, code.spans[*index].clone()));
+                }
+                None => return Err(self.origin(*index).error(steps)),
+                _ => (),
+            }
+        }
+        // The origins are found before the `pre` forget the values read
+        for i in 0..code.outputs.len() {
+            self.faults[i] = match self.registers[code.outputs[i]] {
+                Some(_) => None,
+                None => Some(self.origin(code.outputs[i])),
+            };
+        }
+        for (pre, src) in code.memories.iter() {
+            self.registers[*pre] = self.registers[*src];
+        }
+        self.instant.step();
+        Ok(())
+    }
+    /// Same as the restart of a [CompiledNode](crate::interpreter::compiled_node::CompiledNode)
+    fn restart(&mut self) {
+        self.registers = vec![None; self.registers.len()];
+        self.instant = Instant::INIT;
+        for instance in self.instances.iter_mut() {
+            instance.restart();
+        }
+    }
+
+    /// Same as [CompiledNode::snapshot](crate::interpreter::compiled_node::CompiledNode::snapshot),
+    /// the registers are saved as values
+    pub fn snapshot(&self) -> Snapshot {
+        let values = (0..self.registers.len())
+            .map(|index| self.value(index))
+            .collect();
+        Snapshot {
+            state: NodeState {
+                values,
+                instant: self.instant,
+                steps: self.steps,
+            },
+            instances: self.instances.iter().map(|i| i.snapshot()).collect(),
+        }
+    }
+    /// Same as [CompiledNode::restore](crate::interpreter::compiled_node::CompiledNode::restore),
+    /// `false` when a value does not fit in its register
+    pub fn restore(&mut self, snapshot: &Snapshot) -> bool {
+        if !self.fits(snapshot) {
+            return false;
+        }
+        self.restore_unchecked(snapshot);
+        true
+    }
+    fn restore_unchecked(&mut self, snapshot: &Snapshot) {
+        self.registers = snapshot
+            .state
+            .values
+            .iter()
+            .map(|value| Scalar::to_register(value.as_ref()?))
+            .collect();
+        self.instant = snapshot.state.instant;
+        self.steps = snapshot.state.steps;
+        for (instance, snapshot) in self.instances.iter_mut().zip(snapshot.instances.iter()) {
+            instance.restore_unchecked(snapshot);
+        }
+    }
+    /// Whether `snapshot` was taken on this node
+    fn fits(&self, snapshot: &Snapshot) -> bool {
+        snapshot.state.values.len() == self.registers.len()
+            && self
+                .code
+                .types
+                .iter()
+                .zip(snapshot.state.values.iter())
+                .all(|(t, value)| value.as_ref().is_none_or(|value| t.fits(value)))
+            && snapshot.instances.len() == self.instances.len()
+            && self
+                .instances
+                .iter()
+                .zip(snapshot.instances.iter())
+                .all(|(instance, snapshot)| instance.fits(snapshot))
+    }
+
+    /// Same as [CompiledNode::start_trace](crate::interpreter::compiled_node::CompiledNode::start_trace)
+    pub fn start_trace(&mut self) {
+        self.start_trace_at(self.code.name.clone(), self.steps);
+    }
+    fn start_trace_at(&mut self, name: String, start: usize) {
+        self.trace = Some(Trace::new(name, self.code.flows.clone(), start));
+        for (i, instance) in self.instances.iter_mut().enumerate() {
+            let name = format!("{}_{i}", instance.code.name);
+            instance.start_trace_at(name, start);
+        }
+    }
+    /// Trace of the flows of this node, without its instances
+    pub fn trace(&self) -> Option<&Trace> {
+        self.trace.as_ref()
+    }
+    /// Stop recording and return the trace with the traces of the instances
+    pub fn take_trace(&mut self) -> Option<Trace> {
+        let mut trace = self.trace.take()?;
+        for instance in self.instances.iter_mut() {
+            trace.push_instance(instance.take_trace()?);
+        }
+        Some(trace)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        ast::literal::Value,
+        interpreter::runtime_error::RuntimeErrorKind,
+        parser::span::Span,
+        test::{compile_node, with_counter},
+    };
+
+    const NODES: &str = "node mean(x: float) returns (m: float);
+var
+    s: float;
+    k: int;
+let
+    s = x + (0.0 fby s);
+    k = 1 + (0 fby k);
+    m = s / float(k);
+tel
+
+node safe(x: int) returns (y: int);
+let
+    y = if x == 0 then 0 else 10 / x;
+tel
+
+node bits(x: uint8) returns (y: uint8, b: bool);
+let
+    y = (lnot x) shl 1;
+    b = bit(x, 0) and (x != 3);
+tel
+
+node greet(x: int) returns (s: string);
+let
+    s = \"hello\";
+tel
+
+node same(c: char) returns (d: char);
+let
+    d = c;
+tel
+";
+
+    /// Outputs of both runtimes on the same inputs
+    fn run(node: &str, inputs: Vec<Vec<Value>>) -> (Vec<Vec<Value>>, Vec<Vec<Value>>) {
+        let ast = with_counter(NODES);
+        let mut compiled = compile_node(&ast, node);
+        let mut registers = ast
+            .compile_registers(Span::new(0, 0, node.to_string()))
+            .unwrap();
+        inputs
+            .into_iter()
+            .map(|input| {
+                (
+                    compiled.step(input.clone()).unwrap(),
+                    registers.step(input).unwrap(),
+                )
+            })
+            .unzip()
+    }
+
+    #[test]
+    fn same_outputs() {
+        let ints = |v: &[i64]| v.iter().map(|i| vec![Value::Int(*i)]).collect();
+        let (compiled, registers) = run("counter", ints(&[1, 2, 3, -4]));
+        assert_eq!(compiled, registers);
+        let (compiled, registers) = run("safe", ints(&[2, 0, 5]));
+        assert_eq!(compiled, registers);
+        let floats = [1.5, 2.0, -0.5].iter().map(|f| vec![Value::Float(*f)]);
+        let (compiled, registers) = run("mean", floats.collect());
+        assert_eq!(compiled, registers);
+    }
+    #[test]
+    fn sized_integers() {
+        let inputs = [0, 1, 3, 255].iter().map(|i| vec![Value::Int(*i)]);
+        let (compiled, registers) = run("bits", inputs.collect());
+        assert_eq!(compiled, registers);
+    }
+    #[test]
+    fn strings_are_not_scalars() {
+        let ast = with_counter(NODES);
+        let greet = Span::new(0, 0, "greet".to_string());
+        assert!(ast.compile_registers(greet).is_none());
+    }
+    #[test]
+    fn inputs_of_another_type() {
+        let ast = with_counter(NODES);
+        let same = Span::new(0, 0, "same".to_string());
+        let mut registers = ast.compile_registers(same).unwrap();
+        assert_eq!(
+            registers
+                .step(vec![Value::Int(0xD800)])
+                .map_err(|error| error.kind),
+            Err(RuntimeErrorKind::InputType { position: 0 })
+        );
+        assert_eq!(
+            registers.step(vec![Value::Char('a')]),
+            Ok(vec![Value::Char('a')])
+        );
+    }
+}
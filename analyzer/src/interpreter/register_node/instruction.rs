@@ -0,0 +1,207 @@
+use crate::{
+    ast::{binop::BinOp, builtin::Builtin, integer::IntKind, integer::Overflow, unary_op::UnaryOp},
+    interpreter::{compiled_value::CompiledValue, compiled_value::Scalar, expr_index::ExprIndex},
+};
+
+/// Operation of a [CompiledExpr](crate::interpreter::compiled_expr::CompiledExpr)
+/// on registers, specialised on the types of its operands
+#[derive(Debug, Clone, PartialEq)]
+pub enum Instruction {
+    Lit(CompiledValue),
+    /// `Variable` and `Pre`, a `pre` is scheduled after its readers
+    Copy(ExprIndex),
+    If {
+        cond: ExprIndex,
+        yes: ExprIndex,
+        no: ExprIndex,
+    },
+    /// Arithmetic and bitwise operators on integers of type `kind`
+    Int {
+        op: BinOp,
+        kind: IntKind,
+        lhs: ExprIndex,
+        rhs: ExprIndex,
+    },
+    Float {
+        op: BinOp,
+        lhs: ExprIndex,
+        rhs: ExprIndex,
+    },
+    Bit {
+        kind: IntKind,
+        lhs: ExprIndex,
+        rhs: ExprIndex,
+    },
+    /// `==` or `!=` on operands of type `scalar`
+    Eq {
+        neq: bool,
+        scalar: Scalar,
+        lhs: ExprIndex,
+        rhs: ExprIndex,
+    },
+    And {
+        lhs: ExprIndex,
+        rhs: ExprIndex,
+    },
+    Or {
+        lhs: ExprIndex,
+        rhs: ExprIndex,
+    },
+    Unary {
+        op: UnaryOp,
+        scalar: Scalar,
+        rhs: ExprIndex,
+    },
+    Builtin {
+        builtin: Builtin,
+        scalar: Scalar,
+        args: Vec<ExprIndex>,
+    },
+    /// Step of an instance, run by its [RegisterNode](crate::interpreter::register_node::RegisterNode)
+    Call {
+        instance: usize,
+        args: Vec<ExprIndex>,
+        cond: Option<ExprIndex>,
+        reset: Option<ExprIndex>,
+    },
+    CallOutput {
+        instance: usize,
+        index: usize,
+    },
+}
+
+impl Instruction {
+    /// Value of the register at this instant, `None` when it has no value
+    /// like a `pre` at the first instant or a division by zero
+    pub fn execute(
+        &self,
+        registers: &[Option<CompiledValue>],
+        overflow: Overflow,
+    ) -> Option<CompiledValue> {
+        use Instruction::*;
+        match self {
+            Lit(value) => Some(*value),
+            Copy(src) => registers[*src],
+            If { cond, yes, no } => {
+                if registers[*cond]?.bool() {
+                    registers[*yes]
+                } else {
+                    registers[*no]
+                }
+            }
+            Int { op, kind, lhs, rhs } => {
+                let (l, r) = (registers[*lhs]?.int(), registers[*rhs]?.int());
+                let res = match op {
+                    BinOp::Add | BinOp::Sub | BinOp::Mult | BinOp::Div => {
+                        overflow.apply(*op, *kind, l, r)
+                    }
+                    _ => kind.bitwise(*op, l, r),
+                };
+                res.map(CompiledValue::from_int)
+            }
+            Float { op, lhs, rhs } => {
+                let (l, r) = (registers[*lhs]?.float(), registers[*rhs]?.float());
+                let res = match op {
+                    BinOp::Add => l + r,
+                    BinOp::Sub => l - r,
+                    BinOp::Mult => l * r,
+                    BinOp::Div => l / r,
+                    _ => return None,
+                };
+                Some(CompiledValue::from_float(res))
+            }
+            Bit { kind, lhs, rhs } => kind
+                .bit(registers[*lhs]?.int(), registers[*rhs]?.int())
+                .map(CompiledValue::from_bool),
+            Eq {
+                neq,
+                scalar,
+                lhs,
+                rhs,
+            } => {
+                let (l, r) = (registers[*lhs]?, registers[*rhs]?);
+                let eq = match scalar {
+                    Scalar::Float => l.float() == r.float(),
+                    _ => l == r,
+                };
+                Some(CompiledValue::from_bool(eq != *neq))
+            }
+            And { lhs, rhs } => Some(CompiledValue::from_bool(
+                registers[*lhs]?.bool() && registers[*rhs]?.bool(),
+            )),
+            Or { lhs, rhs } => Some(CompiledValue::from_bool(
+                registers[*lhs]?.bool() || registers[*rhs]?.bool(),
+            )),
+            Unary { op, scalar, rhs } => unary(*op, *scalar, registers[*rhs]?, overflow),
+            Builtin {
+                builtin,
+                scalar,
+                args,
+            } => {
+                // Built-in functions have at most 3 arguments
+                let mut values = [CompiledValue::default(); 3];
+                for (value, arg) in values.iter_mut().zip(args) {
+                    *value = registers[*arg]?;
+                }
+                apply_builtin(*builtin, *scalar, &values[..args.len()])
+            }
+            Call { .. } | CallOutput { .. } => unreachable!(),
+        }
+    }
+}
+
+/// Same as [UnaryOp::apply] on a register of type `scalar`
+fn unary(
+    op: UnaryOp,
+    scalar: Scalar,
+    rhs: CompiledValue,
+    overflow: Overflow,
+) -> Option<CompiledValue> {
+    use UnaryOp::*;
+    let int = |i: i128, kind: IntKind| overflow.fit(i, kind).map(CompiledValue::from_int);
+    match (op, scalar) {
+        (Inv, Scalar::Float) => Some(CompiledValue::from_float(-rhs.float())),
+        (Inv, scalar) => int(-(rhs.int() as i128), scalar.kind()),
+        (Not, _) => Some(CompiledValue::from_bool(!rhs.bool())),
+        (Lnot, scalar) => Some(CompiledValue::from_int(scalar.kind().not(rhs.int()))),
+        (ToFloat, Scalar::Float) => Some(rhs),
+        (ToFloat, _) => Some(CompiledValue::from_float(rhs.int() as f64)),
+        (ToInt, Scalar::Float) => int(rhs.float().trunc() as i128, IntKind::Int64),
+        (Floor, Scalar::Float) => int(rhs.float().floor() as i128, IntKind::Int64),
+        (Round, Scalar::Float) => int(rhs.float().round() as i128, IntKind::Int64),
+        (ToInt | Floor | Round, _) => Some(rhs),
+        (Abs, Scalar::Float) => Some(CompiledValue::from_float(rhs.float().abs())),
+        (Abs, scalar) => int((rhs.int() as i128).abs(), scalar.kind()),
+        (ToSized(kind), Scalar::Float) => int(rhs.float().trunc() as i128, kind),
+        (ToSized(kind), _) => int(rhs.int() as i128, kind),
+        // Lowered by the compiler or not on registers
+        (Pre | Once | Historically | Yesterday | Len, _) => None,
+    }
+}
+
+/// Same as [Builtin::apply] on registers of type `scalar`
+fn apply_builtin(
+    builtin: Builtin,
+    scalar: Scalar,
+    args: &[CompiledValue],
+) -> Option<CompiledValue> {
+    use Builtin::*;
+    let float = |f: fn(f64) -> f64| Some(CompiledValue::from_float(f(args[0].float())));
+    let less = |l: CompiledValue, r: CompiledValue| match scalar {
+        Scalar::Float => l.float() < r.float(),
+        _ => l.int() < r.int(),
+    };
+    let min = |l, r| if less(r, l) { r } else { l };
+    let max = |l, r| if less(l, r) { r } else { l };
+    match (builtin, args) {
+        (Sqrt, _) => float(f64::sqrt),
+        (Sin, _) => float(f64::sin),
+        (Cos, _) => float(f64::cos),
+        (Exp, _) => float(f64::exp),
+        (Atan2, [y, x]) => Some(CompiledValue::from_float(y.float().atan2(x.float()))),
+        (Min, [l, r]) => Some(min(*l, *r)),
+        (Max, [l, r]) => Some(max(*l, *r)),
+        (Clamp, [x, low, high]) => Some(max(min(*x, *high), *low)),
+        _ => None,
+    }
+}
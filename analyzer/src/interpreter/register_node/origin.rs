@@ -0,0 +1,102 @@
+use crate::{
+    ast::{binop::BinOp, unary_op::UnaryOp},
+    interpreter::{
+        compiled_value::CompiledValue,
+        expr_index::ExprIndex,
+        register_node::{RegisterNode, instruction::Instruction},
+        runtime_error::{Dataflow, Origin, RuntimeErrorKind},
+    },
+    parser::span::Span,
+};
+
+impl RegisterNode {
+    /// Instruction of `index` at this instant, `None` for the inputs and the `pre`
+    fn instruction(&self, index: ExprIndex) -> Option<&Instruction> {
+        let schedule = if self.instant.is_init() {
+            &self.code.init_step
+        } else {
+            &self.code.step
+        };
+        let pos = schedule.binary_search_by_key(&index, |(i, _)| *i).ok()?;
+        Some(&schedule[pos].1)
+    }
+}
+
+impl Dataflow for RegisterNode {
+    fn has_value(&self, index: ExprIndex) -> bool {
+        self.registers[index].is_some()
+    }
+    fn reads(&self, index: ExprIndex) -> Vec<ExprIndex> {
+        use Instruction::*;
+        match self.instruction(index) {
+            None | Some(Lit(_) | CallOutput { .. }) => vec![],
+            Some(Copy(src)) => vec![*src],
+            Some(If { cond, yes, no }) => match self.registers[*cond] {
+                Some(cond) if cond.bool() => vec![*yes],
+                Some(_) => vec![*no],
+                None => vec![*cond],
+            },
+            Some(
+                Int { lhs, rhs, .. }
+                | Float { lhs, rhs, .. }
+                | Bit { lhs, rhs, .. }
+                | Eq { lhs, rhs, .. }
+                | And { lhs, rhs }
+                | Or { lhs, rhs },
+            ) => vec![*lhs, *rhs],
+            Some(Unary { rhs, .. }) => vec![*rhs],
+            Some(Builtin { args, .. } | Call { args, .. }) => args.clone(),
+        }
+    }
+    fn input(&self, index: ExprIndex) -> Option<usize> {
+        self.code.inputs.iter().position(|input| *input == index)
+    }
+    fn is_memory(&self, index: ExprIndex) -> bool {
+        self.code.memories.iter().any(|(pre, _)| *pre == index)
+    }
+    fn instance_origin(&self, index: ExprIndex) -> Option<(Origin, &[ExprIndex])> {
+        let Some(Instruction::CallOutput { instance, index }) = self.instruction(index) else {
+            return None;
+        };
+        // The call is the instruction stepping this instance
+        let args = [&self.code.init_step, &self.code.step]
+            .into_iter()
+            .flatten()
+            .find_map(|(_, instruction)| match instruction {
+                Instruction::Call {
+                    instance: call,
+                    args,
+                    ..
+                } if call == instance => Some(args),
+                _ => None,
+            })?;
+        let origin = self.instances[*instance].faults[*index].clone()?;
+        Some((origin, args))
+    }
+    fn fault(&self, index: ExprIndex) -> RuntimeErrorKind {
+        match self.instruction(index) {
+            Some(Instruction::Int {
+                op: BinOp::Div,
+                rhs,
+                ..
+            }) if self.registers[*rhs] == Some(CompiledValue::from_int(0)) => {
+                RuntimeErrorKind::DivisionByZero
+            }
+            Some(Instruction::Int { .. }) => RuntimeErrorKind::Overflow,
+            Some(Instruction::Unary {
+                op:
+                    UnaryOp::Inv
+                    | UnaryOp::Abs
+                    | UnaryOp::ToInt
+                    | UnaryOp::Floor
+                    | UnaryOp::Round
+                    | UnaryOp::ToSized(_),
+                ..
+            }) => RuntimeErrorKind::Overflow,
+            _ => RuntimeErrorKind::NoValue,
+        }
+    }
+    fn span(&self, index: ExprIndex) -> Option<Span> {
+        self.code.spans[index].clone()
+    }
+}
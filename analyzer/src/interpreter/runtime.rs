@@ -0,0 +1,151 @@
+use crate::{
+    ast::literal::Value,
+    interpreter::{
+        compiled_node::{CompiledCode, CompiledNode},
+        node_state::Snapshot,
+        register_node::RegisterNode,
+        runtime_error::RuntimeError,
+        trace::Trace,
+    },
+};
+use std::rc::Rc;
+
+/// Instance of a compiled node run on registers when its flows are all
+/// scalars, and on boxed values by a [CompiledNode] otherwise
+#[derive(Debug, Clone)]
+pub enum Runtime {
+    Registers(RegisterNode),
+    Values(CompiledNode),
+}
+
+impl Runtime {
+    /// Instance of `code` before its first step
+    pub fn new(code: Rc<CompiledCode>) -> Self {
+        match code.registers() {
+            Some(registers) => Runtime::Registers(registers),
+            None => Runtime::Values(CompiledNode::new(code)),
+        }
+    }
+    pub fn name(&self) -> &str {
+        match self {
+            Runtime::Registers(node) => node.name(),
+            Runtime::Values(node) => node.name(),
+        }
+    }
+    /// [CompiledNode::step] on both runtimes
+    pub fn step(&mut self, inputs: Vec<Value>) -> Result<Vec<Value>, RuntimeError> {
+        match self {
+            Runtime::Registers(node) => node.step(inputs),
+            Runtime::Values(node) => node.step(inputs),
+        }
+    }
+    /// [CompiledNode::snapshot], the same on both runtimes
+    pub fn snapshot(&self) -> Snapshot {
+        match self {
+            Runtime::Registers(node) => node.snapshot(),
+            Runtime::Values(node) => node.snapshot(),
+        }
+    }
+    /// [CompiledNode::restore]
+    pub fn restore(&mut self, snapshot: &Snapshot) -> bool {
+        match self {
+            Runtime::Registers(node) => node.restore(snapshot),
+            Runtime::Values(node) => node.restore(snapshot),
+        }
+    }
+    /// [CompiledNode::start_trace]
+    pub fn start_trace(&mut self) {
+        match self {
+            Runtime::Registers(node) => node.start_trace(),
+            Runtime::Values(node) => node.start_trace(),
+        }
+    }
+    /// [CompiledNode::trace]
+    pub fn trace(&self) -> Option<&Trace> {
+        match self {
+            Runtime::Registers(node) => node.trace(),
+            Runtime::Values(node) => node.trace(),
+        }
+    }
+    /// [CompiledNode::take_trace]
+    pub fn take_trace(&mut self) -> Option<Trace> {
+        match self {
+            Runtime::Registers(node) => node.take_trace(),
+            Runtime::Values(node) => node.take_trace(),
+        }
+    }
+}
+
+impl From<CompiledNode> for Runtime {
+    fn from(node: CompiledNode) -> Self {
+        Runtime::Values(node)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        ast::literal::Value,
+        interpreter::runtime::Runtime,
+        parser::{lustre_parser::lustre_parse, span::Span},
+        test::{COUNTER, compile_node, with_counter},
+    };
+
+    const MAIN: &str = "node main(x: int) returns (n: int, m: int);
+var
+    k: int;
+let
+    n = counter(x);
+    k = 0 -> pre n;
+    m = k * 2;
+tel
+
+node greet(x: int) returns (s: string);
+let
+    s = \"hello\";
+tel
+";
+
+    #[test]
+    fn registers_for_scalars() {
+        let ast = with_counter(MAIN);
+        let runtime = |name: &str| ast.compile_runtime(Span::new(0, 0, name.to_string()));
+        assert!(matches!(runtime("main"), Runtime::Registers(_)));
+        assert!(matches!(runtime("greet"), Runtime::Values(_)));
+        // Not checked, the types of the registers are not known
+        let unchecked = lustre_parse(&format!("{COUNTER}{MAIN}")).unwrap();
+        assert!(matches!(
+            unchecked.compile_runtime(Span::new(0, 0, "main".to_string())),
+            Runtime::Values(_)
+        ));
+    }
+    #[test]
+    fn same_snapshots_and_traces() {
+        let ast = with_counter(MAIN);
+        let mut registers = ast.compile_runtime(Span::new(0, 0, "main".to_string()));
+        let mut values = Runtime::from(compile_node(&ast, "main"));
+        registers.start_trace();
+        values.start_trace();
+        for x in 1..4 {
+            let inputs = vec![Value::Int(x)];
+            assert_eq!(registers.step(inputs.clone()), values.step(inputs));
+        }
+        assert_eq!(registers.snapshot(), values.snapshot());
+
+        // A snapshot of one runtime is restored by the other
+        let snapshot = registers.snapshot();
+        registers.step(vec![Value::Int(10)]).unwrap();
+        assert!(values.restore(&snapshot));
+        assert!(registers.restore(&values.snapshot()));
+        let inputs = vec![Value::Int(4)];
+        assert_eq!(
+            registers.step(inputs.clone()),
+            Ok(vec![Value::Int(10), Value::Int(12)])
+        );
+        assert_eq!(
+            values.step(inputs),
+            Ok(vec![Value::Int(10), Value::Int(12)])
+        );
+        assert_eq!(registers.take_trace(), values.take_trace());
+    }
+}
@@ -0,0 +1,260 @@
+use crate::{interpreter::expr_index::ExprIndex, parser::span::Span};
+use serde_derive::{Deserialize, Serialize};
+
+/// Error stopping the execution of a [CompiledNode](crate::interpreter::compiled_node::CompiledNode)
+/// at the step `instant`, `span` is the source of the faulting expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeError {
+    pub kind: RuntimeErrorKind,
+    pub instant: usize,
+    pub span: Option<Span>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RuntimeErrorKind {
+    /// The inputs do not satisfy an assumption of the node
+    AssertionFailed {
+        assertion: String,
+    },
+    /// A contract assumption is violated, `caller` is the node feeding the
+    /// arguments, `None` when they come from the inputs of the program
+    AssumptionFailed {
+        assumption: String,
+        caller: Option<String>,
+    },
+    /// The node does not keep its contract
+    GuaranteeFailed {
+        guarantee: String,
+    },
+    /// The step is given a wrong number of inputs
+    InputCount {
+        expected: usize,
+        found: usize,
+    },
+    /// The input at this position does not have the type of the node input
+    InputType {
+        position: usize,
+    },
+    DivisionByZero,
+    /// An integer does not fit in its type with the trapping behavior
+    Overflow,
+    /// A `pre` is read at the first instant or its source had no value
+    NoPreviousValue,
+    /// An output has no value for another reason
+    NoValue,
+}
+
+impl std::fmt::Display for RuntimeErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuntimeErrorKind::AssertionFailed { assertion } => {
+                write!(f, "assertion '{assertion}' violated")
+            }
+            RuntimeErrorKind::AssumptionFailed { assumption, caller } => match caller {
+                Some(caller) => {
+                    write!(f, "assumption '{assumption}' violated by caller '{caller}'")
+                }
+                None => write!(f, "assumption '{assumption}' violated by the inputs"),
+            },
+            RuntimeErrorKind::GuaranteeFailed { guarantee } => {
+                write!(f, "guarantee '{guarantee}' violated")
+            }
+            RuntimeErrorKind::InputCount { expected, found } => {
+                write!(f, "expected {expected} inputs, found {found}")
+            }
+            RuntimeErrorKind::InputType { position } => {
+                write!(f, "input {position} does not have the type of the node")
+            }
+            RuntimeErrorKind::DivisionByZero => write!(f, "division by zero"),
+            RuntimeErrorKind::Overflow => write!(f, "integer overflow"),
+            RuntimeErrorKind::NoPreviousValue => write!(f, "'pre' has no value"),
+            RuntimeErrorKind::NoValue => write!(f, "no value"),
+        }
+    }
+}
+
+impl std::fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at instant {}", self.kind, self.instant)?;
+        match &self.span {
+            Some(span) => write!(
+                f,
+                " ('{span}' at {}:{})",
+                span.location_line(),
+                span.get_column()
+            ),
+            None => Ok(()),
+        }
+    }
+}
+
+impl std::error::Error for RuntimeError {}
+
+impl RuntimeError {
+    /// Error of an instance called by the node `caller`, which is blamed for
+    /// the assumptions of the instance. The code of a node is shared by its
+    /// callers, its assumptions do not name them.
+    pub(crate) fn called_by(mut self, caller: &str) -> Self {
+        if let RuntimeErrorKind::AssumptionFailed {
+            caller: blamed @ None,
+            ..
+        } = &mut self.kind
+        {
+            *blamed = Some(caller.to_string());
+        }
+        self
+    }
+}
+
+/// Where the missing value of an output comes from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) enum Origin {
+    Fault(RuntimeErrorKind, Option<Span>),
+    /// The input at this position of the node has no value
+    Input(usize),
+}
+
+impl Origin {
+    /// Error of the step `instant` of the program
+    pub(crate) fn error(self, instant: usize) -> RuntimeError {
+        let (kind, span) = match self {
+            Origin::Fault(kind, span) => (kind, span),
+            Origin::Input(_) => (RuntimeErrorKind::NoValue, None),
+        };
+        RuntimeError {
+            kind,
+            instant,
+            span,
+        }
+    }
+}
+
+/// Expressions of a node at the end of an instant, before its `pre` are
+/// updated, to find the [Origin] of a flow without value
+pub(crate) trait Dataflow {
+    fn has_value(&self, index: ExprIndex) -> bool;
+    /// Expressions read by `index` at this instant, only the taken branch
+    /// of an `if` and the taken side of a `->`
+    fn reads(&self, index: ExprIndex) -> Vec<ExprIndex>;
+    /// Position of `index` in the inputs of the node
+    fn input(&self, index: ExprIndex) -> Option<usize>;
+    fn is_memory(&self, index: ExprIndex) -> bool;
+    /// Origin recorded by the instance whose output is read by `index`, with
+    /// the arguments of its call
+    fn instance_origin(&self, index: ExprIndex) -> Option<(Origin, &[ExprIndex])>;
+    /// Why `index` has no value when every expression it reads has one
+    fn fault(&self, index: ExprIndex) -> RuntimeErrorKind;
+    fn span(&self, index: ExprIndex) -> Option<Span>;
+
+    /// Follow the expressions without value read by `index`
+    fn origin(&self, mut index: ExprIndex) -> Origin {
+        loop {
+            if let Some(input) = self.input(index) {
+                return Origin::Input(input);
+            }
+            if self.is_memory(index) {
+                return Origin::Fault(RuntimeErrorKind::NoPreviousValue, self.span(index));
+            }
+            if let Some((origin, args)) = self.instance_origin(index) {
+                match origin {
+                    Origin::Input(input) => {
+                        index = args[input];
+                        continue;
+                    }
+                    fault => return fault,
+                }
+            }
+            match self
+                .reads(index)
+                .into_iter()
+                .find(|read| !self.has_value(*read))
+            {
+                Some(read) => index = read,
+                None => return Origin::Fault(self.fault(index), self.span(index)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        ast::literal::Value,
+        interpreter::runtime_error::{RuntimeError, RuntimeErrorKind},
+        parser::{lustre_parser::lustre_parse, span::Span},
+    };
+
+    const PROGRAM: &str = "node ratio(x: int, y: int) returns (q: int);
+let
+    q = x / y;
+tel
+
+node main(x: int) returns (q: int);
+let
+    q = ratio(10, x - 2);
+tel
+
+node delay(x: int) returns (y: int);
+let
+    y = pre x;
+tel
+
+node grow(x: int8) returns (y: int8);
+let
+    y = x * 2;
+tel
+";
+
+    /// Error of the first failing step on both runtimes
+    fn fail(node: &str, inputs: Vec<Vec<Value>>) -> RuntimeError {
+        // `pre x` has no value at the first instant of `delay`, the checker
+        // reports it but still types the flows
+        let mut ast = lustre_parse(PROGRAM).unwrap();
+        ast.check();
+        let name = Span::new(0, 0, node.to_string());
+        let mut compiled = ast.compile(name.clone());
+        let mut registers = ast.compile_registers(name).unwrap();
+        for input in inputs {
+            let compiled = compiled.step(input.clone());
+            assert_eq!(compiled, registers.step(input));
+            if let Err(error) = compiled {
+                return error;
+            }
+        }
+        panic!("no runtime error")
+    }
+
+    #[test]
+    fn division_by_zero_in_callee() {
+        let inputs = [3, 1, 2].iter().map(|x| vec![Value::Int(*x)]).collect();
+        let error = fail("main", inputs);
+        assert_eq!(error.kind, RuntimeErrorKind::DivisionByZero);
+        assert_eq!(error.instant, 2);
+        let span = error.span.unwrap();
+        assert_eq!(
+            (span.fragment(), span.location_line(), span.get_column()),
+            ("/".to_string(), 3, 11)
+        );
+    }
+    #[test]
+    fn pre_at_first_instant() {
+        let error = fail("delay", vec![vec![Value::Int(1)]]);
+        assert_eq!(error.kind, RuntimeErrorKind::NoPreviousValue);
+        assert_eq!(error.span.unwrap().fragment(), "pre");
+    }
+    #[test]
+    fn overflow() {
+        let inputs = [1, 100].iter().map(|x| vec![Value::Int(*x)]).collect();
+        let error = fail("grow", inputs);
+        assert_eq!((error.kind, error.instant), (RuntimeErrorKind::Overflow, 1));
+    }
+    #[test]
+    fn input_count() {
+        let error = fail("main", vec![vec![]]);
+        let kind = RuntimeErrorKind::InputCount {
+            expected: 1,
+            found: 0,
+        };
+        assert_eq!((error.kind, error.span), (kind, None));
+    }
+}
@@ -0,0 +1,111 @@
+use crate::{
+    ast::literal::Value,
+    interpreter::{node_state::Snapshot, runtime::Runtime, runtime_error::RuntimeError},
+};
+
+/// Run of a node that can go back to any earlier instant: a snapshot is
+/// taken every `period` steps and the inputs since are replayed, the steps
+/// being deterministic
+#[derive(Debug, Clone)]
+pub struct Timeline {
+    node: Runtime,
+    period: usize,
+    /// Snapshots after `start + i * period` steps
+    checkpoints: Vec<Snapshot>,
+    /// Inputs of the steps from `start`
+    inputs: Vec<Vec<Value>>,
+    /// Steps done by the node before the timeline
+    start: usize,
+}
+
+impl Timeline {
+    pub fn new(node: Runtime, period: usize) -> Self {
+        let checkpoint = node.snapshot();
+        Self {
+            start: checkpoint.steps(),
+            node,
+            period: period.max(1),
+            checkpoints: vec![checkpoint],
+            inputs: vec![],
+        }
+    }
+    pub fn node(&self) -> &Runtime {
+        &self.node
+    }
+    /// Number of steps done by the node, the next step is at this instant
+    pub fn steps(&self) -> usize {
+        self.start + self.inputs.len()
+    }
+    /// First instant the timeline can go back to
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// [Runtime::step] kept in the timeline, a failed step is undone
+    pub fn step(&mut self, inputs: Vec<Value>) -> Result<Vec<Value>, RuntimeError> {
+        match self.node.step(inputs.clone()) {
+            Ok(outputs) => {
+                self.inputs.push(inputs);
+                if self.inputs.len().is_multiple_of(self.period) {
+                    self.checkpoints.push(self.node.snapshot());
+                }
+                Ok(outputs)
+            }
+            Err(error) => {
+                self.go_to(self.steps());
+                Err(error)
+            }
+        }
+    }
+    /// State after `steps` steps, restored from the last checkpoint before
+    /// it and replayed. The later steps are forgotten. `false` when `steps`
+    /// is out of the timeline.
+    pub fn go_to(&mut self, steps: usize) -> bool {
+        if steps < self.start || self.steps() < steps {
+            return false;
+        }
+        let len = steps - self.start;
+        let checkpoint = len / self.period;
+        self.checkpoints.truncate(checkpoint + 1);
+        self.inputs.truncate(len);
+        self.node.restore(&self.checkpoints[checkpoint]);
+        for inputs in self.inputs[checkpoint * self.period..].iter() {
+            let replayed = self.node.step(inputs.clone());
+            debug_assert!(replayed.is_ok(), "replayed step failed: {replayed:?}");
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        ast::literal::Value, interpreter::timeline::Timeline, parser::span::Span,
+        test::with_counter,
+    };
+
+    const MAIN: &str = "node main(x: int) returns (n: int);
+let
+    n = counter(x);
+tel
+";
+
+    #[test]
+    fn go_back_and_forth() {
+        let mut node = with_counter(MAIN).compile_runtime(Span::new(0, 0, "main".to_string()));
+        node.start_trace();
+        let mut timeline = Timeline::new(node, 4);
+        for x in 1..=10 {
+            timeline.step(vec![Value::Int(x)]).unwrap();
+        }
+        assert!(timeline.go_to(6));
+        assert_eq!(timeline.steps(), 6);
+        assert_eq!(timeline.step(vec![Value::Int(0)]), Ok(vec![Value::Int(21)]));
+        let trace = timeline.node().trace().unwrap();
+        assert_eq!((trace.len(), trace.get("n", 5)), (7, Some(&Value::Int(21))));
+
+        assert!(timeline.go_to(0));
+        assert_eq!(timeline.step(vec![Value::Int(3)]), Ok(vec![Value::Int(3)]));
+        assert!(!timeline.go_to(2));
+    }
+}
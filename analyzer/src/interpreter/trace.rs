@@ -0,0 +1,169 @@
+use crate::{ast::literal::Value, interpreter::expr_index::ExprIndex};
+
+/// Values of the named flows of a node, its inputs, outputs and local
+/// variables, at every step recorded since
+/// [CompiledNode::start_trace](crate::interpreter::compiled_node::CompiledNode::start_trace)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trace {
+    /// Name of the node, followed by the number of the instance in its caller
+    name: String,
+    /// Named flows and the expression computing them
+    flows: Vec<(String, ExprIndex)>,
+    /// Step of the first recorded instant
+    start: usize,
+    /// Values of the flows at every recorded instant, in the order of `flows`
+    instants: Vec<Vec<Option<Value>>>,
+    /// Traces of the instances of the node, once taken
+    instances: Vec<Trace>,
+}
+
+impl Trace {
+    pub(crate) fn new(name: String, flows: Vec<(String, ExprIndex)>, start: usize) -> Self {
+        Self {
+            name,
+            flows,
+            start,
+            instants: vec![],
+            instances: vec![],
+        }
+    }
+    /// Values of the flows at the step `instant`, a failed step is recorded
+    /// again when it is retried and an instance not stepped keeps its values.
+    /// A node restored before the start of the trace records it from there.
+    pub(crate) fn record(&mut self, instant: usize, values: &[Option<Value>]) {
+        let row = self
+            .flows
+            .iter()
+            .map(|(_, index)| values[*index].clone())
+            .collect();
+        if instant < self.start {
+            self.instants.clear();
+            self.start = instant;
+        }
+        let at = instant - self.start;
+        self.instants.truncate(at);
+        while self.instants.len() < at {
+            let held = match self.instants.last() {
+                Some(last) => last.clone(),
+                None => vec![None; self.flows.len()],
+            };
+            self.instants.push(held);
+        }
+        self.instants.push(row);
+    }
+    pub(crate) fn push_instance(&mut self, instance: Trace) {
+        self.instances.push(instance);
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// Traces of the instances of the node, in the order of their calls
+    pub fn instances(&self) -> &[Trace] {
+        &self.instances
+    }
+
+    /// Value of the flow `name` at the step `instant`, `None` when it has no
+    /// value or is not recorded
+    pub fn get(&self, name: &str, instant: usize) -> Option<&Value> {
+        let flow = self.position(name)?;
+        self.instant(instant)?[flow].as_ref()
+    }
+    /// Names of the flows, the inputs then the outputs and the local variables
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.flows.iter().map(|(name, _)| name.as_str())
+    }
+    /// Values of the flow `name` at every recorded instant
+    pub fn flow(&self, name: &str) -> Option<impl Iterator<Item = Option<&Value>>> {
+        let flow = self.position(name)?;
+        Some(self.instants.iter().map(move |row| row[flow].as_ref()))
+    }
+    /// Values of the flows at the step `instant`, in the order of [Trace::names]
+    pub fn instant(&self, instant: usize) -> Option<&[Option<Value>]> {
+        let row = self.instants.get(instant.checked_sub(self.start)?)?;
+        Some(row)
+    }
+    /// Step of the first recorded instant
+    pub fn start(&self) -> usize {
+        self.start
+    }
+    /// Number of recorded instants
+    pub fn len(&self) -> usize {
+        self.instants.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.instants.is_empty()
+    }
+    fn position(&self, name: &str) -> Option<usize> {
+        self.flows.iter().position(|(flow, _)| flow == name)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        ast::literal::Value,
+        test::{compile_node, with_counter},
+    };
+
+    const MAIN: &str = "node main(x: int) returns (y: int);
+var
+    total: int;
+    last: int;
+let
+    total = counter(x);
+    last = 0 -> pre x;
+    y = total * 2;
+tel
+";
+
+    #[test]
+    fn named_flows() {
+        let mut node = compile_node(&with_counter(MAIN), "main");
+        node.step(vec![Value::Int(5)]).unwrap();
+        node.start_trace();
+        for x in [1, 2, 3] {
+            node.step(vec![Value::Int(x)]).unwrap();
+        }
+        let trace = node.trace().unwrap();
+        assert_eq!(
+            trace.names().collect::<Vec<_>>(),
+            ["x", "y", "total", "last"]
+        );
+        assert_eq!((trace.start(), trace.len()), (1, 3));
+        assert_eq!(trace.get("total", 2), Some(&Value::Int(8)));
+        assert_eq!(trace.get("y", 3), Some(&Value::Int(22)));
+        // Read by no output
+        assert_eq!(trace.get("last", 1), Some(&Value::Int(5)));
+        assert_eq!(trace.get("x", 0), None);
+        assert_eq!(trace.get("n", 1), None);
+        let xs: Vec<_> = trace.flow("x").unwrap().collect();
+        assert_eq!(
+            xs,
+            [
+                Some(&Value::Int(1)),
+                Some(&Value::Int(2)),
+                Some(&Value::Int(3))
+            ]
+        );
+    }
+    #[test]
+    fn restored_before_start() {
+        let mut node = compile_node(&with_counter(MAIN), "main");
+        let snapshot = node.snapshot();
+        node.step(vec![Value::Int(1)]).unwrap();
+        node.step(vec![Value::Int(2)]).unwrap();
+        node.start_trace();
+        assert!(node.restore(&snapshot));
+        node.step(vec![Value::Int(3)]).unwrap();
+        let trace = node.trace().unwrap();
+        assert_eq!((trace.start(), trace.len()), (0, 1));
+        assert_eq!(trace.get("total", 0), Some(&Value::Int(3)));
+        let trace = node.take_trace().unwrap();
+        let counter = &trace.instances()[0];
+        assert_eq!(
+            (counter.start(), counter.get("n", 0)),
+            (0, Some(&Value::Int(3)))
+        );
+    }
+}
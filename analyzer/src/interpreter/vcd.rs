@@ -0,0 +1,300 @@
+use crate::{ast::literal::Value, interpreter::trace::Trace};
+use std::io::{self, Write};
+
+/// Type of a variable of a Value Change Dump
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum VcdKind {
+    /// Vector of `width` bits, a boolean is a wire of 1 bit
+    Wire(u32),
+    Real,
+}
+
+impl VcdKind {
+    /// `None` for the values without waveform, like strings
+    fn of(value: &Value) -> Option<Self> {
+        match value {
+            Value::Bool(_) => Some(VcdKind::Wire(1)),
+            Value::Int(_) => Some(VcdKind::Wire(64)),
+            Value::SizedInt(kind, _) => Some(VcdKind::Wire(kind.bits() as u32)),
+            Value::Char(_) => Some(VcdKind::Wire(32)),
+            Value::Float(_) => Some(VcdKind::Real),
+            Value::Unit | Value::String(_) | Value::Tuple(_) | Value::Array(_) => None,
+        }
+    }
+    /// Value change of the variable `id`, unknown when there is no value
+    fn change(self, value: Option<&Value>, id: &str) -> Option<String> {
+        let bits = |i: i64, width: u32| {
+            let mask = if width == 64 {
+                u64::MAX
+            } else {
+                (1 << width) - 1
+            };
+            format!("b{:b} {id}", i as u64 & mask)
+        };
+        match (self, value) {
+            (VcdKind::Wire(1), Some(Value::Bool(b))) => Some(format!("{}{id}", *b as u8)),
+            (VcdKind::Wire(1), None) => Some(format!("x{id}")),
+            (VcdKind::Wire(width), Some(Value::Int(i) | Value::SizedInt(_, i))) => {
+                Some(bits(*i, width))
+            }
+            (VcdKind::Wire(width), Some(Value::Char(c))) => Some(bits(*c as i64, width)),
+            (VcdKind::Wire(_), None) => Some(format!("bx {id}")),
+            (VcdKind::Real, Some(Value::Float(f))) => Some(format!("r{f} {id}")),
+            // A real variable has no unknown value, it keeps the last one
+            _ => None,
+        }
+    }
+}
+
+/// Scalar component of a flow in a scope of the dump
+struct VcdVar<'a> {
+    trace: &'a Trace,
+    flow: usize,
+    /// Positions of the component in the nested arrays and tuples of the flow
+    path: Vec<usize>,
+    name: String,
+    kind: VcdKind,
+    id: String,
+}
+
+impl VcdVar<'_> {
+    /// Change at the step `instant`, `None` when the instance is not stepped.
+    /// Fails on infinite and NaN floats, which are not VCD reals.
+    fn change(&self, instant: usize) -> io::Result<Option<String>> {
+        let Some(values) = self.trace.instant(instant) else {
+            return Ok(None);
+        };
+        let value = values[self.flow]
+            .as_ref()
+            .and_then(|value| component(value, &self.path));
+        match value {
+            Some(Value::Float(f)) if !f.is_finite() => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} is {f} at the step {instant}", self.name),
+            )),
+            value => Ok(self.kind.change(value, &self.id)),
+        }
+    }
+}
+
+/// Scalar components of `value` with their name and path, `x[i]` for the
+/// items of an array and `x.i` for the ones of a tuple
+fn components(name: String, path: Vec<usize>, value: &Value) -> Vec<(String, Vec<usize>, &Value)> {
+    let (items, array) = match value {
+        Value::Array(items) => (items, true),
+        Value::Tuple(items) => (items, false),
+        value => return vec![(name, path, value)],
+    };
+    items
+        .iter()
+        .enumerate()
+        .flat_map(|(i, item)| {
+            let name = if array {
+                format!("{name}[{i}]")
+            } else {
+                format!("{name}.{i}")
+            };
+            let path = path.iter().copied().chain([i]).collect();
+            components(name, path, item)
+        })
+        .collect()
+}
+
+/// Component of `value` at `path`
+fn component<'a>(value: &'a Value, path: &[usize]) -> Option<&'a Value> {
+    path.iter().try_fold(value, |value, i| match value {
+        Value::Array(items) | Value::Tuple(items) => items.get(*i),
+        _ => None,
+    })
+}
+
+/// Identifier of the variable number `n`, printable ASCII characters
+fn identifier(mut n: usize) -> String {
+    let mut id = String::new();
+    loop {
+        id.push((b'!' + (n % 94) as u8) as char);
+        n /= 94;
+        if n == 0 {
+            return id;
+        }
+    }
+}
+
+impl Trace {
+    /// Value Change Dump of the trace, one time unit per step. Every instance
+    /// is a scope inside the scope of its caller.
+    pub fn write_vcd(&self, w: &mut impl Write) -> io::Result<()> {
+        writeln!(
+            w,
+            "$version lustre_analyzer {} $end",
+            env!("CARGO_PKG_VERSION")
+        )?;
+        writeln!(w, "$timescale 1 ns $end")?;
+        let mut vars = vec![];
+        self.declare(w, &mut vars)?;
+        writeln!(w, "$enddefinitions $end")?;
+
+        let mut last: Vec<Option<String>> = vec![None; vars.len()];
+        for instant in self.start()..self.start() + self.len() {
+            writeln!(w, "#{instant}")?;
+            let first = instant == self.start();
+            if first {
+                writeln!(w, "$dumpvars")?;
+            }
+            for (var, last) in vars.iter().zip(last.iter_mut()) {
+                // The variables without change are unknown until they have one
+                let Some(change) = var.change(instant)? else {
+                    continue;
+                };
+                if last.as_ref() != Some(&change) {
+                    writeln!(w, "{change}")?;
+                    *last = Some(change);
+                }
+            }
+            if first {
+                writeln!(w, "$end")?;
+            }
+        }
+        writeln!(w, "#{}", self.start() + self.len())
+    }
+
+    /// Scope of this trace, a flow without value at every instant has no type
+    /// and is not dumped
+    fn declare<'a>(&'a self, w: &mut impl Write, vars: &mut Vec<VcdVar<'a>>) -> io::Result<()> {
+        writeln!(w, "$scope module {} $end", self.name())?;
+        for (flow, name) in self.names().enumerate() {
+            let Some(value) = self
+                .flow(name)
+                .and_then(|mut values| values.find_map(|v| v))
+            else {
+                continue;
+            };
+            for (name, path, value) in components(name.to_string(), vec![], value) {
+                let Some(kind) = VcdKind::of(value) else {
+                    continue;
+                };
+                let id = identifier(vars.len());
+                match kind {
+                    VcdKind::Wire(width) => writeln!(w, "$var wire {width} {id} {name} $end")?,
+                    VcdKind::Real => writeln!(w, "$var real 64 {id} {name} $end")?,
+                }
+                vars.push(VcdVar {
+                    trace: self,
+                    flow,
+                    path,
+                    name,
+                    kind,
+                    id,
+                });
+            }
+        }
+        for instance in self.instances() {
+            instance.declare(w, vars)?;
+        }
+        writeln!(w, "$upscope $end")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        ast::literal::Value,
+        interpreter::vcd::{component, components, identifier},
+        test::{checked, compile_node, with_counter},
+    };
+
+    const MAIN: &str = "node main(x: int, c: bool) returns (n: int, f: float);
+let
+    n = condact(c, counter(x), 0);
+    f = float(n) / 2.0;
+tel
+";
+
+    #[test]
+    fn scopes_follow_calls() {
+        let mut node = compile_node(&with_counter(MAIN), "main");
+        node.start_trace();
+        for (x, c) in [(1, false), (-1, true), (3, false), (4, true)] {
+            node.step(vec![Value::Int(x), Value::Bool(c)]).unwrap();
+        }
+        let mut vcd = vec![];
+        node.take_trace().unwrap().write_vcd(&mut vcd).unwrap();
+        let vcd = String::from_utf8(vcd).unwrap();
+        let lines: Vec<&str> = vcd.lines().skip(2).collect();
+        let minus_one = |id: &str| format!("b{:b} {id}", -1i64 as u64);
+        assert_eq!(
+            lines,
+            [
+                "$scope module main $end",
+                "$var wire 64 ! x $end",
+                "$var wire 1 \" c $end",
+                "$var wire 64 # n $end",
+                "$var real 64 $ f $end",
+                "$scope module counter_0 $end",
+                "$var wire 64 % x $end",
+                "$var wire 64 & n $end",
+                "$var wire 64 ' m $end",
+                "$upscope $end",
+                "$upscope $end",
+                "$enddefinitions $end",
+                "#0",
+                "$dumpvars",
+                "b1 !",
+                "0\"",
+                "b0 #",
+                "r0 $",
+                // The instance is stepped at the next instant
+                "bx %",
+                "bx &",
+                "bx '",
+                "$end",
+                "#1",
+                &minus_one("!"),
+                "1\"",
+                &minus_one("#"),
+                "r-0.5 $",
+                &minus_one("%"),
+                &minus_one("&"),
+                "b0 '",
+                "#2",
+                "b11 !",
+                "0\"",
+                "#3",
+                "b100 !",
+                "1\"",
+                "b11 #",
+                "r1.5 $",
+                "b100 %",
+                "b11 &",
+                &minus_one("'"),
+                "#4",
+            ]
+        );
+    }
+    #[test]
+    fn components_of_arrays_and_tuples() {
+        let value = Value::Tuple(vec![
+            Value::Array(vec![Value::Bool(true), Value::Bool(false)]),
+            Value::Float(1.0),
+        ]);
+        let components = components("x".to_string(), vec![], &value);
+        let names: Vec<&str> = components.iter().map(|(name, ..)| name.as_str()).collect();
+        assert_eq!(names, ["x.0[0]", "x.0[1]", "x.1"]);
+        let (_, path, _) = &components[1];
+        assert_eq!(component(&value, path), Some(&Value::Bool(false)));
+        assert_eq!(identifier(93), "~");
+        assert_eq!(identifier(94), "!\"");
+    }
+    #[test]
+    fn infinite_real() {
+        let ast = checked("node main(x: float) returns (f: float); let f = 1.0 / x; tel");
+        let mut node = compile_node(&ast, "main");
+        node.start_trace();
+        for x in [1.0, 0.0] {
+            node.step(vec![Value::Float(x)]).unwrap();
+        }
+        let mut vcd = vec![];
+        let error = node.take_trace().unwrap().write_vcd(&mut vcd).unwrap_err();
+        assert_eq!(error.to_string(), "f is inf at the step 1");
+    }
+}
@@ -1,6 +1,8 @@
 // Parsers
 pub(crate) mod args;
 pub(crate) mod array;
+pub(crate) mod automaton;
+pub(crate) mod contract;
 pub(crate) mod equation;
 pub(crate) mod expression;
 pub(crate) mod func_call;
@@ -7,7 +7,7 @@ use crate::parser::{
 };
 use nom::Parser;
 use nom::combinator::opt;
-use nom::multi::many0;
+use nom::multi::{many0, many1};
 use nom::sequence::terminated;
 use nom::{IResult, bytes::tag, sequence::separated_pair};
 
@@ -27,6 +27,21 @@ fn arg(input: LSpan) -> IResult<LSpan, (Vec<Ident>, VarType)> {
     .parse(input)
 }
 
+/// Declarations of local variables : `x, y : int; z : bool;`
+pub(crate) fn var_decls(input: LSpan) -> IResult<LSpan, Vec<(Ident, VarType)>> {
+    many1(terminated(ws(arg), ws(tag(";"))))
+        .map(|l| {
+            let mut res = vec![];
+            for (names, t) in l {
+                for name in names {
+                    res.push((name, t.clone()))
+                }
+            }
+            res
+        })
+        .parse(input)
+}
+
 pub(crate) fn args(input: LSpan) -> IResult<LSpan, Vec<(Ident, VarType)>> {
     (many0(terminated(ws(arg), ws(tag(",")))), opt(ws(arg)))
         .map(|(l, v)| {
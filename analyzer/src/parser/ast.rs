@@ -1,14 +1,45 @@
+use crate::ast::integer::Overflow;
 use crate::parser::node::node;
 use crate::parser::parsed_ast::ParsedAst;
 use crate::parser::span::LSpan;
 use crate::parser::white_space::ws;
 use nom::IResult;
 use nom::Parser;
-use nom::combinator::all_consuming;
+use nom::branch::alt;
+use nom::bytes::complete::tag;
+use nom::combinator::{all_consuming, opt, recognize, value};
 use nom::multi::many0;
+use nom::sequence::delimited;
+
+/// `#![overflow(wrapping)]` at the top of the file
+fn overflow_attribute(input: LSpan) -> IResult<LSpan, Overflow> {
+    delimited(
+        (ws(tag("#![")), ws(tag("overflow")), ws(tag("("))),
+        ws(alt((
+            value(Overflow::Wrapping, tag("wrapping")),
+            value(Overflow::Saturating, tag("saturating")),
+            value(Overflow::Trapping, tag("trapping")),
+        ))),
+        (ws(tag(")")), ws(tag("]"))),
+    )
+    .parse(input)
+}
+
+/// `#![no_prelude]` at the top of the file
+fn no_prelude_attribute(input: LSpan) -> IResult<LSpan, LSpan> {
+    recognize((ws(tag("#![")), ws(tag("no_prelude")), ws(tag("]")))).parse(input)
+}
 
 pub fn ast(input: LSpan) -> IResult<LSpan, ParsedAst> {
-    all_consuming(many0(ws(node)))
-        .map(|nodes| ParsedAst { nodes })
-        .parse(input)
+    all_consuming((
+        opt(overflow_attribute),
+        opt(no_prelude_attribute),
+        many0(ws(node)),
+    ))
+    .map(|(overflow, no_prelude, nodes)| ParsedAst {
+        nodes,
+        overflow: overflow.unwrap_or_default(),
+        prelude: no_prelude.is_none(),
+    })
+    .parse(input)
 }
@@ -0,0 +1,122 @@
+use crate::{
+    ast::automaton::{Automaton, State, Transition},
+    parser::{
+        expression::expression,
+        literal::identifier,
+        span::{LSpan, Span},
+        white_space::ws,
+    },
+};
+use nom::{
+    IResult, Parser,
+    branch::alt,
+    bytes::complete::tag,
+    combinator::recognize,
+    multi::many0,
+    sequence::{separated_pair, terminated},
+};
+
+/// `keyword cond restart Target;` or `keyword cond resume Target;`
+fn transition<'a>(
+    keyword: &'static str,
+) -> impl Parser<LSpan<'a>, Output = Transition, Error = nom::error::Error<LSpan<'a>>> {
+    terminated(
+        (
+            ws(tag(keyword).map(Span::from_lspan)),
+            ws(expression),
+            ws(alt((tag("restart"), tag("resume")))).map(Span::from_lspan),
+            ws(identifier),
+        ),
+        ws(tag(";")),
+    )
+    .map(|(span_keyword, cond, span_kind, target)| Transition {
+        span_keyword,
+        cond,
+        restart: span_kind.fragment() == "restart",
+        span_kind,
+        target,
+    })
+}
+
+/// `initial state Name` or `state Name` followed by its transitions and equations
+fn state<'a>(
+    keyword: impl Parser<LSpan<'a>, Output = LSpan<'a>, Error = nom::error::Error<LSpan<'a>>>,
+) -> impl Parser<LSpan<'a>, Output = State, Error = nom::error::Error<LSpan<'a>>> {
+    (
+        ws(keyword.map(Span::from_lspan)),
+        ws(identifier),
+        many0(transition("unless")),
+        ws(tag("let").map(Span::from_lspan)),
+        many0(terminated(
+            separated_pair(ws(identifier), ws(tag("=")), ws(expression)),
+            ws(tag(";")),
+        )),
+        ws(tag("tel").map(Span::from_lspan)),
+        many0(transition("until")),
+    )
+        .map(
+            |(span_state, name, unless, span_let, let_bindings, span_tel, until)| State {
+                span_state,
+                name,
+                unless,
+                span_let,
+                let_bindings,
+                span_tel,
+                until,
+            },
+        )
+}
+
+/// `automaton initial state ... state ... end`, the semicolon is left to the caller
+pub(crate) fn automaton(input: LSpan) -> IResult<LSpan, Automaton> {
+    (
+        ws(tag("automaton").map(Span::from_lspan)),
+        state(recognize((tag("initial"), ws(tag("state"))))),
+        many0(state(tag("state"))),
+        ws(tag("end").map(Span::from_lspan)),
+    )
+        .map(|(span_automaton, initial, others, span_end)| Automaton {
+            span_automaton,
+            states: std::iter::once(initial).chain(others).collect(),
+            span_end,
+        })
+        .parse(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{
+        automaton::automaton,
+        test::{error_test, ok_test},
+    };
+
+    #[test]
+    fn two_states() {
+        ok_test(
+            automaton,
+            "automaton
+                initial state Off
+                    let
+                        y = 0;
+                    tel
+                    until on restart On;
+                state On
+                    unless off resume Off;
+                    let
+                        y = 0 -> pre y + 1;
+                    tel
+            end",
+        );
+    }
+    #[test]
+    fn initial_state_required() {
+        error_test(
+            automaton,
+            "automaton
+                state Off
+                    let
+                    tel
+            end",
+        );
+    }
+}
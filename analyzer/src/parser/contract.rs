@@ -0,0 +1,115 @@
+use crate::{
+    ast::contract::{Clause, Contract, Mode},
+    parser::{
+        expression::expression,
+        literal::identifier,
+        span::{LSpan, Span},
+        white_space::ws,
+    },
+};
+use nom::{
+    IResult, Parser,
+    branch::alt,
+    bytes::complete::tag,
+    multi::many0,
+    sequence::{delimited, terminated},
+};
+
+/// `keyword expr;`
+fn clause<'a>(
+    keyword: &'static str,
+) -> impl Parser<LSpan<'a>, Output = Clause, Error = nom::error::Error<LSpan<'a>>> {
+    terminated(
+        (ws(tag(keyword).map(Span::from_lspan)), ws(expression)),
+        ws(tag(";")),
+    )
+}
+
+/// `mode name ( require expr; ensure expr; );`
+fn mode(input: LSpan) -> IResult<LSpan, Mode> {
+    terminated(
+        (
+            ws(tag("mode").map(Span::from_lspan)),
+            ws(identifier),
+            delimited(
+                ws(tag("(")),
+                (many0(clause("require")), many0(clause("ensure"))),
+                ws(tag(")")),
+            ),
+        ),
+        ws(tag(";")),
+    )
+    .map(|(span_mode, name, (requires, ensures))| Mode {
+        span_mode,
+        name,
+        requires,
+        ensures,
+    })
+    .parse(input)
+}
+
+enum Item {
+    Assume(Clause),
+    Guarantee(Clause),
+    Mode(Mode),
+}
+
+/// `(*@contract ... *)` between the signature and the body of a node
+pub(crate) fn contract(input: LSpan) -> IResult<LSpan, Contract> {
+    (
+        ws(tag("(*@contract").map(Span::from_lspan)),
+        many0(alt((
+            clause("assume").map(Item::Assume),
+            clause("guarantee").map(Item::Guarantee),
+            mode.map(Item::Mode),
+        ))),
+        ws(tag("*)")),
+    )
+        .map(|(span_contract, items, _)| {
+            let mut contract = Contract {
+                span_contract,
+                assumes: vec![],
+                guarantees: vec![],
+                modes: vec![],
+            };
+            for item in items {
+                match item {
+                    Item::Assume(clause) => contract.assumes.push(clause),
+                    Item::Guarantee(clause) => contract.guarantees.push(clause),
+                    Item::Mode(mode) => contract.modes.push(mode),
+                }
+            }
+            contract
+        })
+        .parse(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{contract::contract, test::ok_test};
+
+    #[test]
+    fn assume_guarantee() {
+        ok_test(
+            contract,
+            "(*@contract
+                assume x != 0;
+                guarantee y == x * x;
+            *)",
+        );
+    }
+    #[test]
+    fn modes() {
+        ok_test(
+            contract,
+            "(*@contract
+                guarantee true;
+                mode up (
+                    require x == 1;
+                    ensure y == 2;
+                );
+                mode empty ();
+            *)",
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{
-    ast::expression::Expr,
+    ast::{automaton::Automaton, expression::Expr},
     parser::{
+        automaton::automaton,
         expression::expression,
         literal::identifier,
         span::{Ident, LSpan, Span},
@@ -41,15 +42,51 @@ pub(crate) fn equation(input: LSpan) -> IResult<LSpan, (Vec<Ident>, Expr)> {
     .parse(input)
 }
 
-pub(crate) fn equations(input: LSpan) -> IResult<LSpan, (Vec<(Vec<Ident>, Expr)>, Vec<Span>)> {
+/// `assert expr`, returns the span of the keyword
+pub(crate) fn assertion(input: LSpan) -> IResult<LSpan, (Span, Expr)> {
+    (ws(tag("assert").map(Span::from_lspan)), ws(expression)).parse(input)
+}
+
+enum Statement {
+    /// Equation and its semicolon
+    Equation((Vec<Ident>, Expr), Span),
+    Assert((Span, Expr)),
+    Automaton(Automaton),
+}
+
+/// Equations, assertions and automata of a node, with the semicolons of the equations
+#[allow(clippy::type_complexity)]
+pub(crate) fn equations(
+    input: LSpan,
+) -> IResult<
+    LSpan,
+    (
+        Vec<(Vec<Ident>, Expr)>,
+        Vec<Span>,
+        Vec<(Span, Expr)>,
+        Vec<Automaton>,
+    ),
+> {
     fold(
         0..,
-        (ws(equation), ws(tag(";"))),
-        || (Vec::new(), Vec::new()),
-        |(mut acc, mut acc_span), ((name, expr), semi_colon_span)| {
-            acc.push((name, expr));
-            acc_span.push(Span::from_lspan(semi_colon_span));
-            (acc, acc_span)
+        alt((
+            terminated(ws(assertion), ws(tag(";"))).map(Statement::Assert),
+            terminated(ws(automaton), ws(tag(";"))).map(Statement::Automaton),
+            (ws(equation), ws(tag(";"))).map(|(equation, semicolon)| {
+                Statement::Equation(equation, Span::from_lspan(semicolon))
+            }),
+        )),
+        || (Vec::new(), Vec::new(), Vec::new(), Vec::new()),
+        |(mut acc, mut acc_span, mut acc_assert, mut acc_automaton), statement| {
+            match statement {
+                Statement::Equation(equation, semicolon) => {
+                    acc.push(equation);
+                    acc_span.push(semicolon);
+                }
+                Statement::Assert(assert) => acc_assert.push(assert),
+                Statement::Automaton(automaton) => acc_automaton.push(automaton),
+            }
+            (acc, acc_span, acc_assert, acc_automaton)
         },
     )
     .parse(input)
@@ -72,6 +109,31 @@ mod tests {
         ok_test(equations, "");
     }
     #[test]
+    fn assertions() {
+        ok_test(
+            equations,
+            "assert x != 0;
+            y = 10 / x;
+            assert (y == 1) or (y == 2);
+            ",
+        );
+    }
+    #[test]
+    fn automata() {
+        ok_test(
+            equations,
+            "x = 1;
+            automaton
+                initial state A
+                    let
+                        y = x;
+                    tel
+            end;
+            z = y;
+            ",
+        );
+    }
+    #[test]
     fn no_end_comma() {
         // does not manage to parse but does not crash !
         ok_test(equations, "x=5");
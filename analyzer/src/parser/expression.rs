@@ -2,6 +2,7 @@ use crate::{
     ast::{
         binop::BinOp,
         expression::{Expr, Precedence},
+        integer::IntKind,
         literal::Value,
         unary_op::UnaryOp,
     },
@@ -9,9 +10,10 @@ use crate::{
         array::array,
         func_call::func_call,
         if_then_else::ifthenelse,
-        literal::{identifier, literal},
+        literal::{identifier, keyword, literal},
         span::{LSpan, Span},
         tuple::tuple,
+        var_type::int_kind,
         white_space::ws,
     },
 };
@@ -19,17 +21,72 @@ use nom::{
     IResult, Parser,
     branch::alt,
     bytes::complete::tag,
-    combinator::{fail, map, opt, value},
-    sequence::delimited,
+    combinator::{fail, map, opt, recognize, value},
+    sequence::{delimited, separated_pair},
 };
 use nom_language::precedence::{Assoc, Operation, binary_op, precedence, unary_op};
 
+/// Numeric operators written like a function call : `float(x)`
+fn function_like_op(input: LSpan) -> IResult<LSpan, Expr> {
+    (
+        ws(alt((
+            recognize(int_kind),
+            tag("float"),
+            tag("int"),
+            tag("floor"),
+            tag("round"),
+            tag("abs"),
+            tag("len"),
+        ))),
+        delimited(ws(tag("(")), ws(expression), ws(tag(")"))),
+    )
+        .map_opt(|(op, rhs)| {
+            use UnaryOp::*;
+            let unary_op = match *op.fragment() {
+                "float" => ToFloat,
+                "int" => ToInt,
+                "floor" => Floor,
+                "round" => Round,
+                "abs" => Abs,
+                "len" => Len,
+                name => ToSized(IntKind::ALL.into_iter().find(|k| k.to_string() == name)?),
+            };
+            Some(Expr::UnaryOp {
+                op: unary_op,
+                span_op: Span::from_lspan(op),
+                rhs: Box::new(rhs),
+            })
+        })
+        .parse(input)
+}
+
+/// Binary operators written like a function call : `bit(x, i)`
+fn function_like_binop(input: LSpan) -> IResult<LSpan, Expr> {
+    (
+        ws(tag("bit")),
+        delimited(
+            ws(tag("(")),
+            separated_pair(ws(expression), ws(tag(",")), ws(expression)),
+            ws(tag(")")),
+        ),
+    )
+        .map(|(op, (lhs, rhs))| Expr::BinOp {
+            lhs: Box::new(lhs),
+            op: BinOp::Bit,
+            span_op: Span::from_lspan(op),
+            rhs: Box::new(rhs),
+        })
+        .parse(input)
+}
+
 fn operand(input: LSpan) -> IResult<LSpan, Expr> {
     map(
         (
             alt((
                 // value(Expr::Lit(Value::Unit), (ws(tag("(")), ws(tag(")")))),
                 delimited(ws(tag("(")), ws(expression), ws(tag(")"))),
+                function_like_op,
+                function_like_binop,
                 map(array, Expr::Array),
                 map(tuple, Expr::Tuple),
                 map(ifthenelse, |(cond, yes, no)| Expr::If {
@@ -62,6 +119,13 @@ pub(crate) fn expression(input: LSpan) -> IResult<LSpan, Expr> {
             unary_op(UnaryOp::Inv.precedence(), ws(tag("-"))),
             unary_op(UnaryOp::Pre.precedence(), ws(tag("pre"))),
             unary_op(UnaryOp::Not.precedence(), ws(tag("not"))),
+            unary_op(UnaryOp::Lnot.precedence(), ws(keyword("lnot"))),
+            unary_op(UnaryOp::Once.precedence(), ws(keyword("once"))),
+            unary_op(
+                UnaryOp::Historically.precedence(),
+                ws(keyword("historically")),
+            ),
+            unary_op(UnaryOp::Yesterday.precedence(), ws(keyword("yesterday"))),
         )),
         fail(),
         alt((
@@ -77,6 +141,13 @@ pub(crate) fn expression(input: LSpan) -> IResult<LSpan, Expr> {
             binary_op(Or.precedence(), Assoc::Left, ws(tag("or"))),
             binary_op(And.precedence(), Assoc::Left, ws(tag("and"))),
             binary_op(Caret.precedence(), Assoc::Left, ws(tag("^"))),
+            binary_op(Land.precedence(), Assoc::Left, ws(tag("land"))),
+            binary_op(Lor.precedence(), Assoc::Left, ws(tag("lor"))),
+            binary_op(Lxor.precedence(), Assoc::Left, ws(tag("lxor"))),
+            binary_op(Shl.precedence(), Assoc::Left, ws(tag("shl"))),
+            binary_op(Shr.precedence(), Assoc::Left, ws(tag("shr"))),
+            binary_op(Since.precedence(), Assoc::Left, ws(tag("since"))),
+            binary_op(Triggered.precedence(), Assoc::Left, ws(tag("triggered"))),
         )),
         operand,
         |op: Operation<LSpan, LSpan, LSpan, Expr>| {
@@ -96,6 +167,13 @@ pub(crate) fn expression(input: LSpan) -> IResult<LSpan, Expr> {
                         "or" => Or,
                         "and" => And,
                         "^" => Caret,
+                        "land" => Land,
+                        "lor" => Lor,
+                        "lxor" => Lxor,
+                        "shl" => Shl,
+                        "shr" => Shr,
+                        "since" => Since,
+                        "triggered" => Triggered,
                         _ => return Err("Non supported binary operation"),
                     };
                     Ok(Expr::BinOp {
@@ -111,6 +189,10 @@ pub(crate) fn expression(input: LSpan) -> IResult<LSpan, Expr> {
                         "pre" => Pre,
                         "-" => Inv,
                         "not" => Not,
+                        "lnot" => Lnot,
+                        "once" => Once,
+                        "historically" => Historically,
+                        "yesterday" => Yesterday,
                         _ => return Err("Non supported unary operator"),
                     };
                     Ok(Expr::UnaryOp {
@@ -127,15 +209,54 @@ pub(crate) fn expression(input: LSpan) -> IResult<LSpan, Expr> {
 
 #[cfg(test)]
 mod tests {
-    use crate::parser::{
-        expression::expression,
-        test::{error_test, ok_test},
+    use crate::{
+        ast::expression::Expr,
+        parser::{
+            expression::expression,
+            span::LSpan,
+            test::{error_test, ok_test},
+        },
     };
 
+    /// Whether `input` parses as a single variable, up to the `;`
+    fn is_variable(input: &str) -> bool {
+        matches!(expression(LSpan::new(input)), Ok((rest, Expr::Variable(_))) if *rest == ";")
+    }
+
     #[test]
     fn basic_addition() {
         ok_test(expression, "a + 2");
         ok_test(expression, " abc + 2");
         error_test(expression, "a + ");
     }
+
+    #[test]
+    fn numeric_conversions() {
+        ok_test(expression, "float(a) + abs(b - 1)");
+        ok_test(expression, "round(floor(1.5) * int(x))");
+        ok_test(expression, "absolute + integer + 2");
+        ok_test(expression, "uint8(x) + int16(3)");
+        ok_test(expression, "len(\"state: \" + name) + 1");
+        error_test(expression, "float(a, b) +");
+    }
+
+    #[test]
+    fn bitwise_operators() {
+        ok_test(expression, "a land b lor c lxor 15");
+        ok_test(expression, "lnot x shl 2 shr 1");
+        ok_test(expression, "bit(x land 6, 2) and not bit(y, 0) or b == 0");
+        error_test(expression, "bit(x) +");
+        assert!(is_variable("lnot_mask;"));
+        assert!(is_variable("lnotx;"));
+    }
+
+    #[test]
+    fn past_temporal_operators() {
+        ok_test(expression, "yesterday a or once (b == 1)");
+        ok_test(expression, "historically (a since b) and (c triggered d)");
+        ok_test(expression, "count_since(x) == 0");
+        assert!(is_variable("once_flag;"));
+        assert!(is_variable("historically_ok;"));
+        assert!(is_variable("yesterday_v;"));
+    }
 }
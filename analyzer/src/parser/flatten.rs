@@ -7,6 +7,9 @@ impl ParsedAst {
     pub fn flatten(self) -> Ast {
         Ast {
             nodes: self.nodes.into_iter().map(|node| node.flatten()).collect(),
+            overflow: self.overflow,
+            prelude: self.prelude,
+            prelude_len: 0,
             types: AstTypes::default(),
         }
     }
@@ -20,18 +23,29 @@ impl ParsedNode {
             span_let,
             span_tel,
             span_semicolon,
+            span_var,
             tag,
+            allow,
             name,
             inputs,
             vars,
             outputs,
             let_bindings,
             span_semicolon_equations,
+            asserts,
+            contract,
+            automata,
         } = self;
         let mut new_let_bindings = vec![];
-        for (names, expr) in let_bindings.into_iter() {
+        // Kept parallel to `new_let_bindings`: every equation coming from
+        // a tuple equation shares the semicolon of the tuple equation.
+        let mut new_span_semicolon_equations = vec![];
+        for ((names, expr), span_semicolon) in
+            let_bindings.into_iter().zip(span_semicolon_equations)
+        {
             if names.len() == 1 {
                 new_let_bindings.push((names[0].clone(), expr));
+                new_span_semicolon_equations.push(span_semicolon);
             } else {
                 for (index, name) in names.into_iter().enumerate() {
                     new_let_bindings.push((
@@ -40,7 +54,8 @@ impl ParsedNode {
                             index: Box::new(Expr::Lit(Value::Int(index as i64))),
                             expr: Box::new(expr.clone()),
                         },
-                    ))
+                    ));
+                    new_span_semicolon_equations.push(span_semicolon.clone());
                 }
             }
         }
@@ -50,13 +65,18 @@ impl ParsedNode {
             span_let,
             span_tel,
             span_semicolon,
+            span_var,
             tag,
+            allow,
             name,
             inputs,
             vars,
             outputs,
             let_bindings: new_let_bindings,
-            span_semicolon_equations,
+            span_semicolon_equations: new_span_semicolon_equations,
+            asserts,
+            contract,
+            automata,
         }
     }
 }
@@ -9,7 +9,8 @@ use nom::{
     character::complete::alpha1,
     character::complete::alphanumeric1,
     character::complete::digit1,
-    character::complete::{char, one_of},
+    character::complete::{char, none_of, one_of},
+    combinator::not,
     combinator::opt,
     combinator::recognize,
     combinator::value,
@@ -17,6 +18,7 @@ use nom::{
     multi::many0_count,
     multi::many1,
     multi::many1_count,
+    sequence::delimited,
     sequence::pair,
     sequence::preceded,
     sequence::terminated,
@@ -31,6 +33,13 @@ pub(crate) fn identifier(input: LSpan) -> IResult<LSpan, Span> {
     .parse(input)
 }
 
+/// The keyword `word`, which must not be the start of an identifier
+pub(crate) fn keyword<'a>(
+    word: &'static str,
+) -> impl Parser<LSpan<'a>, Output = LSpan<'a>, Error = nom::error::Error<LSpan<'a>>> {
+    terminated(tag(word), not(alt((alphanumeric1, tag("_")))))
+}
+
 pub(crate) fn integer(input: LSpan) -> IResult<LSpan, i64> {
     recognize(many1_count(digit1))
         .map_res(|s: LSpan| s.parse::<i64>())
@@ -65,6 +74,40 @@ fn float(input: LSpan) -> IResult<LSpan, f64> {
     .parse(input)
 }
 
+/// Character of a char or string literal delimited by `quote`,
+/// with the escapes `\\`, `\n`, `\t` and `\` followed by a quote.
+fn quoted_char(quote: char) -> impl Fn(LSpan) -> IResult<LSpan, char> {
+    move |input| {
+        alt((
+            preceded(
+                char('\\'),
+                alt((
+                    value('\\', char('\\')),
+                    value('\n', char('n')),
+                    value('\t', char('t')),
+                    value('\'', char('\'')),
+                    value('"', char('"')),
+                )),
+            ),
+            none_of(&[quote, '\\', '\n'][..]),
+        ))
+        .parse(input)
+    }
+}
+
+fn char_literal(input: LSpan) -> IResult<LSpan, char> {
+    delimited(char('\''), quoted_char('\''), char('\'')).parse(input)
+}
+
+fn string_literal(input: LSpan) -> IResult<LSpan, String> {
+    delimited(
+        char('"'),
+        many0(quoted_char('"')).map(|chars| chars.into_iter().collect()),
+        char('"'),
+    )
+    .parse(input)
+}
+
 fn unit(input: LSpan) -> IResult<LSpan, ()> {
     value((), (ws(tag("(")), ws(tag(")")))).parse(input)
 }
@@ -75,6 +118,8 @@ pub(crate) fn literal(input: LSpan) -> IResult<LSpan, Value> {
         float.map(Value::Float),
         integer.map(Value::Int),
         bool_parse.map(Value::Bool),
+        char_literal.map(Value::Char),
+        string_literal.map(Value::String),
     ))
     .parse(input)
 }
@@ -82,7 +127,7 @@ pub(crate) fn literal(input: LSpan) -> IResult<LSpan, Value> {
 #[cfg(test)]
 mod tests {
     use crate::parser::{
-        literal::{bool_parse, identifier, integer, literal},
+        literal::{bool_parse, char_literal, identifier, integer, literal, string_literal},
         test::{error_test, ok_test},
         white_space::ws,
     };
@@ -149,4 +194,14 @@ mod tests {
         ok_test(literal, "0.2345");
         error_test(literal, "abc0.2");
     }
+
+    #[test]
+    fn char_and_string_literal() {
+        ok_test(char_literal, "'a'");
+        ok_test(char_literal, "'\\''");
+        ok_test(string_literal, "\"overheat: \\\"engine\\\"\\n\"");
+        ok_test(literal, "\"\"");
+        error_test(char_literal, "'ab'");
+        error_test(string_literal, "\"unterminated");
+    }
 }
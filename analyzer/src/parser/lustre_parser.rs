@@ -4,12 +4,12 @@ use crate::parser::ast::ast;
 use crate::parser::span::LSpan;
 use lsp_types::{Diagnostic, DiagnosticSeverity, Position, Range};
 
-/// Parse a given string into a complete AST
+/// Parse a given string into a complete AST, preceded by the prelude
 pub fn lustre_parse(input: &str) -> Result<Ast, Vec<Diagnostic>> {
     match ast(LSpan::new(input)) {
         Ok((rest, ast)) => {
             if rest.is_empty() {
-                Ok(ast.flatten())
+                Ok(ast.flatten().load_prelude())
             } else {
                 Err(vec![Diagnostic {
                     range: Range {
@@ -1,5 +1,6 @@
 use crate::ast::ftag::Tag;
-use crate::parser::args::args;
+use crate::parser::args::{args, var_decls};
+use crate::parser::contract::contract;
 use crate::parser::equation::equations;
 use crate::parser::literal::identifier;
 use crate::parser::parsed_node::ParsedNode;
@@ -14,12 +15,30 @@ use nom::branch::alt;
 use nom::bytes::complete::tag;
 use nom::combinator::opt;
 use nom::combinator::recognize;
+use nom::multi::{many0, separated_list1};
 use nom::sequence::delimited;
 
+/// Attribute `#[allow(lint, ...)]`, returns the names of the allowed lints.
+fn allow_attribute(input: LSpan) -> IResult<LSpan, Vec<Span>> {
+    delimited(
+        (ws(tag("#[")), ws(tag("allow")), ws(tag("("))),
+        separated_list1(ws(tag(",")), ws(identifier)),
+        (ws(tag(")")), tag("]")),
+    )
+    .parse(input)
+}
+
 pub(crate) fn node(input: LSpan) -> IResult<LSpan, ParsedNode> {
     (
         (
-            opt(ws(tag("#[test]"))).map(|t| t.map(|s| (Span::from_lspan(s), Tag::Test))),
+            (
+                many0(ws(allow_attribute)),
+                opt(ws(tag("#[test]"))).map(|t| t.map(|s| (Span::from_lspan(s), Tag::Test))),
+                many0(ws(allow_attribute)),
+            )
+                .map(|(before, tag, after)| {
+                    (tag, before.into_iter().chain(after).flatten().collect())
+                }),
             ws(tag("node").map(|s| Span::from_lspan(s))),
             ws(identifier),
             alt((
@@ -39,6 +58,8 @@ pub(crate) fn node(input: LSpan) -> IResult<LSpan, ParsedNode> {
             ws(tag(";")).map(|s| Span::from_lspan(s)),
         ),
         (
+            opt(ws(contract)),
+            opt((ws(tag("var").map(|s| Span::from_lspan(s))), ws(var_decls))),
             ws(tag("let").map(|s| Span::from_lspan(s))),
             ws(equations),
             ws(tag("tel").map(|s| Span::from_lspan(s))),
@@ -46,21 +67,36 @@ pub(crate) fn node(input: LSpan) -> IResult<LSpan, ParsedNode> {
     )
         .map(
             |(
-                (tag, span_node, name, inputs, span_returns, outputs, span_semicolon),
-                (span_let, (let_bindings, span_semicolon_equations), span_tel),
+                ((tag, allow), span_node, name, inputs, span_returns, outputs, span_semicolon),
+                (
+                    contract,
+                    var_section,
+                    span_let,
+                    (let_bindings, span_semicolon_equations, asserts, automata),
+                    span_tel,
+                ),
             )| {
+                let (span_var, vars) = match var_section {
+                    Some((span_var, vars)) => (Some(span_var), vars),
+                    None => (None, vec![]),
+                };
                 ParsedNode {
                     tag,
+                    allow,
                     name,
-                    vars: vec![],
+                    vars,
                     inputs,
                     outputs,
                     let_bindings,
                     span_semicolon_equations,
+                    asserts,
+                    contract,
+                    automata,
 
                     span_node,
                     span_returns,
                     span_semicolon,
+                    span_var,
                     span_let,
                     span_tel,
                 }
@@ -123,4 +159,24 @@ mod tests {
             ",
         );
     }
+    #[test]
+    fn attributes_node() {
+        ok_test(
+            node,
+            "#[allow(unused)] #[test]
+            #[allow(redundant_arrow, constant_condition)]
+            node f() returns ();
+            let
+            tel
+            ",
+        );
+        error_test(
+            node,
+            "#[allow()]
+            node f() returns ();
+            let
+            tel
+            ",
+        );
+    }
 }
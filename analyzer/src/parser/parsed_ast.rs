@@ -1,12 +1,20 @@
-use crate::parser::parsed_node::ParsedNode;
+use crate::{ast::integer::Overflow, parser::parsed_node::ParsedNode};
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct ParsedAst {
     pub(crate) nodes: Vec<ParsedNode>,
+    pub(crate) overflow: Overflow,
+    pub(crate) prelude: bool,
 }
 
 impl std::fmt::Display for ParsedAst {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.overflow != Overflow::default() {
+            writeln!(f, "#![overflow({})]\n", self.overflow)?;
+        }
+        if !self.prelude {
+            writeln!(f, "#![no_prelude]\n")?;
+        }
         for (i, node) in self.nodes.iter().enumerate() {
             write!(f, "{node}")?;
             if i != self.nodes.len() - 1 {
@@ -1,4 +1,4 @@
-use crate::ast::{expression::Expr, ftag::Tag};
+use crate::ast::{automaton::Automaton, contract::Contract, expression::Expr, ftag::Tag};
 use crate::parser::{span::Ident, span::Span, var_type::InnerVarType, var_type::VarType};
 
 #[derive(Clone, Debug, PartialEq)]
@@ -8,8 +8,11 @@ pub(crate) struct ParsedNode {
     pub(crate) span_let: Span,
     pub(crate) span_tel: Span,
     pub(crate) span_semicolon: Span,
+    pub(crate) span_var: Option<Span>,
 
     pub(crate) tag: Option<(Span, Tag)>,
+    /// Lints allowed with `#[allow(...)]`
+    pub(crate) allow: Vec<Span>,
     pub(crate) name: Span,
     pub(crate) inputs: Vec<(Ident, VarType)>,
     pub(crate) vars: Vec<(Ident, VarType)>,
@@ -22,6 +25,10 @@ pub(crate) struct ParsedNode {
     /// ```  
     pub(crate) let_bindings: Vec<(Vec<Ident>, Expr)>,
     pub(crate) span_semicolon_equations: Vec<Span>,
+    /// `assert expr;` with the span of the keyword
+    pub(crate) asserts: Vec<(Span, Expr)>,
+    pub(crate) contract: Option<Contract>,
+    pub(crate) automata: Vec<Automaton>,
 }
 
 impl std::fmt::Display for ParsedNode {
@@ -29,6 +36,10 @@ impl std::fmt::Display for ParsedNode {
         if let Some((_, t)) = &self.tag {
             writeln!(f, "#[{t}]")?;
         }
+        if !self.allow.is_empty() {
+            let lints: Vec<String> = self.allow.iter().map(|lint| lint.fragment()).collect();
+            writeln!(f, "#[allow({})]", lints.join(", "))?;
+        }
         write!(f, "node {}(", self.name)?;
         if self.inputs.len() != 1 || self.inputs[0].1.inner != InnerVarType::Unit {
             for (i, (s, t)) in self.inputs.iter().enumerate() {
@@ -47,7 +58,16 @@ impl std::fmt::Display for ParsedNode {
         }
 
         writeln!(f, ");")?;
+        if let Some(contract) = &self.contract {
+            writeln!(f, "{contract}")?;
+        }
 
+        if !self.vars.is_empty() {
+            writeln!(f, "var")?;
+            for (s, t) in self.vars.iter() {
+                writeln!(f, "\t{s} : {t};")?;
+            }
+        }
         writeln!(f, "let")?;
         for (s, e) in self.let_bindings.iter() {
             if s.len() == 0 {
@@ -63,6 +83,12 @@ impl std::fmt::Display for ParsedNode {
                 writeln!(f, ")= {e};")?;
             }
         }
+        for automaton in self.automata.iter() {
+            writeln!(f, "{automaton}")?;
+        }
+        for (_, e) in self.asserts.iter() {
+            writeln!(f, "\tassert {e};")?;
+        }
         write!(f, "tel")
     }
 }
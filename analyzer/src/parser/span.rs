@@ -4,13 +4,14 @@ use crate::ast::{
 };
 use lsp_types::{Position, Range, SemanticToken};
 use nom_locate::LocatedSpan;
+use serde_derive::{Deserialize, Serialize};
 use std::cmp::Ordering;
 
 pub type LSpan<'a> = LocatedSpan<&'a str>;
 
 pub(crate) type Ident = Span;
 
-#[derive(Debug, Clone, Default, Eq)]
+#[derive(Debug, Clone, Default, Eq, Serialize, Deserialize)]
 pub struct Span {
     /// The offset represents the position of the fragment relatively to
     /// the input of the parser. It starts at offset 0.
@@ -121,14 +122,8 @@ impl Merge for Range {
             self
         } else {
             Range {
-                start: Position {
-                    line: self.start.line.min(other.start.line),
-                    character: self.start.character.min(other.start.character),
-                },
-                end: Position {
-                    line: self.start.line.max(other.start.line),
-                    character: self.start.character.max(other.start.character),
-                },
+                start: self.start.min(other.start),
+                end: self.end.max(other.end),
             }
         }
     }
@@ -233,13 +233,18 @@ fn node_new(input: &mut Tokens) -> Result<Node, ParseError> {
             span_let: todo!(),
             span_tel: todo!(),
             span_semicolon: todo!(),
+            span_var: todo!(),
             tag: todo!(),
+            allow: todo!(),
             name: todo!(),
             inputs: todo!(),
             vars: todo!(),
             outputs: todo!(),
             let_bindings: todo!(),
             span_semicolon_equations: todo!(),
+            asserts: todo!(),
+            contract: todo!(),
+            automata: todo!(),
         },
     )
     .parse(input)
@@ -248,6 +253,9 @@ fn ast_new(input: &mut Tokens) -> Result<Ast, ParseError> {
     node_new
         .map_parse(|node| Ast {
             nodes: vec![node],
+            overflow: Default::default(),
+            prelude: true,
+            prelude_len: 0,
             types: AstTypes::new(),
         })
         .parse(input)
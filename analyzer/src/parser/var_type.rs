@@ -1,5 +1,6 @@
 use std::ops::Index;
 
+use crate::ast::integer::IntKind;
 use crate::checker::infer_types::InferLen;
 use crate::parser::literal::integer;
 use crate::parser::span::LSpan;
@@ -9,18 +10,24 @@ use nom::branch::alt;
 use nom::bytes::complete::tag;
 use nom::combinator::value;
 use nom::{IResult, Parser};
+use serde_derive::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum InnerVarType {
     Unit,
     Int,
+    /// Sized integer like `int8` or `uint16`
+    SizedInt(IntKind),
     Float,
     Bool,
     Char,
     String,
     // Maybe this should be modified
     Tuple(Vec<InnerVarType>),
-    Array { t: Box<InnerVarType>, len: InferLen },
+    Array {
+        t: Box<InnerVarType>,
+        len: InferLen,
+    },
 }
 
 impl InnerVarType {
@@ -42,9 +49,50 @@ impl InnerVarType {
             _ => None,
         }
     }
+    /// Check that `f` holds for every scalar type inside this type
+    /// (elements of arrays and tuples included).
+    pub fn all_scalars(&self, f: impl Fn(&InnerVarType) -> bool + Copy) -> bool {
+        match self {
+            InnerVarType::Tuple(v) => v.iter().all(|t| t.all_scalars(f)),
+            InnerVarType::Array { t, len: _ } => t.all_scalars(f),
+            t => f(t),
+        }
+    }
+    /// Kind of an integer type, `int` being a 64 bits integer
+    pub fn int_kind(&self) -> Option<IntKind> {
+        match self {
+            InnerVarType::Int => Some(IntKind::Int64),
+            InnerVarType::SizedInt(kind) => Some(*kind),
+            _ => None,
+        }
+    }
+    /// `int` and sized integers
+    pub fn is_integer(&self) -> bool {
+        self.int_kind().is_some()
+    }
+    /// `int`, sized integers and `float`
+    pub fn is_numeric(&self) -> bool {
+        matches!(
+            self,
+            InnerVarType::Int | InnerVarType::SizedInt(_) | InnerVarType::Float
+        )
+    }
+    /// Replace every scalar type inside this type by `f` of it.
+    pub fn map_scalars(&self, f: impl Fn(&InnerVarType) -> InnerVarType + Copy) -> InnerVarType {
+        match self {
+            InnerVarType::Tuple(v) => {
+                InnerVarType::Tuple(v.iter().map(|t| t.map_scalars(f)).collect())
+            }
+            InnerVarType::Array { t, len } => InnerVarType::Array {
+                t: Box::new(t.map_scalars(f)),
+                len: len.clone(),
+            },
+            t => f(t),
+        }
+    }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct VarType {
     pub inner: InnerVarType,
     pub initialized: bool,
@@ -56,7 +104,7 @@ impl VarType {
         Some(Self {
             initialized: self.initialized,
             inner: match &self.inner {
-                Unit | Int | Float | Bool | Char | String => return None,
+                Unit | Int | SizedInt(_) | Float | Bool | Char | String => return None,
                 Tuple(inner_var_types) => {
                     let index = if index > 0 {
                         index
@@ -174,6 +222,7 @@ impl std::fmt::Display for InnerVarType {
         match self {
             InnerVarType::Unit => write!(f, "()"),
             InnerVarType::Int => write!(f, "int"),
+            InnerVarType::SizedInt(kind) => write!(f, "{kind}"),
             InnerVarType::Float => write!(f, "float"),
             InnerVarType::Bool => write!(f, "bool"),
             InnerVarType::Char => write!(f, "char"),
@@ -210,8 +259,23 @@ impl VarType {
     }
 }
 
+/// Sized integer type like `int8`, `int` alone is not a sized integer.
+pub(crate) fn int_kind(input: LSpan) -> IResult<LSpan, IntKind> {
+    alt((
+        value(IntKind::Int8, tag("int8")),
+        value(IntKind::Int16, tag("int16")),
+        value(IntKind::Int32, tag("int32")),
+        value(IntKind::Int64, tag("int64")),
+        value(IntKind::UInt8, tag("uint8")),
+        value(IntKind::UInt16, tag("uint16")),
+        value(IntKind::UInt32, tag("uint32")),
+    ))
+    .parse(input)
+}
+
 pub(crate) fn simple_inner_var_type(input: LSpan) -> IResult<LSpan, InnerVarType> {
     ws(alt((
+        int_kind.map(InnerVarType::SizedInt),
         value(InnerVarType::Int, tag("int")),
         value(InnerVarType::Float, alt((tag("float"), tag("real")))),
         value(InnerVarType::Char, tag("char")),
@@ -1,8 +1,17 @@
 use crate::{
-    ast::double_visitor::{DoubleTogetherVisitor, ShallowEq},
-    parser::{ast::ast, lustre_parser::lustre_parse, span::LSpan},
+    ast::{
+        ast::Ast,
+        double_visitor::{DoubleTogetherVisitor, ShallowEq},
+    },
+    interpreter::compiled_node::CompiledNode,
+    parser::{
+        ast::ast,
+        lustre_parser::lustre_parse,
+        span::{LSpan, Span},
+    },
 };
 use colored::Colorize;
+use lsp_types::{Diagnostic, NumberOrString};
 use serde_derive::{Deserialize, Serialize};
 use std::io::Write;
 use test_each_file::test_each_file;
@@ -20,6 +29,10 @@ struct TestInfo {
     parse: Option<TestType>,
     check: Option<TestType>,
     test: Option<TestType>,
+    /// Codes of the diagnostics expected when the check fails
+    diagnostics: Option<Vec<String>>,
+    /// Names of the lints expected when the check passes
+    lints: Option<Vec<String>>,
 }
 fn test([lustre_file, json_info]: [&str; 2]) {
     use TestType::*;
@@ -31,6 +44,9 @@ fn test([lustre_file, json_info]: [&str; 2]) {
             match test_info.check {
                 Some(Pass) => {
                     ok_check(lustre_file);
+                    if let Some(lints) = &test_info.lints {
+                        lint_codes_check(lustre_file, lints);
+                    }
                     match test_info.test {
                         Some(Pass) => {
                             ok_interpretation(lustre_file);
@@ -43,6 +59,9 @@ fn test([lustre_file, json_info]: [&str; 2]) {
                 }
                 Some(Fail) => {
                     error_check(lustre_file);
+                    if let Some(codes) = &test_info.diagnostics {
+                        diagnostic_codes_check(lustre_file, codes);
+                    }
                 }
                 None => {}
             }
@@ -100,6 +119,34 @@ pub fn ok_check(input: &str) {
     }
 }
 
+/// Program without diagnostic from the type checker, for the tests running it
+pub fn checked(input: &str) -> Ast {
+    let mut ast = lustre_parse(input).unwrap();
+    let (diags, _) = ast.check();
+    assert!(diags.is_empty(), "{diags:#?}");
+    ast
+}
+
+/// Counter node called by the `main` nodes of the interpreter tests
+pub const COUNTER: &str = "node counter(x: int) returns (n: int);
+var
+    m: int;
+let
+    m = 0 -> pre n;
+    n = m + x;
+tel
+";
+
+/// The checked program made of the counter followed by `nodes`
+pub fn with_counter(nodes: &str) -> Ast {
+    checked(&format!("{COUNTER}\n{nodes}"))
+}
+
+/// Compile the node `name` of a checked program
+pub fn compile_node(ast: &Ast, name: &str) -> CompiledNode {
+    ast.compile(Span::new(0, 0, name.to_string()))
+}
+
 pub fn error_check(input: &str) {
     ok_parse(input);
     let _ = std::io::stdout().flush();
@@ -115,6 +162,58 @@ pub fn error_check(input: &str) {
     }
 }
 
+/// Codes of the diagnostics, in their order
+fn diagnostics_codes(diags: &[Diagnostic]) -> Vec<String> {
+    diags
+        .iter()
+        .map(|diag| match &diag.code {
+            Some(NumberOrString::String(code)) => code.clone(),
+            Some(NumberOrString::Number(code)) => code.to_string(),
+            None => "no code".to_string(),
+        })
+        .collect()
+}
+
+/// Verify that the type checker emits exactly one diagnostic
+/// for each of the given codes, in this order.
+pub fn diagnostic_codes_check(input: &str, codes: &[String]) {
+    let mut build_ast = lustre_parse(input).unwrap();
+
+    let (diags, _) = build_ast.check();
+    let diags_codes = diagnostics_codes(&diags);
+    if diags_codes != codes {
+        eprintln!(
+            "{} : expected diagnostics {:?} but got {:?}\n>>{} = {:#?}",
+            ">> ERROR".red(),
+            codes,
+            diags_codes,
+            "DIAGS".blue(),
+            diags
+        );
+        panic!()
+    }
+}
+
+/// Verify that the lints of the given lustre program are exactly `names`
+pub fn lint_codes_check(input: &str, names: &[String]) {
+    let mut build_ast = lustre_parse(input).unwrap();
+    let _ = build_ast.check();
+
+    let lints = build_ast.lint();
+    let lints_names = diagnostics_codes(&lints);
+    if lints_names != names {
+        eprintln!(
+            "{} : expected lints {:?} but got {:?}\n>>{} = {:#?}",
+            ">> ERROR".red(),
+            names,
+            lints_names,
+            "LINTS".blue(),
+            lints
+        );
+        panic!()
+    }
+}
+
 pub fn error_parse(input: &str) {
     let span = LSpan::new(input);
 
@@ -1,22 +1,211 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use colored::Colorize;
+use lsp_types::{Diagnostic, DiagnosticSeverity};
+use lustre_analyzer::{
+    ast::ast::Ast, interpreter::program::CompiledProgram, parser::lustre_parser::lustre_parse,
+};
+use rustyline::{DefaultEditor, error::ReadlineError};
+use std::{
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
 
-/// Simple program to greet a person
+mod sim;
+
+/// Compiler and simulator of Lustre programs
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Args {
-    /// Name of the person to greet
-    #[arg(short, long)]
-    name: String,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Step a node interactively, type `:help` for the commands
+    Sim {
+        file: PathBuf,
+        /// Node to simulate
+        #[arg(short, long, default_value = "main")]
+        node: String,
+        /// Seed of the random inputs, taken from the clock by default
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Save a node compiled, to run it without the source
+    Compile {
+        file: PathBuf,
+        /// Node to compile
+        #[arg(short, long, default_value = "main")]
+        node: String,
+        /// Compiled program, `<node>.json` by default
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Run a compiled program on inputs read from a CSV file
+    Run {
+        program: PathBuf,
+        /// One column per input, one row per instant
+        #[arg(short, long)]
+        inputs: PathBuf,
+        /// One column per output, written to the standard output by default
+        #[arg(short, long)]
+        outputs: Option<PathBuf>,
+    },
+}
 
-    /// Number of times to greet
-    #[arg(short, long, default_value_t = 1)]
-    count: u8,
+fn main() -> ExitCode {
+    match Args::parse().command {
+        Command::Sim { file, node, seed } => simulate(file, &node, seed),
+        Command::Compile { file, node, output } => compile(file, &node, output),
+        Command::Run {
+            program,
+            inputs,
+            outputs,
+        } => run(program, inputs, outputs),
+    }
 }
 
-fn main() {
-    let args = Args::parse();
+/// Parsed and checked program of `file`, the errors are reported
+fn load(file: &Path) -> Option<Ast> {
+    let text = match std::fs::read_to_string(file) {
+        Ok(text) => text,
+        Err(error) => {
+            eprintln!("{} {}: {error}", "error:".red(), file.display());
+            return None;
+        }
+    };
+    let mut ast = match lustre_parse(&text) {
+        Ok(ast) => ast,
+        Err(diags) => {
+            report(file, &diags);
+            return None;
+        }
+    };
+    let (diags, _) = ast.check();
+    let errors: Vec<Diagnostic> = diags
+        .into_iter()
+        .filter(|diag| diag.severity == Some(DiagnosticSeverity::ERROR))
+        .collect();
+    if !errors.is_empty() {
+        report(file, &errors);
+        return None;
+    }
+    Some(ast)
+}
+
+fn fail(message: impl std::fmt::Display) -> ExitCode {
+    eprintln!("{} {message}", "error:".red());
+    ExitCode::FAILURE
+}
+
+fn simulate(file: PathBuf, node: &str, seed: Option<u64>) -> ExitCode {
+    let Some(ast) = load(&file) else {
+        return ExitCode::FAILURE;
+    };
+    let seed = seed.unwrap_or_else(|| {
+        let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH);
+        now.map(|now| now.as_nanos() as u64).unwrap_or_default()
+    });
+    let Some(mut sim) = sim::Sim::new(&ast, node, seed) else {
+        return fail(format!("no node '{node}' in {}", file.display()));
+    };
+    println!("{}", sim.header());
+
+    let Ok(mut editor) = DefaultEditor::new() else {
+        return fail("no terminal for the simulator");
+    };
+    loop {
+        match editor.readline(&sim.prompt()) {
+            Ok(line) => {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let _ = editor.add_history_entry(line.as_str());
+                match sim.command(&line) {
+                    Ok(answer) => print!("{answer}"),
+                    Err(error) => println!("{} {error}", "error:".red()),
+                }
+            }
+            Err(ReadlineError::Interrupted | ReadlineError::Eof) => return ExitCode::SUCCESS,
+            Err(error) => return fail(error),
+        }
+    }
+}
+
+fn compile(file: PathBuf, node: &str, output: Option<PathBuf>) -> ExitCode {
+    let Some(mut ast) = load(&file) else {
+        return ExitCode::FAILURE;
+    };
+    let program = match ast.compile_program(node) {
+        Ok(program) => program,
+        Err(error) => return fail(format!("{}: {error}", file.display())),
+    };
+    let output = output.unwrap_or_else(|| PathBuf::from(format!("{node}.json")));
+    let written = program
+        .to_json()
+        .map_err(|error| error.to_string())
+        .and_then(|json| std::fs::write(&output, json).map_err(|error| error.to_string()));
+    match written {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => fail(format!("{}: {error}", output.display())),
+    }
+}
+
+fn run(program: PathBuf, inputs: PathBuf, outputs: Option<PathBuf>) -> ExitCode {
+    let read = |file: &Path| {
+        std::fs::read_to_string(file).map_err(|error| format!("{}: {error}", file.display()))
+    };
+    let program = match read(&program).and_then(|json| {
+        CompiledProgram::from_json(&json).map_err(|error| format!("{}: {error}", program.display()))
+    }) {
+        Ok(program) => program,
+        Err(error) => return fail(error),
+    };
+    let function_type = program.function_type();
+    let stimulus = match read(&inputs).and_then(|csv| {
+        function_type
+            .read_stimulus(&csv)
+            .map_err(|error| format!("{}: {error}", inputs.display()))
+    }) {
+        Ok(stimulus) => stimulus,
+        Err(error) => return fail(error),
+    };
+
+    let mut node = program.node();
+    let mut values = vec![];
+    let mut code = ExitCode::SUCCESS;
+    for inputs in stimulus {
+        match node.step(inputs) {
+            Ok(step) => values.push(step),
+            Err(error) => {
+                // The outputs of the previous instants are still written
+                code = fail(error);
+                break;
+            }
+        }
+    }
+    let written = match &outputs {
+        Some(file) => std::fs::File::create(file)
+            .and_then(|mut file| function_type.write_outputs(&values, &mut file)),
+        None => function_type.write_outputs(&values, &mut std::io::stdout()),
+    };
+    match written {
+        Ok(()) => code,
+        Err(error) => fail(error),
+    }
+}
 
-    for _ in 0..args.count {
-        println!("Hello {}!", args.name);
+fn report(file: &Path, diags: &[Diagnostic]) {
+    for diag in diags {
+        let start = diag.range.start;
+        eprintln!(
+            "{} {}:{}:{}: {}",
+            "error:".red(),
+            file.display(),
+            start.line + 1,
+            start.character + 1,
+            diag.message
+        );
     }
 }
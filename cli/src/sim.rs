@@ -0,0 +1,200 @@
+use lustre_analyzer::{
+    ast::{ast::Ast, literal::Value},
+    checker::function_type::FunctionType,
+    interpreter::{random::Random, timeline::Timeline},
+    parser::span::Span,
+};
+
+/// Steps between two snapshots of the node, to go back quickly
+const CHECKPOINT_PERIOD: usize = 64;
+
+const HELP: &str = "Type the inputs of an instant separated by commas, or a command:
+  :reset           go back to the first instant
+  :back N          undo the last N steps, 1 by default
+  :watch x         show the local flow x after every step
+  :run N random    step N times with random inputs
+  :help            show this message
+";
+
+/// Simulation of a node, one instant per line typed by the user
+pub struct Sim {
+    function_type: FunctionType,
+    timeline: Timeline,
+    watched: Vec<String>,
+    random: Random,
+}
+
+impl Sim {
+    /// `None` when the program has no node `name`
+    pub fn new(ast: &Ast, name: &str, seed: u64) -> Option<Self> {
+        let function_type = ast.function_type(name)?;
+        let mut node = ast.compile_runtime(Span::new(0, 0, name.to_string()));
+        node.start_trace();
+        Some(Self {
+            function_type,
+            timeline: Timeline::new(node, CHECKPOINT_PERIOD),
+            watched: vec![],
+            random: Random::new(seed),
+        })
+    }
+
+    pub fn header(&self) -> String {
+        let flows = |flows: Vec<String>| flows.join(", ");
+        format!(
+            "node {}({}) returns ({})\n:help for the commands",
+            self.timeline.node().name(),
+            flows(
+                self.function_type
+                    .inputs
+                    .iter()
+                    .map(|(n, t)| format!("{n}: {t}"))
+                    .collect()
+            ),
+            flows(
+                self.function_type
+                    .outputs
+                    .iter()
+                    .map(|(n, t)| format!("{n}: {t}"))
+                    .collect()
+            ),
+        )
+    }
+    pub fn prompt(&self) -> String {
+        format!("#{}> ", self.timeline.steps())
+    }
+
+    /// Answer to a line typed by the user
+    pub fn command(&mut self, line: &str) -> Result<String, String> {
+        let line = line.trim();
+        let Some(command) = line.strip_prefix(':') else {
+            let inputs = self.function_type.read_inputs(line)?;
+            return self.step(inputs);
+        };
+        let words: Vec<&str> = command.split_whitespace().collect();
+        match words[..] {
+            ["reset"] => self.back(self.timeline.steps()),
+            ["back"] => self.back(1),
+            ["back", n] => self.back(count(n)?),
+            ["watch", name] => {
+                let trace = self.timeline.node().trace().unwrap();
+                if !trace.names().any(|flow| flow == name) {
+                    let names: Vec<&str> = trace.names().collect();
+                    return Err(format!(
+                        "no flow '{name}', the flows are {}",
+                        names.join(", ")
+                    ));
+                }
+                if !self.watched.iter().any(|watched| watched == name) {
+                    self.watched.push(name.to_string());
+                }
+                Ok(String::new())
+            }
+            ["run", n, "random"] => {
+                let mut answer = String::new();
+                for _ in 0..count(n)? {
+                    let inputs = self.function_type.random_inputs(&mut self.random);
+                    answer += &self.step(inputs)?;
+                }
+                Ok(answer)
+            }
+            ["help"] => Ok(HELP.to_string()),
+            _ => Err(format!("unknown command '{line}', :help for the commands")),
+        }
+    }
+
+    /// Outputs and watched flows of the instant, a failed step is undone
+    fn step(&mut self, inputs: Vec<Value>) -> Result<String, String> {
+        let instant = self.timeline.steps();
+        match self.timeline.step(inputs) {
+            Ok(outputs) => {
+                let trace = self.timeline.node().trace().unwrap();
+                let values = |names: Vec<&str>, values: Vec<Option<&Value>>| {
+                    let values: Vec<String> = names
+                        .iter()
+                        .zip(values)
+                        .map(|(name, value)| match value {
+                            Some(value) => format!("{name} = {value}"),
+                            None => format!("{name} = nil"),
+                        })
+                        .collect();
+                    values.join(", ")
+                };
+                let names: Vec<String> = self
+                    .function_type
+                    .outputs
+                    .keys()
+                    .map(|name| name.to_string())
+                    .collect();
+                let mut answer = format!(
+                    "#{instant} {}\n",
+                    values(
+                        names.iter().map(String::as_str).collect(),
+                        outputs.iter().map(Some).collect()
+                    )
+                );
+                if !self.watched.is_empty() {
+                    let watched = self.watched.iter().map(String::as_str).collect();
+                    let flows = self
+                        .watched
+                        .iter()
+                        .map(|name| trace.get(name, instant))
+                        .collect();
+                    answer += &format!("   {}\n", values(watched, flows));
+                }
+                Ok(answer)
+            }
+            Err(error) => Err(error.to_string()),
+        }
+    }
+    fn back(&mut self, n: usize) -> Result<String, String> {
+        let steps = self.timeline.steps();
+        if n > steps || !self.timeline.go_to(steps - n) {
+            return Err(format!("only {steps} steps to undo"));
+        }
+        Ok(String::new())
+    }
+}
+
+fn count(n: &str) -> Result<usize, String> {
+    n.parse()
+        .map_err(|_| format!("expected a number of steps, found '{n}'"))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::sim::Sim;
+    use lustre_analyzer::parser::lustre_parser::lustre_parse;
+
+    const PROGRAM: &str = "node main(x: int) returns (y: int);
+var
+    last: int;
+    m: int;
+let
+    last = 0 -> pre x;
+    m = 0 -> pre y;
+    y = m + x;
+tel
+";
+
+    #[test]
+    fn back_and_reset() {
+        let mut ast = lustre_parse(PROGRAM).unwrap();
+        assert_eq!(ast.check().0, vec![]);
+        let mut sim = Sim::new(&ast, "main", 1).unwrap();
+        assert_eq!(sim.command(":watch last"), Ok(String::new()));
+        assert_eq!(sim.command("1"), Ok("#0 y = 1\n   last = 0\n".to_string()));
+        assert_eq!(sim.command("2"), Ok("#1 y = 3\n   last = 1\n".to_string()));
+        assert_eq!(sim.command(":back"), Ok(String::new()));
+        assert_eq!(sim.command("5"), Ok("#1 y = 6\n   last = 1\n".to_string()));
+        assert!(sim.command("true").is_err());
+        assert_eq!(sim.command(":reset"), Ok(String::new()));
+        assert_eq!(sim.command("4"), Ok("#0 y = 4\n   last = 0\n".to_string()));
+        assert!(sim.command(":back 2").is_err());
+        assert!(sim.command(":watch z").is_err());
+        assert_eq!(
+            sim.command(":run 3 random").map(|run| run.lines().count()),
+            Ok(6)
+        );
+        assert_eq!(sim.prompt(), "#4> ");
+    }
+}
@@ -1,8 +1,12 @@
+use lsp_types::CodeAction;
+use lsp_types::CodeActionKind;
+use lsp_types::CodeActionOrCommand;
 use lsp_types::Diagnostic;
 use lsp_types::DocumentDiagnosticReport;
 use lsp_types::DocumentDiagnosticReportResult;
 use lsp_types::DocumentHighlight;
 use lsp_types::FullDocumentDiagnosticReport;
+use lsp_types::Hover;
 use lsp_types::InlayHint;
 use lsp_types::Position;
 use lsp_types::Range;
@@ -10,14 +14,20 @@ use lsp_types::RelatedFullDocumentDiagnosticReport;
 use lsp_types::SemanticTokens;
 use lsp_types::SemanticTokensResult;
 use lsp_types::TextEdit;
+use lsp_types::Uri;
+use lsp_types::WorkspaceEdit;
 use lustre_analyzer::ast::ast::Ast;
+use lustre_analyzer::checker::quick_fix::QuickFix;
 use lustre_analyzer::parser::lustre_parser::lustre_parse;
+use std::collections::HashMap;
 
 #[derive(Debug, Clone)]
 pub struct ServerState {
+    uri: Option<Uri>,
     text: String,
     parse: std::result::Result<Ast, Vec<Diagnostic>>,
     type_diag: Vec<Diagnostic>,
+    lint_diag: Vec<Diagnostic>,
     type_hint: Vec<InlayHint>,
     test_hint: Vec<InlayHint>,
     test_diag: Vec<Diagnostic>,
@@ -26,6 +36,22 @@ impl ServerState {
     pub fn text(&self) -> String {
         self.text.clone()
     }
+    pub fn set_uri(&mut self, uri: Uri) {
+        self.uri = Some(uri);
+    }
+    /// The analyzer does not know the document it checks, related
+    /// information of diagnostics point to a placeholder document that
+    /// is replaced here by the document of the server.
+    fn with_document_uri(&self, mut diags: Vec<Diagnostic>) -> Vec<Diagnostic> {
+        if let Some(uri) = &self.uri {
+            for diag in diags.iter_mut() {
+                for info in diag.related_information.iter_mut().flatten() {
+                    info.location.uri = uri.clone();
+                }
+            }
+        }
+        diags
+    }
     /// Core function that update the data concerning
     /// a given text that is Lustre code
     ///
@@ -35,6 +61,7 @@ impl ServerState {
     /// 3. propagate constant of ast
     /// 4. Type check Again with more information on Ast
     /// 5. Propagate constant again on Ast
+    /// 6. Lint the Ast when there is no error
     pub fn update_text(&mut self, s: String) {
         self.text = s;
         // 1.
@@ -44,6 +71,8 @@ impl ServerState {
             let (diags_1, type_hint_1) = ast.check();
 
             if diags_1.is_empty() {
+                // 6.
+                self.lint_diag = ast.lint();
                 eprintln!(">> Second Round");
                 // 3.
                 let (mut ast, mut test_hint_1) = ast.propagate_const();
@@ -79,12 +108,14 @@ impl ServerState {
                 // self.test_hint = test_hint_1;
             } else {
                 eprintln!("\t>> Got {} diags", diags_1.len());
+                self.lint_diag.clear();
                 self.test_hint.clear();
                 self.type_diag = diags_1;
                 self.type_hint = type_hint_1;
             }
         } else {
             self.type_diag.clear();
+            self.lint_diag.clear();
             self.test_hint.clear();
             self.type_hint.clear();
         }
@@ -123,13 +154,19 @@ impl ServerState {
             Ok(ast) => Some(ast.document_hightlight(pos)),
         }
     }
+    /// Documentation of the prelude node called under the cursor
+    pub fn hover(&self, pos: Position) -> Option<Hover> {
+        self.parse.as_ref().ok()?.hover(pos)
+    }
     pub fn diagnostic(&self) -> DocumentDiagnosticReportResult {
         let diags = match &self.parse {
             Ok(_) => {
                 if !self.test_diag.is_empty() {
                     self.test_diag.clone()
-                } else {
+                } else if !self.type_diag.is_empty() {
                     self.type_diag.clone()
+                } else {
+                    self.lint_diag.clone()
                 }
             }
             Err(d) => d.clone(),
@@ -140,11 +177,31 @@ impl ServerState {
                 related_documents: None,
                 full_document_diagnostic_report: FullDocumentDiagnosticReport {
                     result_id: None,
-                    items: diags,
+                    items: self.with_document_uri(diags),
                 },
             },
         ))
     }
+    /// Quick fixes attached by the analyzer to the given diagnostics
+    pub fn code_action(&self, diagnostics: Vec<Diagnostic>) -> Option<Vec<CodeActionOrCommand>> {
+        let uri = self.uri.clone()?;
+        let mut actions = vec![];
+        for diag in diagnostics {
+            for fix in QuickFix::from_diagnostic(&diag) {
+                actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                    title: fix.title,
+                    kind: Some(CodeActionKind::QUICKFIX),
+                    diagnostics: Some(vec![diag.clone()]),
+                    edit: Some(WorkspaceEdit {
+                        changes: Some(HashMap::from([(uri.clone(), vec![fix.edit])])),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }));
+            }
+        }
+        Some(actions)
+    }
     pub fn semantic_tokens_full(&self) -> Option<SemanticTokensResult> {
         match &self.parse {
             Ok(ast) => Some(SemanticTokensResult::Tokens(SemanticTokens {
@@ -166,9 +223,11 @@ impl ServerState {
 impl std::default::Default for ServerState {
     fn default() -> Self {
         Self {
+            uri: None,
             text: Default::default(),
             parse: Err(vec![]),
             type_diag: vec![],
+            lint_diag: vec![],
             type_hint: vec![],
             test_hint: vec![],
             test_diag: vec![],
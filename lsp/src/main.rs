@@ -2,11 +2,13 @@ use std::io::{Read, Write, stdin};
 
 use lsp_server::{Message, Response};
 use lsp_types::{
-    DiagnosticOptions, DiagnosticServerCapabilities, DidChangeTextDocumentParams,
-    DidOpenTextDocumentParams, DocumentFormattingParams, DocumentHighlightParams, InitializeParams,
-    InitializeResult, InlayHintParams, OneOf, SemanticTokenModifier, SemanticTokensFullOptions,
-    SemanticTokensLegend, SemanticTokensOptions, SemanticTokensServerCapabilities,
-    ServerCapabilities, TextDocumentSyncCapability, TextDocumentSyncKind, WorkDoneProgressOptions,
+    CodeActionParams, CodeActionProviderCapability, DiagnosticOptions,
+    DiagnosticServerCapabilities, DidChangeTextDocumentParams, DidOpenTextDocumentParams,
+    DocumentFormattingParams, DocumentHighlightParams, HoverParams, HoverProviderCapability,
+    InitializeParams, InitializeResult, InlayHintParams, OneOf, SemanticTokenModifier,
+    SemanticTokensFullOptions, SemanticTokensLegend, SemanticTokensOptions,
+    SemanticTokensServerCapabilities, ServerCapabilities, TextDocumentSyncCapability,
+    TextDocumentSyncKind, WorkDoneProgressOptions,
 };
 use lustre_analyzer::ast::token_type::TokenType;
 use lustrels::data::ServerState;
@@ -94,6 +96,25 @@ fn main() {
                         ),
                         error: None,
                     }));
+                } else if method == "textDocument/hover" {
+                    let params: HoverParams = from_value(params).unwrap();
+                    send_message(Message::Response(Response {
+                        id: request.id,
+                        result: Some(
+                            to_value(data.hover(params.text_document_position_params.position))
+                                .unwrap(),
+                        ),
+                        error: None,
+                    }));
+                } else if method == "textDocument/codeAction" {
+                    let params: CodeActionParams = from_value(params).unwrap();
+                    send_message(Message::Response(Response {
+                        id: request.id,
+                        result: Some(
+                            to_value(data.code_action(params.context.diagnostics)).unwrap(),
+                        ),
+                        error: None,
+                    }));
                 } else if method == "textDocument/formatting" {
                     let _params: DocumentFormattingParams = from_value(params).unwrap();
                     send_message(Message::Response(Response {
@@ -114,9 +135,11 @@ fn main() {
                 let params = notification.params;
                 if method == "textDocument/didOpen" {
                     let params: DidOpenTextDocumentParams = from_value(params).unwrap();
+                    data.set_uri(params.text_document.uri);
                     data.update_text(params.text_document.text);
                 } else if method == "textDocument/didChange" {
                     let params: DidChangeTextDocumentParams = from_value(params).unwrap();
+                    data.set_uri(params.text_document.uri);
                     data.update_text(params.content_changes[0].text.clone());
                 }
             }
@@ -128,6 +151,7 @@ fn initialize_result() -> InitializeResult {
     InitializeResult {
         capabilities: ServerCapabilities {
             document_formatting_provider: Some(OneOf::Left(true)),
+            code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
             text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
             // document_highlight_provider: Some(OneOf::Left(true)),
             diagnostic_provider: Some(DiagnosticServerCapabilities::Options(DiagnosticOptions {
@@ -152,6 +176,7 @@ fn initialize_result() -> InitializeResult {
                 }),
             ),
             inlay_hint_provider: Some(OneOf::Left(true)),
+            hover_provider: Some(HoverProviderCapability::Simple(true)),
             ..Default::default()
         },
         ..Default::default()